@@ -0,0 +1,130 @@
+// Time and peak heap memory of the in-process distributed key generation,
+// `generate_key_set`, on one core and on all cores.
+//
+//   cargo bench --bench dkg
+//   DKG_BENCH_SIZES=10,100 cargo bench --bench dkg
+//
+// Each size n runs once with threshold t = n / 2 + 1. The default sizes are
+// 10, 100 and 500; larger groups take minutes, as every participant checks the
+// share of every other participant against its t commitments.
+//
+// The `round 1` and `round 2` columns split the all-core run by DKG round.
+// Round 2 starts by running round 1 again for every participant, rather than
+// keeping all round-one states in memory, so `round 1` is also the cost of that
+// rebuild within `round 2`.
+
+use frost_cli::bench::Phase;
+use frost_cli::generate_key_set_with;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+/// The system allocator, keeping track of the heap in use and its peak.
+struct Tracking;
+
+static IN_USE: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Tracking {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let in_use = IN_USE.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+            PEAK.fetch_max(in_use, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        IN_USE.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Tracking = Tracking;
+
+/// One key generation: its time, the time of each DKG round and the peak heap growth in bytes.
+struct Measurement {
+    total: Duration,
+    round_one: Duration,
+    round_two: Duration,
+    peak: usize,
+}
+
+/// Runs one key generation and measures it.
+fn measure(t: u32, n: u32) -> Measurement {
+    let baseline = IN_USE.load(Ordering::Relaxed);
+    PEAK.store(baseline, Ordering::Relaxed);
+    let start = Instant::now();
+    let mut last = start;
+    let (mut round_one, mut round_two) = (Duration::ZERO, Duration::ZERO);
+    let frost_keys = generate_key_set_with(t, n, &mut |phase| {
+        let now = Instant::now();
+        match phase {
+            Phase::DkgRoundOne => round_one = now - last,
+            Phase::DkgRoundTwo => round_two = now - last,
+            _ => {}
+        }
+        last = now;
+    })
+    .expect("Key generation failed");
+    let total = start.elapsed();
+    drop(frost_keys);
+    Measurement {
+        total,
+        round_one,
+        round_two,
+        peak: PEAK.load(Ordering::Relaxed) - baseline,
+    }
+}
+
+fn main() {
+    let sizes: Vec<u32> = std::env::var("DKG_BENCH_SIZES")
+        .unwrap_or_else(|_| "10,100,500".to_string())
+        .split(',')
+        .map(|size| {
+            size.trim()
+                .parse()
+                .expect("DKG_BENCH_SIZES must list numbers")
+        })
+        .collect();
+    let cores = rayon::current_num_threads();
+    let single = rayon::ThreadPoolBuilder::new()
+        .num_threads(1)
+        .build()
+        .expect("Failed to start a single thread pool");
+
+    let mut rows = Vec::new();
+    for n in sizes {
+        let t = n / 2 + 1;
+        let one_core = single.install(|| measure(t, n)).total;
+        rows.push((t, n, one_core, measure(t, n)));
+    }
+
+    println!();
+    println!(
+        "{:>6} {:>6} {:>14} {:>14} {:>9} {:>12} {:>12} {:>12}",
+        "t",
+        "n",
+        "1 core",
+        format!("{} cores", cores),
+        "speedup",
+        "round 1",
+        "round 2",
+        "peak heap"
+    );
+    for (t, n, one_core, all_cores) in rows {
+        println!(
+            "{:>6} {:>6} {:>13.3}s {:>13.3}s {:>8.1}x {:>11.3}s {:>11.3}s {:>9.1} MiB",
+            t,
+            n,
+            one_core.as_secs_f64(),
+            all_cores.total.as_secs_f64(),
+            one_core.as_secs_f64() / all_cores.total.as_secs_f64(),
+            all_cores.round_one.as_secs_f64(),
+            all_cores.round_two.as_secs_f64(),
+            all_cores.peak as f64 / (1024.0 * 1024.0)
+        );
+    }
+}
@@ -0,0 +1,93 @@
+// Criterion benchmarks of every phase of key generation, signing and
+// verification, at several (t, n).
+//
+//   cargo bench --bench phases
+//   cargo bench --bench phases -- "sign/partial signatures"
+//
+// Each benchmark is named `<operation>/<phase>/t=<t>,n=<n>`, where the phases
+// are those reported by `frost-cli bench`; the `total` phase is the whole
+// operation. A phase is timed inside full runs of its operation, so a run of
+// a short phase takes about as long as one of the whole operation.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use frost_cli::bench::{time_generate, time_sign, time_verify, Lap};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// The (t, n) every phase is measured at.
+const SIZES: [(u32, u32); 3] = [(2, 3), (3, 5), (7, 10)];
+
+const MESSAGE: &[u8] = b"frost-cli bench message";
+
+/// Returns the time of `phase` in a run, or of the whole run for `total`.
+fn phase_time(laps: Vec<Lap>, phase: &str) -> Duration {
+    laps.into_iter()
+        .filter(|lap| phase == "total" || lap.phase == phase)
+        .map(|lap| lap.time)
+        .sum()
+}
+
+/// Benchmarks every phase of `run`, whose phases are taken from a first run.
+fn bench_phases(c: &mut Criterion, operation: &str, run: impl Fn(u32, u32) -> Vec<Lap>) {
+    let mut group = c.benchmark_group(operation);
+    group
+        .sample_size(10)
+        .warm_up_time(Duration::from_millis(500))
+        .measurement_time(Duration::from_secs(2));
+    for (t, n) in SIZES {
+        let mut phases: Vec<&str> = run(t, n).iter().map(|lap| lap.phase).collect();
+        phases.push("total");
+        for phase in phases {
+            group.bench_function(BenchmarkId::new(phase, format!("t={},n={}", t, n)), |b| {
+                b.iter_custom(|iters| (0..iters).map(|_| phase_time(run(t, n), phase)).sum())
+            });
+        }
+    }
+    group.finish();
+}
+
+/// Returns the key and signature files of the benchmarks at (t, n).
+fn files(t: u32, n: u32) -> (String, String) {
+    let dir: PathBuf = std::env::temp_dir().join("frost-cli-phases");
+    (
+        dir.join(format!("keys_{}_{}.json", t, n))
+            .to_string_lossy()
+            .into_owned(),
+        dir.join(format!("signature_{}_{}.json", t, n))
+            .to_string_lossy()
+            .into_owned(),
+    )
+}
+
+fn generate(c: &mut Criterion) {
+    bench_phases(c, "generate", |t, n| {
+        time_generate(t, n, &files(t, n).0).expect("Key generation failed")
+    });
+}
+
+fn sign(c: &mut Criterion) {
+    for (t, n) in SIZES {
+        time_generate(t, n, &files(t, n).0).expect("Key generation failed");
+    }
+    bench_phases(c, "sign", |t, n| {
+        let (key_file, signature_file) = files(t, n);
+        let signers: Vec<u32> = (0..t).collect();
+        time_sign(MESSAGE, &signers, &key_file, &signature_file).expect("Signing failed")
+    });
+}
+
+fn verify(c: &mut Criterion) {
+    for (t, n) in SIZES {
+        let (key_file, signature_file) = files(t, n);
+        time_generate(t, n, &key_file).expect("Key generation failed");
+        let signers: Vec<u32> = (0..t).collect();
+        time_sign(MESSAGE, &signers, &key_file, &signature_file).expect("Signing failed");
+    }
+    bench_phases(c, "verify", |t, n| {
+        let (key_file, signature_file) = files(t, n);
+        time_verify(MESSAGE, &key_file, &signature_file).expect("Verification failed")
+    });
+}
+
+criterion_group!(benches, generate, sign, verify);
+criterion_main!(benches);
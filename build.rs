@@ -0,0 +1,17 @@
+//! Writes the C header of the `ffi` feature to `$OUT_DIR/frost_cli.h`.
+//!
+//! The build never writes into the source tree; `examples/write_header.rs`
+//! copies the header to `include/frost_cli.h` when it is to be updated.
+
+fn main() {
+    #[cfg(feature = "ffi")]
+    {
+        println!("cargo:rerun-if-changed=src/ffi.rs");
+        println!("cargo:rerun-if-changed=cbindgen.toml");
+        let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+        let out_dir = std::env::var("OUT_DIR").unwrap();
+        cbindgen::generate(&crate_dir)
+            .expect("Unable to generate the C header")
+            .write_to_file(std::path::Path::new(&out_dir).join("frost_cli.h"));
+    }
+}
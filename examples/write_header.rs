@@ -0,0 +1,18 @@
+// Copies the C header that build.rs generates for the `ffi` feature into
+// `include/frost_cli.h`, where C callers include it from:
+//
+//   cargo run --example write_header --features ffi
+//
+// The build itself only writes the header to OUT_DIR, so that building never
+// modifies the source tree. Run this after changing the C API in src/ffi.rs.
+
+use std::path::Path;
+
+/// The header generated by build.rs for this build.
+const HEADER: &str = include_str!(concat!(env!("OUT_DIR"), "/frost_cli.h"));
+
+fn main() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("include/frost_cli.h");
+    std::fs::write(&path, HEADER).expect("Failed to write the C header");
+    println!("C header saved to: {}", path.display());
+}
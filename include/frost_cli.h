@@ -0,0 +1,184 @@
+/* C API of frost_cli. Generated by cbindgen from src/ffi.rs; do not edit. */
+
+#ifndef FROST_CLI_H
+#define FROST_CLI_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Result codes of every function of the C API.
+typedef enum FrostStatus {
+  // The call succeeded.
+  FROST_STATUS_OK = 0,
+  // A required pointer was null.
+  FROST_STATUS_NULL_POINTER = 1,
+  // An argument is out of range, e.g. a threshold above the number of participants.
+  FROST_STATUS_INVALID_ARGUMENT = 2,
+  // An input does not decode or fails its integrity checks.
+  FROST_STATUS_MALFORMED = 3,
+  // Signing or aggregation failed, e.g. because of an invalid partial signature.
+  FROST_STATUS_SIGNING_FAILED = 4,
+  // The signature does not verify.
+  FROST_STATUS_INVALID_SIGNATURE = 5,
+  // The library panicked; this is a bug.
+  FROST_STATUS_PANIC = 6,
+} FrostStatus;
+
+// An opaque handle to a key set with all private shares.
+typedef struct FrostKeySet FrostKeySet;
+
+// An opaque handle to the secret nonces of one signer for one signature.
+typedef struct FrostNonces FrostNonces;
+
+// A byte string owned by the library.
+typedef struct FrostBuffer {
+  uint8_t *data;
+  size_t len;
+} FrostBuffer;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Returns the message of the last error on this thread.
+//
+// The string stays valid until the next failing call on the same thread.
+const char *frost_last_error(void);
+
+// Releases a buffer returned by the library, zeroizing its contents.
+//
+// # Safety
+// `buffer` must have been returned by this library and not released before.
+void frost_buffer_free(struct FrostBuffer buffer);
+
+// Generates a key set of `n` shares with threshold `t` by distributed key generation.
+//
+// # Safety
+// `out` must point to writable memory for a handle.
+enum FrostStatus frost_generate(uint32_t t, uint32_t n, struct FrostKeySet **out);
+
+// Loads a key set from the contents of a key file.
+//
+// # Safety
+// `data` must point to `len` readable bytes and `out` to writable memory for a handle.
+enum FrostStatus frost_key_set_from_json(const uint8_t *data, size_t len, struct FrostKeySet **out);
+
+// Writes a key set in the format of a key file.
+//
+// # Safety
+// `keys` must be a live handle and `out` must point to writable memory for a buffer.
+enum FrostStatus frost_key_set_to_json(const struct FrostKeySet *keys, struct FrostBuffer *out);
+
+// Releases a key set handle. Null is ignored.
+//
+// # Safety
+// `keys` must be null or a handle that has not been released before.
+void frost_key_set_free(struct FrostKeySet *keys);
+
+// Copies the 32-byte group public key of a key set to `group_key`.
+//
+// # Safety
+// `keys` must be a live handle and `group_key` must point to 32 writable bytes.
+enum FrostStatus frost_key_set_group_key(const struct FrostKeySet *keys, uint8_t *group_key);
+
+// Writes the share of participant `index` (1 to n) in the format of a share file.
+//
+// # Safety
+// `keys` must be a live handle and `out` must point to writable memory for a buffer.
+enum FrostStatus frost_key_set_share(const struct FrostKeySet *keys,
+                                     uint32_t index,
+                                     struct FrostBuffer *out);
+
+// Writes the public package of a key set, as needed by [`frost_aggregate`].
+//
+// # Safety
+// `keys` must be a live handle and `out` must point to writable memory for a buffer.
+enum FrostStatus frost_key_set_public_package(const struct FrostKeySet *keys,
+                                              struct FrostBuffer *out);
+
+// Signs a message with the given signers of a key set in one call.
+//
+// `signers` are positions in the key set (0 to n - 1), as taken by the CLI's `--signers`.
+//
+// # Safety
+// `keys` must be a live handle, `signers` must point to `signers_len` values,
+// `message` to `message_len` bytes and `signature` to 64 writable bytes.
+enum FrostStatus frost_sign(const struct FrostKeySet *keys,
+                            const uint32_t *signers,
+                            size_t signers_len,
+                            const uint8_t *message,
+                            size_t message_len,
+                            uint8_t *signature);
+
+// First signing round: generates fresh nonces for the holder of a share.
+//
+// `commitment` receives the JSON commitment to send to the aggregator.
+//
+// # Safety
+// `share` must point to `share_len` bytes, `nonces` to writable memory for a
+// handle and `commitment` to writable memory for a buffer.
+enum FrostStatus frost_commit(const uint8_t *share,
+                              size_t share_len,
+                              struct FrostNonces **nonces,
+                              struct FrostBuffer *commitment);
+
+// Releases a nonces handle that will not sign. Null is ignored.
+//
+// # Safety
+// `nonces` must be null or a handle that has been neither released nor passed to [`frost_sign_share`].
+void frost_nonces_free(struct FrostNonces *nonces);
+
+// Second signing round: computes the partial signature of the holder of a share.
+//
+// The nonces handle is always consumed, even on error, so it can never sign twice.
+// `commitments` is a JSON array with the commitments of all signers.
+//
+// # Safety
+// The pointer and length pairs must describe readable memory, `nonces` must be a
+// handle from [`frost_commit`] that was not consumed before, and `partial_signature`
+// must point to writable memory for a buffer.
+enum FrostStatus frost_sign_share(const uint8_t *share,
+                                  size_t share_len,
+                                  struct FrostNonces *nonces,
+                                  const uint8_t *message,
+                                  size_t message_len,
+                                  const uint8_t *commitments,
+                                  size_t commitments_len,
+                                  struct FrostBuffer *partial_signature);
+
+// Checks the partial signatures of all signers and combines them into a 64-byte signature.
+//
+// `commitments` and `partial_signatures` are JSON arrays; `public_package` is a public package file.
+//
+// # Safety
+// The pointer and length pairs must describe readable memory and `signature`
+// must point to 64 writable bytes.
+enum FrostStatus frost_aggregate(const uint8_t *public_package,
+                                 size_t public_package_len,
+                                 const uint8_t *message,
+                                 size_t message_len,
+                                 const uint8_t *commitments,
+                                 size_t commitments_len,
+                                 const uint8_t *partial_signatures,
+                                 size_t partial_signatures_len,
+                                 uint8_t *signature);
+
+// Verifies a 64-byte signature against a 32-byte group public key.
+//
+// Returns `FROST_STATUS_INVALID_SIGNATURE` if the signature does not verify.
+//
+// # Safety
+// `group_key` must point to 32 bytes, `message` to `message_len` bytes and `signature` to 64 bytes.
+enum FrostStatus frost_verify(const uint8_t *group_key,
+                              const uint8_t *message,
+                              size_t message_len,
+                              const uint8_t *signature);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* FROST_CLI_H */
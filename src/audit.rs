@@ -0,0 +1,217 @@
+//! Append-only, hash-chained audit log of signing operations and key generations.
+//!
+//! The log is a JSON Lines file with one [`AuditEntry`] per operation. Every entry
+//! carries a sequence number, the hash of the previous entry and its own hash over
+//! all of its fields, so editing, reordering or removing an entry breaks the chain.
+//! A small head file next to the log (`<log>.head`) records the number of entries
+//! and the hash of the last one, so that cutting entries off the end of the log is
+//! detected too. [`verify_log`] checks both, and [`append`] refuses to extend a
+//! log whose head file is missing rather than start a new chain after it.
+//!
+//! Appends take an exclusive lock on the log, so concurrent runs do not interleave.
+
+use crate::files;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+
+/// The `prev_hash` of the first entry.
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// What an operation reports to the log.
+#[derive(Clone, Debug, Default)]
+pub struct AuditRecord {
+    /// The operation, e.g. `generate` or `sign`.
+    pub operation: String,
+    /// Fingerprint of the group key involved, if known.
+    pub key_fingerprint: Option<String>,
+    /// SHA-256 of the signed message, in hex. Batches list one hash per message.
+    pub message_hashes: Vec<String>,
+    pub context: Option<String>,
+    /// Participant indices (1 to n) that took part.
+    pub signers: Vec<u32>,
+    /// `ok`, or `error: <reason>`.
+    pub outcome: String,
+}
+
+/// One line of the audit log.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub seq: u64,
+    /// Unix time, in seconds.
+    pub timestamp: u64,
+    pub operation: String,
+    pub key_fingerprint: Option<String>,
+    pub message_hashes: Vec<String>,
+    pub context: Option<String>,
+    pub signers: Vec<u32>,
+    pub outcome: String,
+    pub prev_hash: String,
+    pub hash: String,
+}
+
+/// The head file: the length of the log and the hash of its last entry.
+#[derive(Debug, Serialize, Deserialize)]
+struct AuditHead {
+    entries: u64,
+    hash: String,
+}
+
+impl AuditEntry {
+    /// Computes the entry's hash over every field except the hash itself.
+    fn compute_hash(&self) -> String {
+        let mut unhashed = self.clone();
+        unhashed.hash = String::new();
+        let mut h = Sha256::new();
+        h.update(b"frost-cli audit entry");
+        h.update(serde_json::to_vec(&unhashed).expect("audit entries always serialize"));
+        hex::encode(h.finalize())
+    }
+}
+
+/// Returns the SHA-256 of a message, in hex, as recorded in the log.
+pub fn message_hash(message: &[u8]) -> String {
+    hex::encode(Sha256::digest(message))
+}
+
+/// Returns the outcome recorded for the result of an operation.
+pub fn outcome<T>(result: &Result<T, Box<dyn std::error::Error>>) -> String {
+    match result {
+        Ok(_) => "ok".to_string(),
+        Err(err) => format!("error: {}", err),
+    }
+}
+
+fn head_path(log_file: &str) -> String {
+    format!("{}.head", log_file)
+}
+
+/// Appends a record to the log, creating the log if needed, and returns the new entry.
+///
+/// # Errors
+/// Returns an error if the log cannot be locked, read or written, if its last
+/// entry does not match the head file, or if the log has entries but no head file.
+pub fn append(
+    log_file: &str,
+    record: AuditRecord,
+) -> Result<AuditEntry, Box<dyn std::error::Error>> {
+    if let Some(parent) = Path::new(log_file).parent() {
+        if !parent.as_os_str().is_empty() {
+            fs::create_dir_all(parent)?;
+        }
+    }
+    let mut log = OpenOptions::new()
+        .create(true)
+        .read(true)
+        .append(true)
+        .open(log_file)?;
+    log.lock()?;
+
+    let head = read_head(log_file)?;
+    let (seq, prev_hash) = match head {
+        Some(head) => (head.entries, head.hash),
+        None if log.metadata()?.len() > 0 => {
+            return Err(format!(
+                "The head file {} is missing, but the log has entries; restore the head file \
+                 before appending to {}",
+                head_path(log_file),
+                log_file
+            )
+            .into())
+        }
+        None => (0, GENESIS_HASH.to_string()),
+    };
+    let mut entry = AuditEntry {
+        seq,
+        timestamp: crate::proposal::now(),
+        operation: record.operation,
+        key_fingerprint: record.key_fingerprint,
+        message_hashes: record.message_hashes,
+        context: record.context,
+        signers: record.signers,
+        outcome: record.outcome,
+        prev_hash,
+        hash: String::new(),
+    };
+    entry.hash = entry.compute_hash();
+
+    writeln!(log, "{}", serde_json::to_string(&entry)?)?;
+    log.sync_data()?;
+    let head = AuditHead {
+        entries: seq + 1,
+        hash: entry.hash.clone(),
+    };
+    files::write_atomic(
+        Path::new(&head_path(log_file)),
+        serde_json::to_string(&head)?.as_bytes(),
+        files::PUBLIC_MODE,
+        true,
+    )?;
+    log.unlock()?;
+    Ok(entry)
+}
+
+fn read_head(log_file: &str) -> Result<Option<AuditHead>, Box<dyn std::error::Error>> {
+    match fs::read_to_string(head_path(log_file)) {
+        Ok(contents) => Ok(Some(serde_json::from_str(&contents).map_err(|err| {
+            format!(
+                "Audit head file {} is malformed: {}",
+                head_path(log_file),
+                err
+            )
+        })?)),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Checks the hash chain of a log and returns its number of entries.
+///
+/// # Errors
+/// Returns an error describing the first entry that was edited, reordered or
+/// removed, or a log that was truncated.
+pub fn verify_log(log_file: &str) -> Result<u64, Box<dyn std::error::Error>> {
+    let file = File::open(log_file)?;
+    let mut expected_prev = GENESIS_HASH.to_string();
+    let mut entries = 0u64;
+    for (line_number, line) in BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        let line_number = line_number + 1;
+        let entry: AuditEntry = serde_json::from_str(&line)
+            .map_err(|err| format!("Line {} is not a valid entry: {}", line_number, err))?;
+        if entry.seq != entries {
+            return Err(format!(
+                "Line {} has sequence number {} instead of {}: entries were removed or reordered",
+                line_number, entry.seq, entries
+            )
+            .into());
+        }
+        if entry.prev_hash != expected_prev {
+            return Err(format!(
+                "Line {} does not chain to the entry before it: entries were removed or reordered",
+                line_number
+            )
+            .into());
+        }
+        if entry.hash != entry.compute_hash() {
+            return Err(format!("Line {} was edited: its hash does not match", line_number).into());
+        }
+        expected_prev = entry.hash;
+        entries += 1;
+    }
+
+    match read_head(log_file)? {
+        Some(head) if head.entries != entries || head.hash != expected_prev => Err(format!(
+            "The log does not match its head file (the log has {} entries, the head records {}): \
+             the log was truncated or replaced",
+            entries, head.entries
+        )
+        .into()),
+        None if entries > 0 => {
+            Err(format!("The head file {} is missing", head_path(log_file)).into())
+        }
+        _ => Ok(entries),
+    }
+}
@@ -0,0 +1,311 @@
+//! Timings of key generation, signing and verification, phase by phase.
+//!
+//! [`time_generate`], [`time_sign`] and [`time_verify`] run the steps of
+//! [`generate_keys`](crate::generate_keys), [`sign_message`](crate::sign_message)
+//! and [`validate_signature`](crate::validate_signature) and return the time
+//! spent in each phase. [`run_bench`] repeats them for the `bench` command;
+//! the criterion benchmarks in `benches/phases.rs` measure every phase on its own.
+
+use crate::policy::DEFAULT_CONTEXT;
+use crate::{files, generate_key_set_with, load_keys, threshold_sign_with, verify_signature};
+use std::fmt;
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// A phase of the in-memory key generation or signing, reported by
+/// [`generate_key_set_with`] and [`threshold_sign_with`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Phase {
+    /// Every participant draws its polynomial and proof of secret key.
+    Participants,
+    /// The proofs of secret key are verified.
+    Proofs,
+    /// Every participant deals its secret shares.
+    DkgRoundOne,
+    /// Every participant checks the shares it received and derives its key.
+    DkgRoundTwo,
+    /// The group commitments and the key file checksum are computed.
+    GroupCommitments,
+    /// The signers' secret keys are rebuilt from the key file.
+    SignerKeys,
+    /// Every signer generates its commitment.
+    Commitments,
+    /// Every signer produces its partial signature.
+    PartialSignatures,
+    /// The partial signatures are checked and aggregated.
+    Aggregation,
+}
+
+impl Phase {
+    /// Returns the name the phase is reported under.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Phase::Participants => "participants",
+            Phase::Proofs => "proofs of secret key",
+            Phase::DkgRoundOne => "dkg round 1",
+            Phase::DkgRoundTwo => "dkg round 2",
+            Phase::GroupCommitments => "group commitments",
+            Phase::SignerKeys => "signer keys",
+            Phase::Commitments => "commitments",
+            Phase::PartialSignatures => "partial signatures",
+            Phase::Aggregation => "aggregation",
+        }
+    }
+}
+
+/// The time spent in one phase of a run.
+#[derive(Clone, Debug)]
+pub struct Lap {
+    pub phase: &'static str,
+    pub time: Duration,
+}
+
+/// Splits a run into laps, each ending when its phase completes.
+struct Stopwatch {
+    last: Instant,
+    laps: Vec<Lap>,
+}
+
+impl Stopwatch {
+    fn new() -> Self {
+        Stopwatch {
+            last: Instant::now(),
+            laps: Vec::new(),
+        }
+    }
+
+    fn lap(&mut self, phase: &'static str) {
+        let now = Instant::now();
+        self.laps.push(Lap {
+            phase,
+            time: now - self.last,
+        });
+        self.last = now;
+    }
+}
+
+/// Generates a `t`-of-`n` key set like [`generate_keys`](crate::generate_keys)
+/// and returns the time of each phase.
+///
+/// # Errors
+/// Returns an error if key generation or writing `key_file` fails.
+pub fn time_generate(
+    t: u32,
+    n: u32,
+    key_file: &str,
+) -> Result<Vec<Lap>, Box<dyn std::error::Error>> {
+    let mut watch = Stopwatch::new();
+    let frost_keys = generate_key_set_with(t, n, &mut |phase| watch.lap(phase.name()))?;
+    files::write_secret_json(key_file, &frost_keys, true)?;
+    watch.lap("save key file");
+    Ok(watch.laps)
+}
+
+/// Signs `message` with the signers at the given positions in `key_file` like
+/// [`sign_message`](crate::sign_message) and returns the time of each phase.
+///
+/// # Errors
+/// Returns an error if loading the keys, signing or writing `signature_file` fails.
+pub fn time_sign(
+    message: &[u8],
+    signers: &[u32],
+    key_file: &str,
+    signature_file: &str,
+) -> Result<Vec<Lap>, Box<dyn std::error::Error>> {
+    let mut watch = Stopwatch::new();
+    let frost_keys = load_keys(key_file)?;
+    watch.lap("load key file");
+    let n = frost_keys.private_shares.len() as u32;
+    let signature = threshold_sign_with(
+        &frost_keys,
+        message,
+        DEFAULT_CONTEXT,
+        signers,
+        n,
+        &mut |phase| watch.lap(phase.name()),
+    )?;
+    files::write_json(signature_file, &signature.to_vec())?;
+    watch.lap("save signature");
+    Ok(watch.laps)
+}
+
+/// Verifies the signature in `signature_file` like
+/// [`validate_signature`](crate::validate_signature) and returns the time of each phase.
+///
+/// # Errors
+/// Returns an error if a file cannot be loaded or the signature is invalid.
+pub fn time_verify(
+    message: &[u8],
+    key_file: &str,
+    signature_file: &str,
+) -> Result<Vec<Lap>, Box<dyn std::error::Error>> {
+    let mut watch = Stopwatch::new();
+    let frost_keys = load_keys(key_file)?;
+    watch.lap("load key file");
+    let signature: Vec<u8> = serde_json::from_reader(BufReader::new(File::open(signature_file)?))?;
+    let signature: [u8; 64] = signature
+        .try_into()
+        .map_err(|_| "Invalid length for threshold signature")?;
+    watch.lap("load signature");
+    verify_signature(&frost_keys.group_key, message, &signature)?;
+    watch.lap("verification");
+    Ok(watch.laps)
+}
+
+/// The times of one phase over all iterations of a benchmark.
+#[derive(Clone, Debug)]
+pub struct PhaseTiming {
+    /// `generate`, `sign` or `verify`.
+    pub operation: &'static str,
+    pub phase: &'static str,
+    pub samples: Vec<Duration>,
+}
+
+impl PhaseTiming {
+    pub fn mean(&self) -> Duration {
+        self.samples.iter().sum::<Duration>() / self.samples.len().max(1) as u32
+    }
+
+    pub fn min(&self) -> Duration {
+        self.samples.iter().min().copied().unwrap_or_default()
+    }
+
+    pub fn max(&self) -> Duration {
+        self.samples.iter().max().copied().unwrap_or_default()
+    }
+}
+
+/// The result of [`run_bench`], printed as a table by the `bench` command.
+#[derive(Clone, Debug)]
+pub struct BenchReport {
+    pub t: u32,
+    pub n: u32,
+    pub iterations: u32,
+    /// Every phase of generate, sign and verify, in the order they run.
+    pub phases: Vec<PhaseTiming>,
+}
+
+impl BenchReport {
+    /// Returns the mean time of a whole operation.
+    pub fn total(&self, operation: &str) -> Duration {
+        self.phases
+            .iter()
+            .filter(|timing| timing.operation == operation)
+            .map(PhaseTiming::mean)
+            .sum()
+    }
+
+    fn record(&mut self, operation: &'static str, laps: Vec<Lap>) {
+        for lap in laps {
+            match self
+                .phases
+                .iter_mut()
+                .find(|timing| timing.operation == operation && timing.phase == lap.phase)
+            {
+                Some(timing) => timing.samples.push(lap.time),
+                None => self.phases.push(PhaseTiming {
+                    operation,
+                    phase: lap.phase,
+                    samples: vec![lap.time],
+                }),
+            }
+        }
+    }
+}
+
+/// Formats a duration in milliseconds.
+fn millis(duration: Duration) -> String {
+    format!("{:.3} ms", duration.as_secs_f64() * 1000.0)
+}
+
+impl fmt::Display for BenchReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "Timings for t = {}, n = {} over {} iterations:",
+            self.t, self.n, self.iterations
+        )?;
+        writeln!(
+            f,
+            "{:<10} {:<22} {:>13} {:>13} {:>13}",
+            "operation", "phase", "mean", "min", "max"
+        )?;
+        for (position, timing) in self.phases.iter().enumerate() {
+            writeln!(
+                f,
+                "{:<10} {:<22} {:>13} {:>13} {:>13}",
+                timing.operation,
+                timing.phase,
+                millis(timing.mean()),
+                millis(timing.min()),
+                millis(timing.max())
+            )?;
+            let last = self
+                .phases
+                .get(position + 1)
+                .is_none_or(|next| next.operation != timing.operation);
+            if last {
+                writeln!(
+                    f,
+                    "{:<10} {:<22} {:>13}",
+                    timing.operation,
+                    "total",
+                    millis(self.total(timing.operation))
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Generates a `t`-of-`n` key set, signs with the first `t` participants and
+/// verifies the signature, `iterations` times, and returns the time of every phase.
+///
+/// The key and signature files are written to a scratch directory that is
+/// removed afterwards, so file phases include the cost of the disk.
+///
+/// # Errors
+/// Returns an error if `iterations` is zero or any run fails.
+pub fn run_bench(
+    t: u32,
+    n: u32,
+    iterations: u32,
+) -> Result<BenchReport, Box<dyn std::error::Error>> {
+    if iterations == 0 {
+        return Err("At least one iteration is required".into());
+    }
+    let dir = std::env::temp_dir().join(format!("frost-cli-bench-{}", std::process::id()));
+    let result = bench_in(&dir, t, n, iterations);
+    let _ = fs::remove_dir_all(&dir);
+    result
+}
+
+fn bench_in(
+    dir: &Path,
+    t: u32,
+    n: u32,
+    iterations: u32,
+) -> Result<BenchReport, Box<dyn std::error::Error>> {
+    let key_file = dir.join("frost_keys.json").to_string_lossy().into_owned();
+    let signature_file = dir.join("signature.json").to_string_lossy().into_owned();
+    let message = b"frost-cli bench message";
+    let signers: Vec<u32> = (0..t).collect();
+
+    let mut report = BenchReport {
+        t,
+        n,
+        iterations,
+        phases: Vec::new(),
+    };
+    for _ in 0..iterations {
+        report.record("generate", time_generate(t, n, &key_file)?);
+        report.record(
+            "sign",
+            time_sign(message, &signers, &key_file, &signature_file)?,
+        );
+        report.record("verify", time_verify(message, &key_file, &signature_file)?);
+    }
+    Ok(report)
+}
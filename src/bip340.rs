@@ -0,0 +1,179 @@
+//! BIP-340 Schnorr signatures and Taproot tweaks for `secp256k1-tr` key sets.
+//!
+//! Key sets of the [`Secp256k1Tr`] ciphersuite always have a group key with an
+//! even y coordinate, so its 32-byte x coordinate is the BIP-340 x-only public
+//! key, and their threshold signatures are BIP-340 signatures.
+//!
+//! For a Taproot key-path spend the signature must verify against the output key
+//! `Q = P + t * G`, where `P` is the internal key and
+//! `t = tagged_hash("TapTweak", x(P) || merkle_root)` as in BIP-341. Adding `t` to
+//! every share adds it to the group secret, so [`tweak_keys`] turns a key set for
+//! `P` into one for `Q`, negated if needed to keep the y coordinate even. The
+//! tweaked key file then signs with the usual `sign` command.
+
+use crate::ciphersuite::{bip340_tagged_hash, lift_x, x_only, Ciphersuite, Secp256k1Tr, SuiteId};
+use crate::files;
+use crate::rfc9591::{self, SuiteKeys};
+use crate::{load_key_file, KeyFile};
+use k256::elliptic_curve::PrimeField;
+use k256::{FieldBytes, ProjectivePoint, Scalar};
+
+/// Returns the Taproot tweak of internal key `internal_key` committing to an optional script tree.
+///
+/// # Errors
+/// Returns an error in the negligible case that the tweak is not below the group order.
+pub fn taproot_tweak(
+    internal_key: &[u8; 32],
+    merkle_root: Option<&[u8; 32]>,
+) -> Result<Scalar, Box<dyn std::error::Error>> {
+    let mut input = internal_key.to_vec();
+    if let Some(merkle_root) = merkle_root {
+        input.extend(merkle_root);
+    }
+    let hash = bip340_tagged_hash("TapTweak", &input);
+    Option::from(Scalar::from_repr(FieldBytes::from(hash)))
+        .ok_or_else(|| "Taproot tweak is not below the group order".into())
+}
+
+/// Returns the x-only Taproot output key of an x-only internal key.
+pub fn taproot_output_key(
+    internal_key: &[u8; 32],
+    merkle_root: Option<&[u8; 32]>,
+) -> Result<[u8; 32], Box<dyn std::error::Error>> {
+    let point = lift_x(internal_key).ok_or("Internal key is not a valid x-only key")?;
+    let tweak = taproot_tweak(internal_key, merkle_root)?;
+    let output = point + ProjectivePoint::GENERATOR * tweak;
+    if output == ProjectivePoint::IDENTITY {
+        return Err("Taproot output key is the point at infinity".into());
+    }
+    Ok(x_only(&output))
+}
+
+/// Returns the x-only public key of a `secp256k1-tr` key set.
+///
+/// # Errors
+/// Returns an error if the keys belong to another ciphersuite.
+pub fn x_only_key(keys: &SuiteKeys) -> Result<[u8; 32], Box<dyn std::error::Error>> {
+    let suite = keys.suite()?;
+    if suite != SuiteId::Secp256k1Tr {
+        return Err(format!(
+            "Keys of the {} ciphersuite are not BIP-340 keys; generate them with --ciphersuite secp256k1-tr",
+            suite
+        )
+        .into());
+    }
+    let group_key =
+        Secp256k1Tr::deserialize_element(&keys.group_key).ok_or("Invalid group public key")?;
+    Ok(x_only(&group_key))
+}
+
+/// Applies the Taproot tweak to a `secp256k1-tr` key set, returning the key set of the output key.
+///
+/// # Errors
+/// Returns an error if the keys belong to another ciphersuite or do not decode.
+pub fn tweak_keys(
+    keys: &SuiteKeys,
+    merkle_root: Option<&[u8; 32]>,
+) -> Result<SuiteKeys, Box<dyn std::error::Error>> {
+    let tweak = taproot_tweak(&x_only_key(keys)?, merkle_root)?;
+    let tweak_point = ProjectivePoint::GENERATOR * tweak;
+
+    let mut commitments = keys
+        .commitments
+        .iter()
+        .map(|bytes| Secp256k1Tr::deserialize_element(bytes))
+        .collect::<Option<Vec<_>>>()
+        .ok_or("Invalid commitment")?;
+    let mut shares = keys
+        .private_shares
+        .iter()
+        .map(|(bytes, index)| Some((Secp256k1Tr::deserialize_scalar(bytes)? + tweak, *index)))
+        .collect::<Option<Vec<_>>>()
+        .ok_or("Invalid private key bytes")?;
+    let first = commitments
+        .first_mut()
+        .ok_or("No commitments in the key file")?;
+    *first += tweak_point;
+
+    let mut group_key = commitments[0];
+    if group_key == ProjectivePoint::IDENTITY {
+        return Err("Taproot output key is the point at infinity".into());
+    }
+    if Secp256k1Tr::needs_negation(&group_key) {
+        group_key = -group_key;
+        shares.iter_mut().for_each(|(share, _)| *share = -*share);
+        commitments
+            .iter_mut()
+            .for_each(|commitment| *commitment = -*commitment);
+    }
+
+    let mut tweaked = SuiteKeys {
+        ciphersuite: keys.ciphersuite.clone(),
+        group_key: Secp256k1Tr::serialize_element(&group_key),
+        private_shares: shares
+            .iter()
+            .map(|(share, index)| (Secp256k1Tr::serialize_scalar(share), *index))
+            .collect(),
+        threshold: keys.threshold,
+        commitments: commitments
+            .iter()
+            .map(Secp256k1Tr::serialize_element)
+            .collect(),
+        checksum: None,
+    };
+    tweaked.checksum = Some(tweaked.compute_checksum());
+    Ok(tweaked)
+}
+
+/// Verifies a 64-byte BIP-340 signature over `message` against an x-only public key.
+pub fn verify(
+    public_key: &[u8; 32],
+    message: &[u8],
+    signature: &[u8],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let public_key = lift_x(public_key).ok_or("Invalid x-only public key")?;
+    rfc9591::verify::<Secp256k1Tr>(&public_key, message, signature)
+}
+
+fn load_suite_keys(key_file: &str) -> Result<SuiteKeys, Box<dyn std::error::Error>> {
+    match load_key_file(key_file)? {
+        KeyFile::Suite(keys) => Ok(keys),
+        KeyFile::Frost(_) => Err(format!(
+            "Key file {} holds frost-dalek keys, not BIP-340 keys",
+            key_file
+        )
+        .into()),
+    }
+}
+
+/// Writes the Taproot-tweaked key set of a `secp256k1-tr` key file.
+///
+/// # Arguments
+/// - `key_file`: Path to the key file of the internal key.
+/// - `merkle_root`: The Merkle root of the script tree, or `None` for a key-path-only output.
+/// - `output_key_file`: Path to save the key set of the output key.
+/// - `force`: Replace `output_key_file` if it exists.
+pub fn taproot_tweak_keys(
+    key_file: &str,
+    merkle_root: Option<&[u8; 32]>,
+    output_key_file: &str,
+    force: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    files::check_overwrite(output_key_file, force)?;
+    let keys = load_suite_keys(key_file)?;
+    let tweaked = tweak_keys(&keys, merkle_root)?;
+
+    files::write_secret_json(output_key_file, &tweaked, force)?;
+
+    println!(
+        "Taproot output key {} saved to: {}",
+        hex::encode(x_only_key(&tweaked)?),
+        output_key_file
+    );
+    Ok(())
+}
+
+/// Returns the x-only public key of a `secp256k1-tr` key file, in hex.
+pub fn export_x_only_key(key_file: &str) -> Result<String, Box<dyn std::error::Error>> {
+    Ok(hex::encode(x_only_key(&load_suite_keys(key_file)?)?))
+}
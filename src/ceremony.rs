@@ -0,0 +1,462 @@
+//! Interactive key ceremony.
+//!
+//! [`run_ceremony`] walks a non-expert through generating a key set and handing
+//! out its shares: it asks for the threshold, the number of participants, a label
+//! for each share holder, where to write the files and an optional passphrase per
+//! share, runs the distributed key generation, and shows the fingerprint of every
+//! participant's public share for the holder to confirm before anything is
+//! written. It then writes one share file per holder, the public package and a
+//! public summary of the ceremony, `ceremony.json`.
+//!
+//! A share with a passphrase is written as an [`EncryptedShare`]: the share file
+//! encrypted with ChaCha20-Poly1305 under a key derived from the passphrase with
+//! Argon2id. [`load_share`](crate::load_share) decrypts such files with the
+//! passphrase from the `FROST_PASSPHRASE` environment variable, or asks for it on
+//! the terminal; [`load_share_with_passphrase`](crate::load_share_with_passphrase)
+//! takes the passphrase from its caller.
+
+use crate::files;
+use crate::{fingerprint, generate_key_set, public_package_of, share_of, FrostShare};
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::Path;
+use zeroize::{Zeroize, Zeroizing};
+
+/// The environment variable [`load_share`](crate::load_share) reads the passphrase of an encrypted share from.
+pub const PASSPHRASE_VAR: &str = "FROST_PASSPHRASE";
+
+/// The largest Argon2id memory cost, in KiB, accepted from an encrypted share file.
+///
+/// The parameters are read from the file before it is authenticated, so they are
+/// capped to keep a crafted file from making decryption exhaust memory or time.
+pub const MAX_M_COST: u32 = 256 * 1024;
+
+/// The largest Argon2id number of passes accepted from an encrypted share file.
+pub const MAX_T_COST: u32 = 16;
+
+/// The largest Argon2id degree of parallelism accepted from an encrypted share file.
+pub const MAX_P_COST: u32 = 16;
+
+/// The file name of the public summary written by [`run_ceremony`].
+pub const SUMMARY_FILE: &str = "ceremony.json";
+
+/// A share file encrypted under a passphrase.
+///
+/// The group key and the participant index are stored in the clear, so the
+/// file can be told apart from other holders' files, and are authenticated
+/// along with the encrypted share.
+#[derive(Serialize, Deserialize)]
+pub struct EncryptedShare {
+    pub group_key: [u8; 32],
+    pub index: u32,
+    /// Argon2id salt, in hex.
+    pub salt: String,
+    /// Argon2id memory cost in KiB.
+    pub m_cost: u32,
+    /// Argon2id number of passes.
+    pub t_cost: u32,
+    /// Argon2id degree of parallelism.
+    pub p_cost: u32,
+    /// ChaCha20-Poly1305 nonce, in hex.
+    pub nonce: String,
+    /// The encrypted share file, in hex.
+    pub ciphertext: String,
+}
+
+/// A share holder, as recorded in the ceremony summary.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Holder {
+    pub index: u32,
+    pub label: String,
+    pub share_file: String,
+    pub encrypted: bool,
+    /// The fingerprint of the holder's public share.
+    pub fingerprint: String,
+}
+
+/// The public summary of a ceremony, written to [`SUMMARY_FILE`].
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CeremonySummary {
+    pub threshold: u32,
+    pub n: u32,
+    pub group_key: [u8; 32],
+    pub fingerprint: String,
+    pub public_file: String,
+    pub holders: Vec<Holder>,
+}
+
+fn derive_key(
+    passphrase: &str,
+    salt: &[u8],
+    params: Params,
+) -> Result<Zeroizing<[u8; 32]>, Box<dyn std::error::Error>> {
+    let mut key = Zeroizing::new([0u8; 32]);
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), salt, key.as_mut())
+        .map_err(|err| format!("Failed to derive a key from the passphrase: {}", err))?;
+    Ok(key)
+}
+
+fn associated_data(group_key: &[u8; 32], index: u32) -> Vec<u8> {
+    let mut aad = b"frost-cli encrypted share".to_vec();
+    aad.extend_from_slice(group_key);
+    aad.extend_from_slice(&index.to_be_bytes());
+    aad
+}
+
+/// Encrypts a share under a passphrase.
+pub fn encrypt_share(
+    share: &FrostShare,
+    passphrase: &str,
+) -> Result<EncryptedShare, Box<dyn std::error::Error>> {
+    let mut salt = [0u8; 16];
+    let mut nonce = [0u8; 12];
+    OsRng.fill_bytes(&mut salt);
+    OsRng.fill_bytes(&mut nonce);
+    let params = Params::default();
+    let key = derive_key(passphrase, &salt, params.clone())?;
+
+    let mut plaintext = serde_json::to_vec(share)?;
+    let (group_key, index) = (share.group_key, share.private_share.1);
+    let ciphertext = ChaCha20Poly1305::new(Key::from_slice(key.as_ref())).encrypt(
+        Nonce::from_slice(&nonce),
+        Payload {
+            msg: &plaintext,
+            aad: &associated_data(&group_key, index),
+        },
+    );
+    plaintext.zeroize();
+
+    Ok(EncryptedShare {
+        group_key,
+        index,
+        salt: hex::encode(salt),
+        m_cost: params.m_cost(),
+        t_cost: params.t_cost(),
+        p_cost: params.p_cost(),
+        nonce: hex::encode(nonce),
+        ciphertext: hex::encode(ciphertext.map_err(|_| "Failed to encrypt the share")?),
+    })
+}
+
+/// Decrypts a share encrypted by [`encrypt_share`].
+///
+/// # Errors
+/// Returns an error if the passphrase is wrong, the file has been tampered with
+/// or its key derivation parameters exceed [`MAX_M_COST`], [`MAX_T_COST`] or [`MAX_P_COST`].
+pub fn decrypt_share(
+    encrypted: &EncryptedShare,
+    passphrase: &str,
+) -> Result<FrostShare, Box<dyn std::error::Error>> {
+    if encrypted.m_cost > MAX_M_COST
+        || encrypted.t_cost > MAX_T_COST
+        || encrypted.p_cost > MAX_P_COST
+    {
+        return Err(format!(
+            "Key derivation parameters m_cost = {}, t_cost = {}, p_cost = {} exceed the limits \
+             of {}, {} and {}",
+            encrypted.m_cost,
+            encrypted.t_cost,
+            encrypted.p_cost,
+            MAX_M_COST,
+            MAX_T_COST,
+            MAX_P_COST
+        )
+        .into());
+    }
+    let params = Params::new(encrypted.m_cost, encrypted.t_cost, encrypted.p_cost, None)
+        .map_err(|err| format!("Invalid key derivation parameters: {}", err))?;
+    let key = derive_key(passphrase, &hex::decode(&encrypted.salt)?, params)?;
+    let nonce = hex::decode(&encrypted.nonce)?;
+    if nonce.len() != 12 {
+        return Err("Invalid nonce length".into());
+    }
+
+    let plaintext = Zeroizing::new(
+        ChaCha20Poly1305::new(Key::from_slice(key.as_ref()))
+            .decrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: &hex::decode(&encrypted.ciphertext)?,
+                    aad: &associated_data(&encrypted.group_key, encrypted.index),
+                },
+            )
+            .map_err(|_| "Wrong passphrase, or the share file has been tampered with")?,
+    );
+    let share: FrostShare = serde_json::from_slice(&plaintext)?;
+    if share.group_key != encrypted.group_key || share.private_share.1 != encrypted.index {
+        return Err("Encrypted share does not match its header".into());
+    }
+    Ok(share)
+}
+
+/// Returns the passphrase of an encrypted share file, from [`PASSPHRASE_VAR`] or the terminal.
+pub(crate) fn passphrase_for(share_file: &str) -> Result<String, Box<dyn std::error::Error>> {
+    if let Ok(passphrase) = std::env::var(PASSPHRASE_VAR) {
+        return Ok(passphrase);
+    }
+    if !io::stdin().is_terminal() {
+        return Err(format!(
+            "Share file {} is encrypted; set {} to its passphrase",
+            share_file, PASSPHRASE_VAR
+        )
+        .into());
+    }
+    Ok(read_hidden(&format!("Passphrase for {}: ", share_file))?)
+}
+
+/// Reads a line from the terminal without echoing it.
+#[cfg(not(target_arch = "wasm32"))]
+fn read_hidden(prompt: &str) -> io::Result<String> {
+    rpassword::prompt_password(prompt)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn read_hidden(_prompt: &str) -> io::Result<String> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "No terminal to read a passphrase from",
+    ))
+}
+
+/// Reads the answers of a ceremony from a terminal or a script.
+pub struct Prompter<R, W> {
+    input: R,
+    output: W,
+    /// Read passphrases from the terminal without echoing them, rather than from `input`.
+    hide_passphrases: bool,
+}
+
+impl<R: BufRead, W: Write> Prompter<R, W> {
+    pub fn new(input: R, output: W, hide_passphrases: bool) -> Self {
+        Prompter {
+            input,
+            output,
+            hide_passphrases,
+        }
+    }
+
+    fn say(&mut self, text: impl AsRef<str>) -> io::Result<()> {
+        writeln!(self.output, "{}", text.as_ref())
+    }
+
+    fn line(&mut self) -> Result<String, Box<dyn std::error::Error>> {
+        let mut line = String::new();
+        if self.input.read_line(&mut line)? == 0 {
+            return Err("Ceremony aborted: no more input".into());
+        }
+        Ok(line.trim().to_string())
+    }
+
+    /// Asks a question, returning `default` on an empty answer.
+    fn ask(&mut self, question: &str, default: &str) -> Result<String, Box<dyn std::error::Error>> {
+        write!(self.output, "{} [{}]: ", question, default)?;
+        self.output.flush()?;
+        let answer = self.line()?;
+        Ok(if answer.is_empty() {
+            default.to_string()
+        } else {
+            answer
+        })
+    }
+
+    /// Asks for a number in `min..=max` until one is given.
+    fn ask_number(
+        &mut self,
+        question: &str,
+        default: u32,
+        min: u32,
+        max: u32,
+    ) -> Result<u32, Box<dyn std::error::Error>> {
+        loop {
+            match self.ask(question, &default.to_string())?.parse::<u32>() {
+                Ok(number) if (min..=max).contains(&number) => return Ok(number),
+                _ => self.say(format!("Please enter a number from {} to {}.", min, max))?,
+            }
+        }
+    }
+
+    fn confirm(&mut self, question: &str) -> Result<bool, Box<dyn std::error::Error>> {
+        write!(self.output, "{} [y/N] ", question)?;
+        self.output.flush()?;
+        Ok(self.line()?.eq_ignore_ascii_case("y"))
+    }
+
+    fn secret(&mut self, prompt: &str) -> Result<String, Box<dyn std::error::Error>> {
+        if self.hide_passphrases {
+            self.output.flush()?;
+            Ok(read_hidden(prompt)?)
+        } else {
+            write!(self.output, "{}", prompt)?;
+            self.output.flush()?;
+            self.line()
+        }
+    }
+
+    /// Asks for an optional passphrase, twice, until both entries match.
+    fn passphrase(&mut self, label: &str) -> Result<Option<String>, Box<dyn std::error::Error>> {
+        loop {
+            let mut passphrase = self.secret(&format!(
+                "Passphrase for {}'s share (empty for none): ",
+                label
+            ))?;
+            if passphrase.is_empty() {
+                return Ok(None);
+            }
+            let mut repeated = self.secret("Repeat the passphrase: ")?;
+            let matches = passphrase == repeated;
+            repeated.zeroize();
+            if matches {
+                return Ok(Some(passphrase));
+            }
+            passphrase.zeroize();
+            self.say("The passphrases do not match.")?;
+        }
+    }
+}
+
+/// Returns a file name for a holder's share from its label.
+fn share_file_name(index: u32, label: &str) -> String {
+    let slug: String = label
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    let slug: Vec<&str> = slug.split('-').filter(|part| !part.is_empty()).collect();
+    format!("share_{}_{}.json", index, slug.join("-"))
+}
+
+/// Runs an interactive key ceremony and writes its files.
+///
+/// # Arguments
+/// - `prompter`: Where the questions go and the answers come from.
+/// - `force`: Replace existing files in the output directory.
+///
+/// # Returns
+/// The summary of the ceremony, or `None` if a holder did not confirm their
+/// fingerprint, in which case no file is written.
+///
+/// # Errors
+/// Returns an error if the input ends early, the key generation fails or a file
+/// cannot be written.
+pub fn run_ceremony<R: BufRead, W: Write>(
+    prompter: &mut Prompter<R, W>,
+    force: bool,
+) -> Result<Option<CeremonySummary>, Box<dyn std::error::Error>> {
+    prompter.say("FROST key ceremony")?;
+    let n = prompter.ask_number("Number of participants (n)", 3, 1, 255)?;
+    let t = prompter.ask_number("Signatures required (t)", (n / 2 + 1).min(n), 1, n)?;
+
+    let mut labels = Vec::new();
+    for index in 1..=n {
+        labels.push(prompter.ask(
+            &format!("Label of participant {}", index),
+            &format!("participant {}", index),
+        )?);
+    }
+    let dir = prompter.ask("Output directory", "./results/ceremony")?;
+    let dir = Path::new(&dir);
+    let path = |name: &str| dir.join(name).to_string_lossy().into_owned();
+    let public_file = path("frost_public.json");
+    let share_files: Vec<String> = (1..=n)
+        .zip(&labels)
+        .map(|(index, label)| path(&share_file_name(index, label)))
+        .collect();
+    for file in share_files
+        .iter()
+        .chain([&public_file, &path(SUMMARY_FILE)])
+    {
+        files::check_overwrite(file, force)?;
+    }
+
+    let mut passphrases = Vec::new();
+    for label in &labels {
+        passphrases.push(prompter.passphrase(label)?.map(Zeroizing::new));
+    }
+
+    prompter.say(format!(
+        "Running the distributed key generation for {} of {}...",
+        t, n
+    ))?;
+    let frost_keys = generate_key_set(t, n)?;
+    let public_package = public_package_of(&frost_keys)?;
+    prompter.say(format!(
+        "Group key fingerprint: {}",
+        fingerprint(&frost_keys.group_key)
+    ))?;
+
+    let mut holders = Vec::new();
+    for ((index, label), share_file) in (1..=n).zip(&labels).zip(&share_files) {
+        let public_share = public_package
+            .public_shares
+            .iter()
+            .find(|(_, share_index)| *share_index == index)
+            .ok_or_else(|| format!("No public share for participant {}", index))?;
+        let share_fingerprint = fingerprint(&public_share.0);
+        prompter.say(format!(
+            "Participant {} ({}): share fingerprint {}",
+            index, label, share_fingerprint
+        ))?;
+        if !prompter.confirm(&format!("Does {} confirm this fingerprint?", label))? {
+            prompter.say("Fingerprint not confirmed; no files were written.")?;
+            return Ok(None);
+        }
+        holders.push(Holder {
+            index,
+            label: label.clone(),
+            share_file: share_file.clone(),
+            encrypted: passphrases[index as usize - 1].is_some(),
+            fingerprint: share_fingerprint,
+        });
+    }
+
+    for (holder, passphrase) in holders.iter().zip(&passphrases) {
+        let share = share_of(&frost_keys, holder.index)?;
+        match passphrase {
+            Some(passphrase) => files::write_secret_json(
+                &holder.share_file,
+                &encrypt_share(&share, passphrase)?,
+                force,
+            )?,
+            None => files::write_secret_json(&holder.share_file, &share, force)?,
+        }
+    }
+    files::write_json(&public_file, &public_package)?;
+    let summary = CeremonySummary {
+        threshold: t,
+        n,
+        group_key: frost_keys.group_key,
+        fingerprint: fingerprint(&frost_keys.group_key),
+        public_file,
+        holders,
+    };
+    files::write_json(path(SUMMARY_FILE), &summary)?;
+
+    prompter.say(format!(
+        "\nCeremony complete: {} of {} signatures required, group key fingerprint {}.",
+        t, n, summary.fingerprint
+    ))?;
+    for holder in &summary.holders {
+        prompter.say(format!(
+            "  {} ({}): {}{}",
+            holder.index,
+            holder.label,
+            holder.share_file,
+            if holder.encrypted { " (encrypted)" } else { "" }
+        ))?;
+    }
+    prompter.say(format!("  Public package: {}", summary.public_file))?;
+    prompter.say(format!("  Summary: {}", path(SUMMARY_FILE)))?;
+    prompter.say("Hand each share file to its holder only, then delete your copies.")?;
+    Ok(Some(summary))
+}
@@ -0,0 +1,590 @@
+//! The ciphersuites of RFC 9591: a prime-order group and the hash functions H1 to H5.
+//!
+//! [`Ciphersuite`] abstracts the group and hashes so that the protocol in
+//! [`rfc9591`](crate::rfc9591) is written once. Three suites implement it:
+//!
+//! - [`Ed25519`]: FROST(Ed25519, SHA-512), whose signatures are RFC 8032 Ed25519 signatures;
+//! - [`Ristretto255`]: FROST(ristretto255, SHA-512);
+//! - [`Secp256k1`]: FROST(secp256k1, SHA-256);
+//! - [`Secp256k1Tr`]: FROST(secp256k1, SHA-256) with BIP-340 challenges and
+//!   signatures, for Taproot.
+//!
+//! [`SuiteId`] names these suites, and the pre-standard frost-dalek protocol the
+//! rest of this crate uses, on the command line and in key files.
+
+use curve25519_dalek::constants::{ED25519_BASEPOINT_POINT, RISTRETTO_BASEPOINT_POINT};
+use curve25519_dalek::edwards::{CompressedEdwardsY, EdwardsPoint};
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar as DalekScalar;
+use curve25519_dalek::traits::{Identity, IsIdentity};
+use k256::elliptic_curve::bigint::{U256, U512};
+use k256::elliptic_curve::hash2curve::{hash_to_field, ExpandMsgXmd};
+use k256::elliptic_curve::ops::Reduce;
+use k256::elliptic_curve::point::AffineCoordinates;
+use k256::elliptic_curve::sec1::{FromEncodedPoint, ToEncodedPoint};
+use k256::elliptic_curve::PrimeField;
+use k256::{AffinePoint, EncodedPoint, FieldBytes, ProjectivePoint, WideBytes};
+use sha2::{Digest, Sha256, Sha512};
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+use std::str::FromStr;
+use zeroize::Zeroize;
+
+/// A prime-order group with its serialization and the hash functions of an RFC 9591 ciphersuite.
+pub trait Ciphersuite {
+    type Scalar: Copy
+        + PartialEq
+        + Zeroize
+        + Add<Output = Self::Scalar>
+        + Sub<Output = Self::Scalar>
+        + Mul<Output = Self::Scalar>
+        + Neg<Output = Self::Scalar>;
+    type Element: Copy
+        + PartialEq
+        + Add<Output = Self::Element>
+        + Neg<Output = Self::Element>
+        + Mul<Self::Scalar, Output = Self::Element>;
+
+    /// The suite's `contextString`.
+    const CONTEXT: &'static str;
+
+    fn scalar_from_u32(value: u32) -> Self::Scalar;
+    /// Reduces 64 uniformly random bytes to a scalar.
+    fn scalar_from_wide(bytes: &[u8; 64]) -> Self::Scalar;
+    /// Inverts a non-zero scalar.
+    fn invert(scalar: &Self::Scalar) -> Self::Scalar;
+    fn generator() -> Self::Element;
+    fn identity() -> Self::Element;
+
+    fn serialize_scalar(scalar: &Self::Scalar) -> Vec<u8>;
+    /// Decodes a canonically encoded scalar.
+    fn deserialize_scalar(bytes: &[u8]) -> Option<Self::Scalar>;
+    fn serialize_element(element: &Self::Element) -> Vec<u8>;
+    /// Decodes an element, rejecting the identity and, where it matters, elements outside the prime-order subgroup.
+    fn deserialize_element(bytes: &[u8]) -> Option<Self::Element>;
+
+    fn h1(m: &[u8]) -> Self::Scalar;
+    fn h2(m: &[u8]) -> Self::Scalar;
+    fn h3(m: &[u8]) -> Self::Scalar;
+    fn h4(m: &[u8]) -> Vec<u8>;
+    fn h5(m: &[u8]) -> Vec<u8>;
+
+    /// Returns `true` if `element` must be negated to be in the suite's normal form.
+    ///
+    /// Only BIP-340 has one: group keys and group commitments with an even y
+    /// coordinate. Keys are negated when generated, and signers negate their
+    /// nonces when the group commitment is odd.
+    fn needs_negation(_element: &Self::Element) -> bool {
+        false
+    }
+
+    /// The challenge `H2(SerializeElement(R) || SerializeElement(PK) || msg)`.
+    fn challenge(
+        commitment: &Self::Element,
+        group_key: &Self::Element,
+        message: &[u8],
+    ) -> Self::Scalar {
+        let mut input = Self::serialize_element(commitment);
+        input.extend(Self::serialize_element(group_key));
+        input.extend_from_slice(message);
+        Self::h2(&input)
+    }
+
+    /// Encodes a signature as `SerializeElement(R) || SerializeScalar(z)`.
+    fn serialize_signature(commitment: &Self::Element, z: &Self::Scalar) -> Vec<u8> {
+        let mut signature = Self::serialize_element(commitment);
+        signature.extend(Self::serialize_scalar(z));
+        signature
+    }
+
+    /// Decodes a signature into `R` and `z`.
+    fn deserialize_signature(signature: &[u8]) -> Option<(Self::Element, Self::Scalar)> {
+        let element_len = Self::serialize_element(&Self::generator()).len();
+        if signature.len() != element_len + Self::serialize_scalar(&Self::scalar_from_u32(0)).len()
+        {
+            return None;
+        }
+        let (commitment, z) = signature.split_at(element_len);
+        Some((
+            Self::deserialize_element(commitment)?,
+            Self::deserialize_scalar(z)?,
+        ))
+    }
+
+    /// Checks `z * G == R + c * PK`.
+    fn verify_equation(
+        group_key: &Self::Element,
+        commitment: &Self::Element,
+        z: &Self::Scalar,
+        challenge: &Self::Scalar,
+    ) -> bool {
+        Self::generator() * *z == *commitment + *group_key * *challenge
+    }
+}
+
+/// Hashes `CONTEXT || tag || m` with SHA-512.
+fn sha512_tagged(context: &str, tag: &str, m: &[u8]) -> Sha512 {
+    let mut h = Sha512::new();
+    h.update(context.as_bytes());
+    h.update(tag.as_bytes());
+    h.update(m);
+    h
+}
+
+/// FROST(Ed25519, SHA-512).
+pub struct Ed25519;
+
+impl Ciphersuite for Ed25519 {
+    type Scalar = DalekScalar;
+    type Element = EdwardsPoint;
+
+    const CONTEXT: &'static str = "FROST-ED25519-SHA512-v1";
+
+    fn scalar_from_u32(value: u32) -> DalekScalar {
+        DalekScalar::from(value)
+    }
+
+    fn scalar_from_wide(bytes: &[u8; 64]) -> DalekScalar {
+        DalekScalar::from_bytes_mod_order_wide(bytes)
+    }
+
+    fn invert(scalar: &DalekScalar) -> DalekScalar {
+        scalar.invert()
+    }
+
+    fn generator() -> EdwardsPoint {
+        ED25519_BASEPOINT_POINT
+    }
+
+    fn identity() -> EdwardsPoint {
+        EdwardsPoint::identity()
+    }
+
+    fn serialize_scalar(scalar: &DalekScalar) -> Vec<u8> {
+        scalar.to_bytes().to_vec()
+    }
+
+    fn deserialize_scalar(bytes: &[u8]) -> Option<DalekScalar> {
+        DalekScalar::from_canonical_bytes(bytes.try_into().ok()?)
+    }
+
+    fn serialize_element(element: &EdwardsPoint) -> Vec<u8> {
+        element.compress().to_bytes().to_vec()
+    }
+
+    fn deserialize_element(bytes: &[u8]) -> Option<EdwardsPoint> {
+        let compressed = CompressedEdwardsY(bytes.try_into().ok()?);
+        let point = compressed.decompress()?;
+        // Reject non-canonical encodings, the identity and points with a torsion component.
+        if point.compress() != compressed || point.is_identity() || !point.is_torsion_free() {
+            return None;
+        }
+        Some(point)
+    }
+
+    fn h1(m: &[u8]) -> DalekScalar {
+        DalekScalar::from_hash(sha512_tagged(Self::CONTEXT, "rho", m))
+    }
+
+    /// The challenge hash has no context string, so that signatures are RFC 8032 signatures.
+    fn h2(m: &[u8]) -> DalekScalar {
+        DalekScalar::from_hash(Sha512::new().chain(m))
+    }
+
+    fn h3(m: &[u8]) -> DalekScalar {
+        DalekScalar::from_hash(sha512_tagged(Self::CONTEXT, "nonce", m))
+    }
+
+    fn h4(m: &[u8]) -> Vec<u8> {
+        sha512_tagged(Self::CONTEXT, "msg", m).finalize().to_vec()
+    }
+
+    fn h5(m: &[u8]) -> Vec<u8> {
+        sha512_tagged(Self::CONTEXT, "com", m).finalize().to_vec()
+    }
+
+    /// Verifies with the cofactor, as RFC 8032 allows and RFC 9591 requires.
+    fn verify_equation(
+        group_key: &EdwardsPoint,
+        commitment: &EdwardsPoint,
+        z: &DalekScalar,
+        challenge: &DalekScalar,
+    ) -> bool {
+        (ED25519_BASEPOINT_POINT * z).mul_by_cofactor()
+            == (commitment + group_key * challenge).mul_by_cofactor()
+    }
+}
+
+/// FROST(ristretto255, SHA-512).
+pub struct Ristretto255;
+
+impl Ciphersuite for Ristretto255 {
+    type Scalar = DalekScalar;
+    type Element = RistrettoPoint;
+
+    const CONTEXT: &'static str = "FROST-RISTRETTO255-SHA512-v1";
+
+    fn scalar_from_u32(value: u32) -> DalekScalar {
+        DalekScalar::from(value)
+    }
+
+    fn scalar_from_wide(bytes: &[u8; 64]) -> DalekScalar {
+        DalekScalar::from_bytes_mod_order_wide(bytes)
+    }
+
+    fn invert(scalar: &DalekScalar) -> DalekScalar {
+        scalar.invert()
+    }
+
+    fn generator() -> RistrettoPoint {
+        RISTRETTO_BASEPOINT_POINT
+    }
+
+    fn identity() -> RistrettoPoint {
+        RistrettoPoint::identity()
+    }
+
+    fn serialize_scalar(scalar: &DalekScalar) -> Vec<u8> {
+        scalar.to_bytes().to_vec()
+    }
+
+    fn deserialize_scalar(bytes: &[u8]) -> Option<DalekScalar> {
+        DalekScalar::from_canonical_bytes(bytes.try_into().ok()?)
+    }
+
+    fn serialize_element(element: &RistrettoPoint) -> Vec<u8> {
+        element.compress().to_bytes().to_vec()
+    }
+
+    fn deserialize_element(bytes: &[u8]) -> Option<RistrettoPoint> {
+        let point = CompressedRistretto(bytes.try_into().ok()?).decompress()?;
+        if point.is_identity() {
+            return None;
+        }
+        Some(point)
+    }
+
+    fn h1(m: &[u8]) -> DalekScalar {
+        DalekScalar::from_hash(sha512_tagged(Self::CONTEXT, "rho", m))
+    }
+
+    fn h2(m: &[u8]) -> DalekScalar {
+        DalekScalar::from_hash(sha512_tagged(Self::CONTEXT, "chal", m))
+    }
+
+    fn h3(m: &[u8]) -> DalekScalar {
+        DalekScalar::from_hash(sha512_tagged(Self::CONTEXT, "nonce", m))
+    }
+
+    fn h4(m: &[u8]) -> Vec<u8> {
+        sha512_tagged(Self::CONTEXT, "msg", m).finalize().to_vec()
+    }
+
+    fn h5(m: &[u8]) -> Vec<u8> {
+        sha512_tagged(Self::CONTEXT, "com", m).finalize().to_vec()
+    }
+}
+
+/// `hash_to_field` of RFC 9380 with `expand_message_xmd` over SHA-256 and the
+/// domain separation tag `context || tag`.
+fn secp256k1_hash_to_scalar(context: &str, tag: &str, m: &[u8]) -> k256::Scalar {
+    let mut out = [k256::Scalar::ZERO];
+    hash_to_field::<ExpandMsgXmd<k256::sha2::Sha256>, k256::Scalar>(
+        &[m],
+        &[context.as_bytes(), tag.as_bytes()],
+        &mut out,
+    )
+    .expect("the domain separation tag and output length are within bounds");
+    out[0]
+}
+
+/// Hashes `context || tag || m` with SHA-256.
+fn sha256_tagged(context: &str, tag: &str, m: &[u8]) -> Vec<u8> {
+    let mut h = Sha256::new();
+    h.update(context.as_bytes());
+    h.update(tag.as_bytes());
+    h.update(m);
+    h.finalize().to_vec()
+}
+
+/// The BIP-340 tagged hash `SHA-256(SHA-256(tag) || SHA-256(tag) || m)`.
+pub fn bip340_tagged_hash(tag: &str, m: &[u8]) -> [u8; 32] {
+    let tag_hash = Sha256::digest(tag.as_bytes());
+    let mut h = Sha256::new();
+    h.update(tag_hash);
+    h.update(tag_hash);
+    h.update(m);
+    h.finalize().into()
+}
+
+/// Returns the x coordinate of a secp256k1 point, the BIP-340 x-only encoding.
+pub fn x_only(element: &ProjectivePoint) -> [u8; 32] {
+    element.to_affine().x().into()
+}
+
+/// FROST(secp256k1, SHA-256).
+pub struct Secp256k1;
+
+impl Ciphersuite for Secp256k1 {
+    type Scalar = k256::Scalar;
+    type Element = ProjectivePoint;
+
+    const CONTEXT: &'static str = "FROST-secp256k1-SHA256-v1";
+
+    fn scalar_from_u32(value: u32) -> k256::Scalar {
+        k256::Scalar::from(value)
+    }
+
+    fn scalar_from_wide(bytes: &[u8; 64]) -> k256::Scalar {
+        <k256::Scalar as Reduce<U512>>::reduce_bytes(&WideBytes::clone_from_slice(bytes))
+    }
+
+    fn invert(scalar: &k256::Scalar) -> k256::Scalar {
+        scalar.invert().unwrap()
+    }
+
+    fn generator() -> ProjectivePoint {
+        ProjectivePoint::GENERATOR
+    }
+
+    fn identity() -> ProjectivePoint {
+        ProjectivePoint::IDENTITY
+    }
+
+    fn serialize_scalar(scalar: &k256::Scalar) -> Vec<u8> {
+        scalar.to_bytes().to_vec()
+    }
+
+    fn deserialize_scalar(bytes: &[u8]) -> Option<k256::Scalar> {
+        if bytes.len() != 32 {
+            return None;
+        }
+        k256::Scalar::from_repr(FieldBytes::clone_from_slice(bytes)).into()
+    }
+
+    fn serialize_element(element: &ProjectivePoint) -> Vec<u8> {
+        element
+            .to_affine()
+            .to_encoded_point(true)
+            .as_bytes()
+            .to_vec()
+    }
+
+    fn deserialize_element(bytes: &[u8]) -> Option<ProjectivePoint> {
+        if bytes.len() != 33 {
+            return None;
+        }
+        let encoded = EncodedPoint::from_bytes(bytes).ok()?;
+        let point: Option<AffinePoint> = AffinePoint::from_encoded_point(&encoded).into();
+        let point = ProjectivePoint::from(point?);
+        if point == ProjectivePoint::IDENTITY {
+            return None;
+        }
+        Some(point)
+    }
+
+    fn h1(m: &[u8]) -> k256::Scalar {
+        secp256k1_hash_to_scalar(Self::CONTEXT, "rho", m)
+    }
+
+    fn h2(m: &[u8]) -> k256::Scalar {
+        secp256k1_hash_to_scalar(Self::CONTEXT, "chal", m)
+    }
+
+    fn h3(m: &[u8]) -> k256::Scalar {
+        secp256k1_hash_to_scalar(Self::CONTEXT, "nonce", m)
+    }
+
+    fn h4(m: &[u8]) -> Vec<u8> {
+        sha256_tagged(Self::CONTEXT, "msg", m)
+    }
+
+    fn h5(m: &[u8]) -> Vec<u8> {
+        sha256_tagged(Self::CONTEXT, "com", m)
+    }
+}
+
+/// FROST(secp256k1, SHA-256) for BIP-340 Schnorr signatures and Taproot.
+///
+/// The group is the one of [`Secp256k1`], but the challenge is the BIP-340
+/// `tagged_hash("BIP0340/challenge", x(R) || x(PK) || msg)`, signatures are
+/// `x(R) || z` and both the group key and `R` are normalized to an even y
+/// coordinate, so that the signature verifies against the 32-byte x-only key.
+pub struct Secp256k1Tr;
+
+impl Ciphersuite for Secp256k1Tr {
+    type Scalar = k256::Scalar;
+    type Element = ProjectivePoint;
+
+    const CONTEXT: &'static str = "FROST-secp256k1-SHA256-TR-v1";
+
+    fn scalar_from_u32(value: u32) -> k256::Scalar {
+        Secp256k1::scalar_from_u32(value)
+    }
+
+    fn scalar_from_wide(bytes: &[u8; 64]) -> k256::Scalar {
+        Secp256k1::scalar_from_wide(bytes)
+    }
+
+    fn invert(scalar: &k256::Scalar) -> k256::Scalar {
+        Secp256k1::invert(scalar)
+    }
+
+    fn generator() -> ProjectivePoint {
+        ProjectivePoint::GENERATOR
+    }
+
+    fn identity() -> ProjectivePoint {
+        ProjectivePoint::IDENTITY
+    }
+
+    fn serialize_scalar(scalar: &k256::Scalar) -> Vec<u8> {
+        Secp256k1::serialize_scalar(scalar)
+    }
+
+    fn deserialize_scalar(bytes: &[u8]) -> Option<k256::Scalar> {
+        Secp256k1::deserialize_scalar(bytes)
+    }
+
+    fn serialize_element(element: &ProjectivePoint) -> Vec<u8> {
+        Secp256k1::serialize_element(element)
+    }
+
+    fn deserialize_element(bytes: &[u8]) -> Option<ProjectivePoint> {
+        Secp256k1::deserialize_element(bytes)
+    }
+
+    fn h1(m: &[u8]) -> k256::Scalar {
+        secp256k1_hash_to_scalar(Self::CONTEXT, "rho", m)
+    }
+
+    /// The BIP-340 challenge hash, reduced modulo the group order.
+    fn h2(m: &[u8]) -> k256::Scalar {
+        let hash = bip340_tagged_hash("BIP0340/challenge", m);
+        <k256::Scalar as Reduce<U256>>::reduce_bytes(&FieldBytes::from(hash))
+    }
+
+    fn h3(m: &[u8]) -> k256::Scalar {
+        secp256k1_hash_to_scalar(Self::CONTEXT, "nonce", m)
+    }
+
+    fn h4(m: &[u8]) -> Vec<u8> {
+        sha256_tagged(Self::CONTEXT, "msg", m)
+    }
+
+    fn h5(m: &[u8]) -> Vec<u8> {
+        sha256_tagged(Self::CONTEXT, "com", m)
+    }
+
+    fn needs_negation(element: &ProjectivePoint) -> bool {
+        element.to_affine().y_is_odd().into()
+    }
+
+    fn challenge(
+        commitment: &ProjectivePoint,
+        group_key: &ProjectivePoint,
+        message: &[u8],
+    ) -> k256::Scalar {
+        let mut input = x_only(commitment).to_vec();
+        input.extend(x_only(group_key));
+        input.extend_from_slice(message);
+        Self::h2(&input)
+    }
+
+    fn serialize_signature(commitment: &ProjectivePoint, z: &k256::Scalar) -> Vec<u8> {
+        let mut signature = x_only(commitment).to_vec();
+        signature.extend(Self::serialize_scalar(z));
+        signature
+    }
+
+    /// Decodes `x(R) || z`, lifting `x(R)` to the point with an even y coordinate.
+    fn deserialize_signature(signature: &[u8]) -> Option<(ProjectivePoint, k256::Scalar)> {
+        if signature.len() != 64 {
+            return None;
+        }
+        let (x, z) = signature.split_at(32);
+        Some((lift_x(x.try_into().ok()?)?, Self::deserialize_scalar(z)?))
+    }
+}
+
+/// Returns the point with x coordinate `x` and an even y coordinate, as BIP-340 `lift_x`.
+pub fn lift_x(x: &[u8; 32]) -> Option<ProjectivePoint> {
+    let mut compressed = [2u8; 33];
+    compressed[1..].copy_from_slice(x);
+    Secp256k1::deserialize_element(&compressed)
+}
+
+/// The signing protocols and ciphersuites a key file can use.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SuiteId {
+    /// The pre-standard FROST over ristretto255 of frost-dalek, used by key files without a ciphersuite.
+    FrostDalek,
+    Ed25519,
+    Ristretto255,
+    Secp256k1,
+    Secp256k1Tr,
+}
+
+impl SuiteId {
+    /// Every suite, in the order they are listed to users.
+    pub const ALL: [SuiteId; 5] = [
+        SuiteId::FrostDalek,
+        SuiteId::Ed25519,
+        SuiteId::Ristretto255,
+        SuiteId::Secp256k1,
+        SuiteId::Secp256k1Tr,
+    ];
+
+    /// The name used on the command line.
+    pub fn name(&self) -> &'static str {
+        match self {
+            SuiteId::FrostDalek => "frost-dalek",
+            SuiteId::Ed25519 => "ed25519",
+            SuiteId::Ristretto255 => "ristretto255",
+            SuiteId::Secp256k1 => "secp256k1",
+            SuiteId::Secp256k1Tr => "secp256k1-tr",
+        }
+    }
+
+    /// The RFC 9591 `contextString` stored in key files, or `None` for frost-dalek.
+    pub fn context(&self) -> Option<&'static str> {
+        match self {
+            SuiteId::FrostDalek => None,
+            SuiteId::Ed25519 => Some(Ed25519::CONTEXT),
+            SuiteId::Ristretto255 => Some(Ristretto255::CONTEXT),
+            SuiteId::Secp256k1 => Some(Secp256k1::CONTEXT),
+            SuiteId::Secp256k1Tr => Some(Secp256k1Tr::CONTEXT),
+        }
+    }
+
+    /// Finds the suite a key file's `contextString` names.
+    pub fn from_context(context: &str) -> Option<SuiteId> {
+        SuiteId::ALL
+            .into_iter()
+            .find(|suite| suite.context() == Some(context))
+    }
+}
+
+impl fmt::Display for SuiteId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for SuiteId {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        SuiteId::ALL
+            .into_iter()
+            .find(|suite| suite.name() == name)
+            .ok_or_else(|| {
+                let names: Vec<&str> = SuiteId::ALL.iter().map(SuiteId::name).collect();
+                format!(
+                    "Unknown ciphersuite {:?}; expected one of: {}",
+                    name,
+                    names.join(", ")
+                )
+            })
+    }
+}
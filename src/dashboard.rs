@@ -0,0 +1,368 @@
+//! Terminal dashboard for a signing coordinator.
+//!
+//! [`coordinate_with_dashboard`] runs the same signing rounds as
+//! [`coordinate`](crate::net::coordinate) on a background thread and draws
+//! their progress in the terminal: a row per participant with its status in
+//! each round, the elapsed time, and the final signature or the error that
+//! stopped the run. The [`Dashboard`] state is fed by
+//! [`SigningEvent`]s and can be drawn on any ratatui backend.
+
+use crate::files;
+use crate::load_public_package;
+use crate::net::{request_signature_with, SigningEvent};
+use crate::roast::request_signature_robust;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Paragraph, Row, Table, Wrap};
+use ratatui::Frame;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How often the dashboard redraws while waiting for participants.
+const TICK: Duration = Duration::from_millis(100);
+
+/// A participant's status in one signing round.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Status {
+    Waiting,
+    Committed,
+    PartialSigned,
+    Faulted(String),
+}
+
+impl Status {
+    fn label(&self) -> &str {
+        match self {
+            Status::Waiting => "waiting",
+            Status::Committed => "committed",
+            Status::PartialSigned => "partial signed",
+            Status::Faulted(_) => "faulted",
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            Status::Waiting => Color::DarkGray,
+            Status::Committed | Status::PartialSigned => Color::Green,
+            Status::Faulted(_) => Color::Red,
+        }
+    }
+}
+
+/// One row of the dashboard.
+#[derive(Clone, Debug)]
+pub struct Participant {
+    pub address: String,
+    /// The participant's index, once it has answered.
+    pub index: Option<u32>,
+    /// Status in the first round, the commitments.
+    pub commit: Status,
+    /// Status in the second round, the partial signatures.
+    pub sign: Status,
+    /// Time since the start of the run at which the row last changed.
+    pub updated: Option<Duration>,
+}
+
+/// The state drawn by the dashboard.
+pub struct Dashboard {
+    pub message: String,
+    pub participants: Vec<Participant>,
+    pub signature: Option<[u8; 64]>,
+    pub error: Option<String>,
+    started: Instant,
+    finished: Option<Duration>,
+}
+
+impl Dashboard {
+    /// Starts a dashboard with every participant waiting.
+    pub fn new(message: &str, addresses: &[String]) -> Self {
+        Dashboard {
+            message: message.to_string(),
+            participants: addresses
+                .iter()
+                .map(|address| Participant {
+                    address: address.clone(),
+                    index: None,
+                    commit: Status::Waiting,
+                    sign: Status::Waiting,
+                    updated: None,
+                })
+                .collect(),
+            signature: None,
+            error: None,
+            started: Instant::now(),
+            finished: None,
+        }
+    }
+
+    /// Returns the time since the run started, frozen once it is over.
+    pub fn elapsed(&self) -> Duration {
+        self.finished.unwrap_or_else(|| self.started.elapsed())
+    }
+
+    /// Returns true once the run has produced a signature or failed.
+    pub fn is_finished(&self) -> bool {
+        self.finished.is_some()
+    }
+
+    /// Updates the state with a step of the signing run.
+    pub fn apply(&mut self, event: SigningEvent) {
+        let elapsed = self.elapsed();
+        match event {
+            SigningEvent::Joined { address, index } => {
+                if let Some(participant) = row(&mut self.participants, &address, elapsed) {
+                    participant.index = Some(index);
+                }
+            }
+            SigningEvent::Committed { address } => {
+                if let Some(participant) = row(&mut self.participants, &address, elapsed) {
+                    participant.commit = Status::Committed;
+                }
+            }
+            SigningEvent::SessionStarted { signers } => {
+                // A new session asks its signers for a fresh partial signature.
+                for participant in &mut self.participants {
+                    if participant
+                        .index
+                        .is_some_and(|index| signers.contains(&index))
+                        && !matches!(participant.sign, Status::Faulted(_))
+                    {
+                        participant.sign = Status::Waiting;
+                        participant.updated = Some(elapsed);
+                    }
+                }
+            }
+            SigningEvent::PartialSigned { address } => {
+                if let Some(participant) = row(&mut self.participants, &address, elapsed) {
+                    participant.sign = Status::PartialSigned;
+                }
+            }
+            SigningEvent::Faulted { address, reason } => {
+                if let Some(participant) = row(&mut self.participants, &address, elapsed) {
+                    // The fault belongs to the first round the participant has not completed.
+                    if participant.commit == Status::Waiting {
+                        participant.commit = Status::Faulted(reason);
+                    } else {
+                        participant.sign = Status::Faulted(reason);
+                    }
+                }
+            }
+            SigningEvent::Signed(signature) => {
+                self.signature = Some(signature);
+                self.finished = Some(elapsed);
+            }
+        }
+    }
+
+    /// Records the error that stopped the run.
+    pub fn fail(&mut self, error: String) {
+        self.error = Some(error);
+        self.finished.get_or_insert(self.started.elapsed());
+    }
+
+    /// Draws the dashboard over the whole frame.
+    pub fn render(&self, frame: &mut Frame) {
+        let [header, table, footer] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Min(4),
+            Constraint::Length(5),
+        ])
+        .areas(frame.area());
+
+        let committed = self.count(|participant| participant.commit == Status::Committed);
+        let signed = self.count(|participant| participant.sign == Status::PartialSigned);
+        frame.render_widget(
+            Paragraph::new(format!(
+                "Message: {}\nElapsed: {:.1}s   Committed: {}/{}   Partial signatures: {}/{}",
+                self.message,
+                self.elapsed().as_secs_f64(),
+                committed,
+                self.participants.len(),
+                signed,
+                self.participants.len()
+            ))
+            .block(Block::new().title(" FROST signing ".bold())),
+            header,
+        );
+
+        let rows = self.participants.iter().map(|participant| {
+            Row::new(vec![
+                Line::from(
+                    participant
+                        .index
+                        .map_or_else(|| "?".to_string(), |index| index.to_string()),
+                ),
+                Line::from(participant.address.clone()),
+                Line::from(participant.commit.label()).style(participant.commit.color()),
+                Line::from(participant.sign.label()).style(participant.sign.color()),
+                Line::from(
+                    participant
+                        .updated
+                        .map(|updated| format!("{:.1}s", updated.as_secs_f64()))
+                        .unwrap_or_default(),
+                ),
+            ])
+        });
+        frame.render_widget(
+            Table::new(
+                rows,
+                [
+                    Constraint::Length(11),
+                    Constraint::Min(21),
+                    Constraint::Length(16),
+                    Constraint::Length(16),
+                    Constraint::Length(9),
+                ],
+            )
+            .header(Row::new(["Participant", "Address", "Round 1", "Round 2", "Updated"]).bold())
+            .block(Block::bordered().title(" Participants ")),
+            table,
+        );
+
+        let (text, style) = match (&self.signature, &self.error) {
+            (Some(signature), _) => (
+                format!("Signature: {}", hex::encode(signature)),
+                Style::new().fg(Color::Green),
+            ),
+            (None, Some(error)) => (format!("Failed: {}", error), Style::new().fg(Color::Red)),
+            (None, None) => ("Signing...".to_string(), Style::new()),
+        };
+        let hint = if self.is_finished() {
+            "Press q to exit"
+        } else {
+            "Press q to abort"
+        };
+        frame.render_widget(
+            Paragraph::new(text)
+                .style(style)
+                .wrap(Wrap { trim: false })
+                .block(Block::bordered().title_bottom(hint)),
+            footer,
+        );
+    }
+
+    fn count(&self, predicate: impl Fn(&Participant) -> bool) -> usize {
+        self.participants
+            .iter()
+            .filter(|participant| predicate(participant))
+            .count()
+    }
+}
+
+/// Returns the row of the participant at `address`, marked as updated at `elapsed`.
+fn row<'a>(
+    participants: &'a mut [Participant],
+    address: &str,
+    elapsed: Duration,
+) -> Option<&'a mut Participant> {
+    let participant = participants
+        .iter_mut()
+        .find(|participant| participant.address == address)?;
+    participant.updated = Some(elapsed);
+    Some(participant)
+}
+
+/// Signs a message with remote participants like [`coordinate`](crate::net::coordinate),
+/// showing the progress of the run in the terminal.
+///
+/// The participants are checked against the public package in `public_file`,
+/// and the index of every participant that joins is added to `signers`.
+/// With a `robust` timeout, the run falls back to other participants as
+/// [`request_signature_robust`] does. The dashboard stays up after the run
+/// until `q` is pressed; pressing it earlier aborts the run.
+///
+/// # Errors
+/// Returns the error that stopped the run, or an error if it was aborted.
+pub fn coordinate_with_dashboard(
+    message: &str,
+    context: &str,
+    public_file: &str,
+    participants: &[String],
+    robust: Option<Duration>,
+    signature_file: &str,
+    signers: &mut Vec<u32>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let public = load_public_package(public_file)?;
+    let (events, received) = mpsc::channel();
+    let (run_message, run_context, run_participants) = (
+        message.to_string(),
+        context.to_string(),
+        participants.to_vec(),
+    );
+    let run = thread::spawn(move || {
+        let mut report = |event| {
+            let _ = events.send(event);
+        };
+        match robust {
+            Some(timeout) => request_signature_robust(
+                &run_message,
+                &run_context,
+                &public,
+                &run_participants,
+                timeout,
+                &mut report,
+            ),
+            None => request_signature_with(
+                &run_message,
+                &run_context,
+                &public,
+                &run_participants,
+                &mut report,
+            ),
+        }
+        .map_err(|err| err.to_string())
+    });
+
+    let mut dashboard = Dashboard::new(message, participants);
+    let mut run = Some(run);
+    let mut terminal = ratatui::init();
+    let shown = loop {
+        while let Ok(event) = received.try_recv() {
+            dashboard.apply(event);
+        }
+        if run.as_ref().is_some_and(|run| run.is_finished()) {
+            let result = run.take().map(thread::JoinHandle::join);
+            while let Ok(event) = received.try_recv() {
+                dashboard.apply(event);
+            }
+            match result {
+                Some(Ok(Err(err))) => dashboard.fail(err),
+                Some(Err(_)) => dashboard.fail("The signing run panicked".to_string()),
+                _ => {}
+            }
+        }
+        if let Err(err) = terminal.draw(|frame| dashboard.render(frame)) {
+            break Err(err);
+        }
+        match event::poll(TICK) {
+            Ok(true) => match event::read() {
+                Ok(Event::Key(key))
+                    if key.kind == KeyEventKind::Press
+                        && matches!(key.code, KeyCode::Char('q') | KeyCode::Esc) =>
+                {
+                    break Ok(())
+                }
+                Ok(_) => {}
+                Err(err) => break Err(err),
+            },
+            Ok(false) => {}
+            Err(err) => break Err(err),
+        }
+    };
+    ratatui::restore();
+    signers.extend(dashboard.participants.iter().filter_map(|p| p.index));
+    shown?;
+
+    match (dashboard.signature, dashboard.error) {
+        (Some(signature), _) => {
+            files::write_json(signature_file, &signature.to_vec())?;
+            println!("Threshold signature saved to: {}", signature_file);
+            Ok(())
+        }
+        (None, Some(error)) => Err(error.into()),
+        (None, None) => Err("Signing aborted".into()),
+    }
+}
@@ -0,0 +1,128 @@
+//! Plain Ed25519 keys and signatures from `ed25519` key sets.
+//!
+//! With the FROST(Ed25519, SHA-512) ciphersuite the group key is an ordinary
+//! Ed25519 public key and the aggregate signature `R || z` an ordinary RFC 8032
+//! signature: the challenge is `SHA-512(R || A || M)` without any context string.
+//! Any Ed25519 verifier accepts them, so a threshold key can stand in for a
+//! single-party key wherever one is expected. This module exports the group key
+//! in the formats such tools read.
+
+use crate::ciphersuite::SuiteId;
+use crate::rfc9591::{self, SuiteKeys};
+use crate::{load_key_file, KeyFile};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use std::fmt;
+use std::str::FromStr;
+
+/// Formats the group key can be exported in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyFormat {
+    /// The 32 key bytes in hex.
+    Hex,
+    /// An OpenSSH `authorized_keys` line.
+    Openssh,
+    /// The 32 key bytes in base58, as Solana addresses are written.
+    Base58,
+}
+
+impl fmt::Display for KeyFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            KeyFormat::Hex => "hex",
+            KeyFormat::Openssh => "openssh",
+            KeyFormat::Base58 => "base58",
+        };
+        f.write_str(name)
+    }
+}
+
+impl FromStr for KeyFormat {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "hex" => Ok(KeyFormat::Hex),
+            "openssh" => Ok(KeyFormat::Openssh),
+            "base58" => Ok(KeyFormat::Base58),
+            _ => Err(format!(
+                "Unknown key format {:?}; expected hex, openssh or base58",
+                name
+            )),
+        }
+    }
+}
+
+/// Returns the group key of an `ed25519` key set as an Ed25519 public key.
+///
+/// # Errors
+/// Returns an error if the keys belong to another ciphersuite.
+pub fn public_key(keys: &SuiteKeys) -> Result<[u8; 32], Box<dyn std::error::Error>> {
+    let suite = keys.suite()?;
+    if suite != SuiteId::Ed25519 {
+        return Err(format!(
+            "Keys of the {} ciphersuite are not Ed25519 keys; generate them with --ciphersuite ed25519",
+            suite
+        )
+        .into());
+    }
+    Ok(keys.group_key.as_slice().try_into()?)
+}
+
+/// Verifies an RFC 8032 signature, whether it was made by a threshold of signers or a single key.
+pub fn verify(
+    public_key: &[u8; 32],
+    message: &[u8],
+    signature: &[u8; 64],
+) -> Result<(), Box<dyn std::error::Error>> {
+    rfc9591::verify_suite(SuiteId::Ed25519, public_key, message, signature)
+}
+
+/// Formats a public key as an OpenSSH `authorized_keys` line.
+pub fn openssh_public_key(public_key: &[u8; 32], comment: &str) -> String {
+    // The key blob is the string "ssh-ed25519" followed by the key, both length-prefixed.
+    let mut blob = Vec::new();
+    for field in [&b"ssh-ed25519"[..], &public_key[..]] {
+        blob.extend((field.len() as u32).to_be_bytes());
+        blob.extend(field);
+    }
+    let line = format!("ssh-ed25519 {}", BASE64.encode(blob));
+    if comment.is_empty() {
+        line
+    } else {
+        format!("{} {}", line, comment)
+    }
+}
+
+/// Formats a public key in the given format.
+pub fn format_public_key(public_key: &[u8; 32], format: KeyFormat, comment: &str) -> String {
+    match format {
+        KeyFormat::Hex => hex::encode(public_key),
+        KeyFormat::Openssh => openssh_public_key(public_key, comment),
+        KeyFormat::Base58 => bs58::encode(public_key).into_string(),
+    }
+}
+
+/// Returns the Ed25519 group key of a key file in the given format.
+///
+/// # Arguments
+/// - `key_file`: Path to an `ed25519` key file.
+/// - `format`: The output format.
+/// - `comment`: Comment appended to OpenSSH keys, e.g. `frost@host`.
+pub fn export_public_key(
+    key_file: &str,
+    format: KeyFormat,
+    comment: &str,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let keys = match load_key_file(key_file)? {
+        KeyFile::Suite(keys) => keys,
+        KeyFile::Frost(_) => {
+            return Err(format!(
+                "Key file {} holds frost-dalek keys, not Ed25519 keys",
+                key_file
+            )
+            .into())
+        }
+    };
+    Ok(format_public_key(&public_key(&keys)?, format, comment))
+}
@@ -0,0 +1,321 @@
+//! Enrollment of a new participant into an existing group.
+//!
+//! The new participant gets the next free index, n + 1, and a share on the
+//! group's existing polynomial, so the group key, the commitments and every
+//! other share stay valid. The share is computed by t helpers with the same
+//! rounds as a [repair](crate::repair), as if the share at n + 1 had been lost,
+//! so no helper learns it.
+//!
+//! 1. Every helper runs [`deal_enrollment`] and privately delivers each part to its helper.
+//! 2. Every helper adds the parts it received with
+//!    [`combine_repair`](crate::repair::combine_repair) and sends the sum to the new participant.
+//! 3. The new participant runs [`complete_enrollment`], which checks the share against the
+//!    helpers' public shares and returns it with the public package extended by the new participant.
+//! 4. Every existing holder runs [`accept_enrollment`] with the extended public package, so
+//!    that its share counts the new participant. Refresh, repair and reshare deal to every
+//!    participant up to the share's n, and fail until every holder has accepted.
+
+use crate::files;
+use crate::repair::{
+    add_sums, check_helpers, combine_repair, load_files, split_share, RepairPart, RepairSum,
+};
+use crate::rounds::lagrange_coefficient_at;
+use crate::{
+    integrity, keys_from_shares, load_keys, load_public_package, load_share, public_package_of,
+    share_of, FrostPublicPackage, FrostShare,
+};
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_TABLE;
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::Identity;
+use rand::rngs::OsRng;
+use rand::{CryptoRng, RngCore};
+use std::path::{Path, PathBuf};
+
+/// Returns the index the next enrolled participant gets.
+pub fn next_index(public_package: &FrostPublicPackage) -> u32 {
+    public_package
+        .public_shares
+        .iter()
+        .map(|(_, index)| *index)
+        .max()
+        .unwrap_or(0)
+        + 1
+}
+
+/// Splits `share`'s contribution to the new participant's share into one random part per helper.
+///
+/// # Errors
+/// Returns an error if the share does not belong to the public package's group,
+/// `helpers` does not include this share's holder, or there are fewer than t helpers.
+pub fn deal_enrollment<R: RngCore + CryptoRng>(
+    csprng: &mut R,
+    share: &FrostShare,
+    public_package: &FrostPublicPackage,
+    helpers: &[u32],
+) -> Result<Vec<RepairPart>, Box<dyn std::error::Error>> {
+    if share.group_key != public_package.group_key {
+        return Err("Share does not belong to the public package's group".into());
+    }
+    let index = next_index(public_package);
+    check_helpers(helpers, index, public_package.threshold, index)?;
+    split_share(csprng, share, helpers, index)
+}
+
+/// Adds the helpers' sums into the new participant's share.
+///
+/// Returns the share and the public package extended with the new participant.
+///
+/// # Errors
+/// Returns an error if a helper's sum is missing or is for another index, or the
+/// share does not match the public share implied by the helpers' public shares.
+pub fn complete_enrollment(
+    public_package: &FrostPublicPackage,
+    sums: &[RepairSum],
+) -> Result<(FrostShare, FrostPublicPackage), Box<dyn std::error::Error>> {
+    let index = next_index(public_package);
+    let key = add_sums(sums, index, public_package.threshold, index)?;
+
+    // The new public share is the helpers' public shares interpolated at the new index.
+    let helpers = &sums[0].helpers;
+    let mut expected = RistrettoPoint::identity();
+    for &helper in helpers {
+        let public_share = public_package
+            .public_shares
+            .iter()
+            .find(|(_, share_index)| *share_index == helper)
+            .and_then(|(share_bytes, _)| CompressedRistretto(*share_bytes).decompress())
+            .ok_or_else(|| format!("No public share for helper {}", helper))?;
+        expected += lagrange_coefficient_at(index, helper, helpers) * public_share;
+    }
+    let public_share = &key * &RISTRETTO_BASEPOINT_TABLE;
+    if public_share != expected {
+        return Err(format!(
+            "Enrolled share does not match the helpers' public shares at index {}",
+            index
+        )
+        .into());
+    }
+
+    let mut extended = public_package.clone();
+    extended
+        .public_shares
+        .push((public_share.compress().to_bytes(), index));
+    extended.checksum = Some(integrity::public_package_checksum(&extended));
+    integrity::check_public_package(&extended)?;
+
+    let mut share = FrostShare {
+        group_key: public_package.group_key,
+        private_share: (key.to_bytes(), index),
+        threshold: public_package.threshold,
+        n: index,
+        commitments: public_package.commitments.clone(),
+        checksum: None,
+    };
+    share.checksum = Some(integrity::share_checksum(&share));
+    integrity::check_share(&share)?;
+    Ok((share, extended))
+}
+
+/// Updates an existing holder's share to the participants of the extended public package.
+///
+/// # Errors
+/// Returns an error if the share does not belong to the package's group, or its
+/// public share is not the one listed in the package.
+pub fn accept_enrollment(
+    share: &FrostShare,
+    extended: &FrostPublicPackage,
+) -> Result<FrostShare, Box<dyn std::error::Error>> {
+    if share.group_key != extended.group_key
+        || share.threshold != extended.threshold
+        || share.commitments != extended.commitments
+    {
+        return Err("Share does not belong to the public package's group".into());
+    }
+    let (key_bytes, index) = share.private_share;
+    let key = Scalar::from_canonical_bytes(key_bytes).ok_or("Invalid private key bytes")?;
+    let public_share = (&key * &RISTRETTO_BASEPOINT_TABLE).compress().to_bytes();
+    if !extended.public_shares.contains(&(public_share, index)) {
+        return Err(format!(
+            "Public package does not list the public share of participant {}",
+            index
+        )
+        .into());
+    }
+    let n = next_index(extended) - 1;
+    if n < share.n {
+        return Err(format!(
+            "Public package has {} participants, fewer than the share's {}",
+            n, share.n
+        )
+        .into());
+    }
+
+    let mut accepted = FrostShare {
+        group_key: share.group_key,
+        private_share: share.private_share,
+        threshold: share.threshold,
+        n,
+        commitments: share.commitments.clone(),
+        checksum: None,
+    };
+    accepted.checksum = Some(integrity::share_checksum(&accepted));
+    integrity::check_share(&accepted)?;
+    Ok(accepted)
+}
+
+/// Enrolls a new participant into a key file in-process.
+///
+/// # Arguments
+/// - `key_file`: Path to the file containing the current keys.
+/// - `helpers`: Indices of the holders computing the new share, at least t of them.
+/// - `output_key_file`: Path to save the keys including the new participant.
+/// - `force`: Replace `output_key_file` if it exists.
+pub fn enroll_keys(
+    key_file: &str,
+    helpers: &[u32],
+    output_key_file: &str,
+    force: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    files::check_overwrite(output_key_file, force)?;
+    let frost_keys = load_keys(key_file)?;
+    let public_package = public_package_of(&frost_keys)?;
+    let mut shares = frost_keys
+        .private_shares
+        .iter()
+        .map(|(_, index)| share_of(&frost_keys, *index))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Round 1: every helper splits its contribution.
+    let mut parts = Vec::new();
+    for share in shares
+        .iter()
+        .filter(|share| helpers.contains(&share.private_share.1))
+    {
+        parts.extend(deal_enrollment(
+            &mut OsRng,
+            share,
+            &public_package,
+            helpers,
+        )?);
+    }
+
+    // Round 2: every helper adds the parts addressed to it.
+    let mut sums = Vec::new();
+    for &helper in helpers {
+        let received: Vec<RepairPart> = parts
+            .iter()
+            .filter(|part| part.to == helper)
+            .cloned()
+            .collect();
+        sums.push(combine_repair(helper, &received)?);
+    }
+
+    // Round 3: the new participant adds the sums.
+    let (share, _) = complete_enrollment(&public_package, &sums)?;
+    let index = share.private_share.1;
+    shares.push(share);
+    let enrolled_keys = keys_from_shares(&shares)?;
+
+    files::write_secret_json(output_key_file, &enrolled_keys, force)?;
+
+    println!(
+        "Enrolled participant {}. Keys saved to: {}",
+        index, output_key_file
+    );
+    Ok(())
+}
+
+/// First enrollment round, on every helper: writes `enroll_<from>_to_<to>.json` for every helper into `output_dir`.
+///
+/// Existing part files are only replaced if `force` is set.
+pub fn enroll_deal(
+    share_file: &str,
+    public_file: &str,
+    helpers: &[u32],
+    output_dir: &str,
+    force: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let share = load_share(share_file)?;
+    let public_package = load_public_package(public_file)?;
+    let parts = deal_enrollment(&mut OsRng, &share, &public_package, helpers)?;
+    let paths: Vec<PathBuf> = parts
+        .iter()
+        .map(|part| Path::new(output_dir).join(format!("enroll_{}_to_{}.json", part.from, part.to)))
+        .collect();
+    for path in &paths {
+        files::check_overwrite(path, force)?;
+    }
+    for (part, path) in parts.iter().zip(&paths) {
+        files::write_secret_json(path, part, force)?;
+    }
+
+    println!(
+        "Enrollment parts of participant {} for new participant {} saved to: {}",
+        share.private_share.1,
+        next_index(&public_package),
+        output_dir
+    );
+    Ok(())
+}
+
+/// Last enrollment round, on the new participant: adds the helpers' sums into its share.
+///
+/// # Arguments
+/// - `public_file`: Path to the group's public package.
+/// - `sum_files`: Paths to the sums, one from every helper.
+/// - `output_share_file`: Path to save the new participant's share.
+/// - `output_public_file`: Path to save the public package including the new participant.
+/// - `force`: Replace the output files if they exist.
+pub fn enroll_finish(
+    public_file: &str,
+    sum_files: &[String],
+    output_share_file: &str,
+    output_public_file: &str,
+    force: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    for file in [output_share_file, output_public_file] {
+        files::check_overwrite(file, force)?;
+    }
+    let public_package = load_public_package(public_file)?;
+    let sums: Vec<RepairSum> = load_files(sum_files)?;
+    let (share, extended) = complete_enrollment(&public_package, &sums)?;
+
+    files::write_secret_json(output_share_file, &share, force)?;
+    files::write_json(output_public_file, &extended)?;
+
+    println!(
+        "Share of new participant {} saved to: {}",
+        share.private_share.1, output_share_file
+    );
+    println!("Updated public package saved to: {}", output_public_file);
+    Ok(())
+}
+
+/// After an enrollment, on every existing holder: updates its share to the
+/// participants of the extended public package.
+///
+/// # Arguments
+/// - `share_file`: Path to the holder's share.
+/// - `public_file`: Path to the public package written by [`enroll_finish`].
+/// - `output_share_file`: Path to save the updated share.
+/// - `force`: Replace `output_share_file` if it exists.
+pub fn enroll_accept(
+    share_file: &str,
+    public_file: &str,
+    output_share_file: &str,
+    force: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    files::check_overwrite(output_share_file, force)?;
+    let share = load_share(share_file)?;
+    let extended = load_public_package(public_file)?;
+    let accepted = accept_enrollment(&share, &extended)?;
+
+    files::write_secret_json(output_share_file, &accepted, force)?;
+
+    println!(
+        "Share of participant {} updated to {} participants, saved to: {}",
+        accepted.private_share.1, accepted.n, output_share_file
+    );
+    Ok(())
+}
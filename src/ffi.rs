@@ -0,0 +1,481 @@
+//! C bindings for key generation, signing and verification.
+//!
+//! Built with the `ffi` feature, which also generates the C header
+//! `frost_cli.h` with cbindgen into `$OUT_DIR`; the committed copy in
+//! `include/frost_cli.h` is updated from it with the `write_header` example,
+//! and `tests/ffi.rs` checks that the two match. The API follows a few rules so
+//! that it can be called from C, Go, Swift and other languages with a C FFI:
+//!
+//! - Every function returns a [`FrostStatus`]; `FROST_STATUS_OK` is zero. After
+//!   an error, [`frost_last_error`] describes it.
+//! - Key sets and signing nonces are opaque handles created by this library and
+//!   released with [`frost_key_set_free`] and [`frost_nonces_free`].
+//! - Byte strings returned by the library are [`FrostBuffer`]s released with
+//!   [`frost_buffer_free`], which zeroizes them first. Inputs are pointer and
+//!   length pairs owned by the caller.
+//! - Shares, public packages, commitments and partial signatures are passed as
+//!   the JSON of the CLI's files and of the messages in [`rounds`], so handles
+//!   and files can be mixed freely. Group keys and signatures are raw bytes
+//!   (32 and 64 bytes).
+//! - Outputs are only written when the call succeeds, and panics never cross the
+//!   boundary: they are reported as `FROST_STATUS_PANIC`.
+
+use crate::policy::DEFAULT_CONTEXT;
+use crate::rounds::{self, PartialSignature, SigningCommitment};
+use crate::{
+    generate_key_set_with, integrity, public_package_of, share_of, threshold_sign,
+    verify_signature, FrostKeys, FrostPublicPackage, FrostShare,
+};
+use frost_dalek::signature::SecretKey as SignatureSecretKey;
+use rand::rngs::OsRng;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::ffi::{c_char, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+use zeroize::Zeroize;
+
+/// Result codes of every function of the C API.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FrostStatus {
+    /// The call succeeded.
+    Ok = 0,
+    /// A required pointer was null.
+    NullPointer = 1,
+    /// An argument is out of range, e.g. a threshold above the number of participants.
+    InvalidArgument = 2,
+    /// An input does not decode or fails its integrity checks.
+    Malformed = 3,
+    /// Signing or aggregation failed, e.g. because of an invalid partial signature.
+    SigningFailed = 4,
+    /// The signature does not verify.
+    InvalidSignature = 5,
+    /// The library panicked; this is a bug.
+    Panic = 6,
+}
+
+/// A byte string owned by the library.
+#[repr(C)]
+pub struct FrostBuffer {
+    pub data: *mut u8,
+    pub len: usize,
+}
+
+/// An opaque handle to a key set with all private shares.
+pub struct FrostKeySet(FrostKeys);
+
+/// An opaque handle to the secret nonces of one signer for one signature.
+pub struct FrostNonces(rounds::SigningNonces);
+
+thread_local! {
+    static LAST_ERROR: RefCell<CString> = RefCell::new(CString::default());
+}
+
+struct Error(FrostStatus, String);
+
+fn fail(status: FrostStatus) -> impl Fn(Box<dyn std::error::Error>) -> Error {
+    move |err| Error(status, err.to_string())
+}
+
+/// Runs the body of an API function, recording its error and catching panics.
+fn run(body: impl FnOnce() -> Result<(), Error>) -> FrostStatus {
+    let (status, message) = match catch_unwind(AssertUnwindSafe(body)) {
+        Ok(Ok(())) => return FrostStatus::Ok,
+        Ok(Err(Error(status, message))) => (status, message),
+        Err(_) => (FrostStatus::Panic, "The library panicked".to_string()),
+    };
+    let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = message);
+    status
+}
+
+/// Borrows a caller-owned byte string; `data` may be null if `len` is zero.
+unsafe fn bytes<'a>(data: *const u8, len: usize) -> Result<&'a [u8], Error> {
+    if len == 0 {
+        return Ok(&[]);
+    }
+    if data.is_null() {
+        return Err(Error(FrostStatus::NullPointer, "Null input".to_string()));
+    }
+    Ok(std::slice::from_raw_parts(data, len))
+}
+
+unsafe fn array<'a, const N: usize>(data: *const u8) -> Result<&'a [u8; N], Error> {
+    Ok(bytes(data, N)?.try_into().expect("slice of N bytes"))
+}
+
+unsafe fn handle<'a, T>(handle: *const T) -> Result<&'a T, Error> {
+    handle
+        .as_ref()
+        .ok_or_else(|| Error(FrostStatus::NullPointer, "Null handle".to_string()))
+}
+
+fn check_output<T>(out: *mut T) -> Result<(), Error> {
+    if out.is_null() {
+        return Err(Error(FrostStatus::NullPointer, "Null output".to_string()));
+    }
+    Ok(())
+}
+
+fn json<'a, T: serde::Deserialize<'a>>(input: &'a [u8]) -> Result<T, Error> {
+    serde_json::from_slice(input).map_err(|err| Error(FrostStatus::Malformed, err.to_string()))
+}
+
+fn buffer(contents: Vec<u8>) -> FrostBuffer {
+    let contents = Box::into_raw(contents.into_boxed_slice());
+    FrostBuffer {
+        data: contents as *mut u8,
+        len: contents.len(),
+    }
+}
+
+fn json_buffer<T: serde::Serialize>(value: &T) -> Result<FrostBuffer, Error> {
+    serde_json::to_vec(value)
+        .map(buffer)
+        .map_err(|err| Error(FrostStatus::Malformed, err.to_string()))
+}
+
+fn parse_share(input: &[u8]) -> Result<FrostShare, Error> {
+    let share: FrostShare = json(input)?;
+    integrity::check_share(&share).map_err(fail(FrostStatus::Malformed))?;
+    Ok(share)
+}
+
+/// Returns the message of the last error on this thread.
+///
+/// The string stays valid until the next failing call on the same thread.
+#[no_mangle]
+pub extern "C" fn frost_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ptr())
+}
+
+/// Releases a buffer returned by the library, zeroizing its contents.
+///
+/// # Safety
+/// `buffer` must have been returned by this library and not released before.
+#[no_mangle]
+pub unsafe extern "C" fn frost_buffer_free(buffer: FrostBuffer) {
+    if !buffer.data.is_null() {
+        let mut contents = Box::from_raw(ptr::slice_from_raw_parts_mut(buffer.data, buffer.len));
+        contents.zeroize();
+    }
+}
+
+/// Generates a key set of `n` shares with threshold `t` by distributed key generation.
+///
+/// # Safety
+/// `out` must point to writable memory for a handle.
+#[no_mangle]
+pub unsafe extern "C" fn frost_generate(t: u32, n: u32, out: *mut *mut FrostKeySet) -> FrostStatus {
+    run(|| {
+        check_output(out)?;
+        if t == 0 {
+            return Err(Error(
+                FrostStatus::InvalidArgument,
+                "Threshold must be at least 1".to_string(),
+            ));
+        }
+        // Key generation reports its progress on stdout, which belongs to the caller.
+        let frost_keys =
+            generate_key_set_with(t, n, &mut |_| {}).map_err(fail(FrostStatus::InvalidArgument))?;
+        *out = Box::into_raw(Box::new(FrostKeySet(frost_keys)));
+        Ok(())
+    })
+}
+
+/// Loads a key set from the contents of a key file.
+///
+/// # Safety
+/// `data` must point to `len` readable bytes and `out` to writable memory for a handle.
+#[no_mangle]
+pub unsafe extern "C" fn frost_key_set_from_json(
+    data: *const u8,
+    len: usize,
+    out: *mut *mut FrostKeySet,
+) -> FrostStatus {
+    run(|| {
+        check_output(out)?;
+        let frost_keys: FrostKeys = json(bytes(data, len)?)?;
+        integrity::check_key_set(&frost_keys).map_err(fail(FrostStatus::Malformed))?;
+        *out = Box::into_raw(Box::new(FrostKeySet(frost_keys)));
+        Ok(())
+    })
+}
+
+/// Writes a key set in the format of a key file.
+///
+/// # Safety
+/// `keys` must be a live handle and `out` must point to writable memory for a buffer.
+#[no_mangle]
+pub unsafe extern "C" fn frost_key_set_to_json(
+    keys: *const FrostKeySet,
+    out: *mut FrostBuffer,
+) -> FrostStatus {
+    run(|| {
+        check_output(out)?;
+        *out = json_buffer(&handle(keys)?.0)?;
+        Ok(())
+    })
+}
+
+/// Releases a key set handle. Null is ignored.
+///
+/// # Safety
+/// `keys` must be null or a handle that has not been released before.
+#[no_mangle]
+pub unsafe extern "C" fn frost_key_set_free(keys: *mut FrostKeySet) {
+    if !keys.is_null() {
+        drop(Box::from_raw(keys));
+    }
+}
+
+/// Copies the 32-byte group public key of a key set to `group_key`.
+///
+/// # Safety
+/// `keys` must be a live handle and `group_key` must point to 32 writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn frost_key_set_group_key(
+    keys: *const FrostKeySet,
+    group_key: *mut u8,
+) -> FrostStatus {
+    run(|| {
+        check_output(group_key)?;
+        let keys = handle(keys)?;
+        ptr::copy_nonoverlapping(keys.0.group_key.as_ptr(), group_key, 32);
+        Ok(())
+    })
+}
+
+/// Writes the share of participant `index` (1 to n) in the format of a share file.
+///
+/// # Safety
+/// `keys` must be a live handle and `out` must point to writable memory for a buffer.
+#[no_mangle]
+pub unsafe extern "C" fn frost_key_set_share(
+    keys: *const FrostKeySet,
+    index: u32,
+    out: *mut FrostBuffer,
+) -> FrostStatus {
+    run(|| {
+        check_output(out)?;
+        let share =
+            share_of(&handle(keys)?.0, index).map_err(fail(FrostStatus::InvalidArgument))?;
+        *out = json_buffer(&share)?;
+        Ok(())
+    })
+}
+
+/// Writes the public package of a key set, as needed by [`frost_aggregate`].
+///
+/// # Safety
+/// `keys` must be a live handle and `out` must point to writable memory for a buffer.
+#[no_mangle]
+pub unsafe extern "C" fn frost_key_set_public_package(
+    keys: *const FrostKeySet,
+    out: *mut FrostBuffer,
+) -> FrostStatus {
+    run(|| {
+        check_output(out)?;
+        let public_package =
+            public_package_of(&handle(keys)?.0).map_err(fail(FrostStatus::Malformed))?;
+        *out = json_buffer(&public_package)?;
+        Ok(())
+    })
+}
+
+/// Signs a message with the given signers of a key set in one call.
+///
+/// `signers` are positions in the key set (0 to n - 1), as taken by the CLI's `--signers`.
+///
+/// # Safety
+/// `keys` must be a live handle, `signers` must point to `signers_len` values,
+/// `message` to `message_len` bytes and `signature` to 64 writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn frost_sign(
+    keys: *const FrostKeySet,
+    signers: *const u32,
+    signers_len: usize,
+    message: *const u8,
+    message_len: usize,
+    signature: *mut u8,
+) -> FrostStatus {
+    run(|| {
+        check_output(signature)?;
+        let keys = &handle(keys)?.0;
+        if signers.is_null() && signers_len != 0 {
+            return Err(Error(FrostStatus::NullPointer, "Null signers".to_string()));
+        }
+        let signers = if signers_len == 0 {
+            &[]
+        } else {
+            std::slice::from_raw_parts(signers, signers_len)
+        };
+        let n = keys.private_shares.len() as u32;
+        let signature_bytes = threshold_sign(keys, bytes(message, message_len)?, signers, n)
+            .map_err(fail(FrostStatus::SigningFailed))?;
+        ptr::copy_nonoverlapping(signature_bytes.as_ptr(), signature, 64);
+        Ok(())
+    })
+}
+
+/// First signing round: generates fresh nonces for the holder of a share.
+///
+/// `commitment` receives the JSON commitment to send to the aggregator.
+///
+/// # Safety
+/// `share` must point to `share_len` bytes, `nonces` to writable memory for a
+/// handle and `commitment` to writable memory for a buffer.
+#[no_mangle]
+pub unsafe extern "C" fn frost_commit(
+    share: *const u8,
+    share_len: usize,
+    nonces: *mut *mut FrostNonces,
+    commitment: *mut FrostBuffer,
+) -> FrostStatus {
+    run(|| {
+        check_output(nonces)?;
+        check_output(commitment)?;
+        let share = parse_share(bytes(share, share_len)?)?;
+        let (signing_nonces, signing_commitment) =
+            rounds::commit(&mut OsRng, share.private_share.1);
+        *commitment = json_buffer(&signing_commitment)?;
+        *nonces = Box::into_raw(Box::new(FrostNonces(signing_nonces)));
+        Ok(())
+    })
+}
+
+/// Releases a nonces handle that will not sign. Null is ignored.
+///
+/// # Safety
+/// `nonces` must be null or a handle that has been neither released nor passed to [`frost_sign_share`].
+#[no_mangle]
+pub unsafe extern "C" fn frost_nonces_free(nonces: *mut FrostNonces) {
+    if !nonces.is_null() {
+        drop(Box::from_raw(nonces));
+    }
+}
+
+/// Second signing round: computes the partial signature of the holder of a share.
+///
+/// The nonces handle is always consumed, even on error, so it can never sign twice.
+/// `commitments` is a JSON array with the commitments of all signers.
+///
+/// # Safety
+/// The pointer and length pairs must describe readable memory, `nonces` must be a
+/// handle from [`frost_commit`] that was not consumed before, and `partial_signature`
+/// must point to writable memory for a buffer.
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn frost_sign_share(
+    share: *const u8,
+    share_len: usize,
+    nonces: *mut FrostNonces,
+    message: *const u8,
+    message_len: usize,
+    commitments: *const u8,
+    commitments_len: usize,
+    partial_signature: *mut FrostBuffer,
+) -> FrostStatus {
+    run(|| {
+        if nonces.is_null() {
+            return Err(Error(FrostStatus::NullPointer, "Null nonces".to_string()));
+        }
+        let FrostNonces(signing_nonces) = *Box::from_raw(nonces);
+        check_output(partial_signature)?;
+        let share = parse_share(bytes(share, share_len)?)?;
+        let commitments: Vec<SigningCommitment> = json(bytes(commitments, commitments_len)?)?;
+
+        let (mut key_bytes, index) = share.private_share;
+        let secret_key = SignatureSecretKey::from_bytes(index, key_bytes);
+        key_bytes.zeroize();
+        let secret_key = secret_key.map_err(|_| {
+            Error(
+                FrostStatus::Malformed,
+                "Invalid private key bytes".to_string(),
+            )
+        })?;
+
+        let partial = rounds::sign_share(
+            &secret_key,
+            &share.group_key,
+            signing_nonces,
+            bytes(message, message_len)?,
+            DEFAULT_CONTEXT,
+            &commitments,
+        )
+        .map_err(fail(FrostStatus::SigningFailed))?;
+        *partial_signature = json_buffer(&partial)?;
+        Ok(())
+    })
+}
+
+/// Checks the partial signatures of all signers and combines them into a 64-byte signature.
+///
+/// `commitments` and `partial_signatures` are JSON arrays; `public_package` is a public package file.
+///
+/// # Safety
+/// The pointer and length pairs must describe readable memory and `signature`
+/// must point to 64 writable bytes.
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn frost_aggregate(
+    public_package: *const u8,
+    public_package_len: usize,
+    message: *const u8,
+    message_len: usize,
+    commitments: *const u8,
+    commitments_len: usize,
+    partial_signatures: *const u8,
+    partial_signatures_len: usize,
+    signature: *mut u8,
+) -> FrostStatus {
+    run(|| {
+        check_output(signature)?;
+        let public_package: FrostPublicPackage = json(bytes(public_package, public_package_len)?)?;
+        integrity::check_public_package(&public_package).map_err(fail(FrostStatus::Malformed))?;
+        let commitments: Vec<SigningCommitment> = json(bytes(commitments, commitments_len)?)?;
+        let partials: Vec<PartialSignature> =
+            json(bytes(partial_signatures, partial_signatures_len)?)?;
+        let public_shares: BTreeMap<u32, [u8; 32]> = public_package
+            .public_shares
+            .iter()
+            .map(|&(share, index)| (index, share))
+            .collect();
+
+        let signature_bytes = rounds::aggregate(
+            &public_package.group_key,
+            bytes(message, message_len)?,
+            DEFAULT_CONTEXT,
+            &commitments,
+            &partials,
+            &public_shares,
+        )
+        .map_err(fail(FrostStatus::SigningFailed))?;
+        ptr::copy_nonoverlapping(signature_bytes.as_ptr(), signature, 64);
+        Ok(())
+    })
+}
+
+/// Verifies a 64-byte signature against a 32-byte group public key.
+///
+/// Returns `FROST_STATUS_INVALID_SIGNATURE` if the signature does not verify.
+///
+/// # Safety
+/// `group_key` must point to 32 bytes, `message` to `message_len` bytes and `signature` to 64 bytes.
+#[no_mangle]
+pub unsafe extern "C" fn frost_verify(
+    group_key: *const u8,
+    message: *const u8,
+    message_len: usize,
+    signature: *const u8,
+) -> FrostStatus {
+    run(|| {
+        verify_signature(
+            array(group_key)?,
+            bytes(message, message_len)?,
+            array(signature)?,
+        )
+        .map_err(fail(FrostStatus::InvalidSignature))
+    })
+}
@@ -0,0 +1,140 @@
+//! Writing of output files.
+//!
+//! Every file is written to a temporary file next to its destination, flushed to
+//! disk and then renamed over the destination, so a crash never leaves a
+//! truncated key, share or signature file behind. Missing parent directories are
+//! created. Files holding secrets (key sets, shares and the packages exchanged
+//! during refresh, reshare, repair and enrollment) are created readable by their
+//! owner only, and never replace an existing file unless asked to: without
+//! `force` the temporary file is hard-linked to its destination instead of
+//! renamed over it, which fails if the destination exists, even if it was
+//! created after [`check_overwrite`] looked.
+
+use serde::Serialize;
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use zeroize::Zeroizing;
+
+static TEMP_FILES: AtomicUsize = AtomicUsize::new(0);
+
+/// Permissions of files holding secrets.
+pub const SECRET_MODE: u32 = 0o600;
+/// Permissions of public files.
+pub const PUBLIC_MODE: u32 = 0o644;
+
+/// Writes `value` as pretty-printed JSON to a file readable by its owner only.
+///
+/// Unless `force` is set, an existing file at `path` is not replaced.
+pub fn write_secret_json<T: Serialize>(
+    path: impl AsRef<Path>,
+    value: &T,
+    force: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let contents = Zeroizing::new(serde_json::to_vec_pretty(value)?);
+    write_atomic(path.as_ref(), &contents, SECRET_MODE, force)
+}
+
+/// Writes `value` as pretty-printed JSON to a public file.
+pub fn write_json<T: Serialize>(
+    path: impl AsRef<Path>,
+    value: &T,
+) -> Result<(), Box<dyn std::error::Error>> {
+    write_atomic(
+        path.as_ref(),
+        &serde_json::to_vec_pretty(value)?,
+        PUBLIC_MODE,
+        true,
+    )
+}
+
+/// Replaces the contents of `path` with `contents` in one step.
+///
+/// # Arguments
+/// - `path`: The destination file. Its parent directories are created if missing.
+/// - `contents`: The bytes to write.
+/// - `mode`: The Unix permissions of the new file; ignored on other platforms.
+/// - `replace`: Replace `path` if it exists; otherwise fail without touching it.
+pub fn write_atomic(
+    path: &Path,
+    contents: &[u8],
+    mode: u32,
+    replace: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    fs::create_dir_all(parent)?;
+
+    let temp_path = temp_path(parent, path)?;
+    let result = write_new(&temp_path, contents, mode).and_then(|()| {
+        if replace {
+            fs::rename(&temp_path, path)
+        } else {
+            // Unlike a rename, linking fails if the destination exists.
+            fs::hard_link(&temp_path, path)?;
+            fs::remove_file(&temp_path)
+        }
+    });
+    if let Err(err) = result {
+        let _ = fs::remove_file(&temp_path);
+        if !replace && err.kind() == ErrorKind::AlreadyExists {
+            return Err(exists_error(path));
+        }
+        return Err(format!("Failed to write {}: {}", path.display(), err).into());
+    }
+    Ok(())
+}
+
+/// Returns an error if `path` exists and `force` is not set.
+///
+/// Used before writing key and share files so that a command fails before doing
+/// any work if an output file exists. The write itself checks again.
+pub fn check_overwrite(
+    path: impl AsRef<Path>,
+    force: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let path = path.as_ref();
+    if !force && path.exists() {
+        return Err(exists_error(path));
+    }
+    Ok(())
+}
+
+fn exists_error(path: &Path) -> Box<dyn std::error::Error> {
+    format!(
+        "{} already exists; pass --force to overwrite it",
+        path.display()
+    )
+    .into()
+}
+
+fn temp_path(parent: &Path, path: &Path) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let name = path
+        .file_name()
+        .ok_or_else(|| format!("{} is not a file path", path.display()))?;
+    Ok(parent.join(format!(
+        ".{}.{}-{}.tmp",
+        name.to_string_lossy(),
+        std::process::id(),
+        TEMP_FILES.fetch_add(1, Ordering::Relaxed)
+    )))
+}
+
+fn write_new(path: &Path, contents: &[u8], mode: u32) -> std::io::Result<()> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(mode);
+    }
+    #[cfg(not(unix))]
+    let _ = mode;
+
+    let mut file = options.open(path)?;
+    file.write_all(contents)?;
+    file.sync_all()
+}
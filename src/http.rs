@@ -0,0 +1,437 @@
+//! HTTP signing service.
+//!
+//! [`serve`] exposes a key set over a small JSON REST API so that applications can
+//! request threshold signatures without shelling out to `frost-cli sign`:
+//!
+//! | Method | Path                         | Body                                  | Answer                                |
+//! |--------|------------------------------|---------------------------------------|---------------------------------------|
+//! | POST   | `/signatures`                | `{"message", "signers", "context"}`   | `202 {"id", "status"}`                |
+//! | GET    | `/signatures/{id}`           |                                       | `200 {"id", "status", "error"}`       |
+//! | GET    | `/signatures/{id}/signature` |                                       | `200 {"signature"}`, `409` until done |
+//! | GET    | `/group-key`                 |                                       | `200 {"group_key", "threshold", "n"}` |
+//! | POST   | `/verify`                    | `{"message", "signature", "context"}` | `200 {"valid", "error"}`              |
+//!
+//! Signing requests are queued and processed one at a time by a background worker
+//! using [`threshold_sign_with`]; their `status` moves from `pending` to `done` or `failed`.
+//! The `context` is optional and defaults to [`DEFAULT_CONTEXT`].
+//! Keys and signatures use the same byte-array encoding as the key and signature files.
+//!
+//! With a [`Policy`], the worker evaluates every request on behalf of each signer
+//! before signing, as `sign --policy-file` does, and a refused request fails with
+//! the [`PolicyViolation`](crate::policy::PolicyViolation) as its error.
+//!
+//! The service holds every share, so it is guarded: with an API token, every request
+//! must carry `Authorization: Bearer <token>` and is otherwise answered `401`;
+//! without one, [`serve`] only listens on loopback addresses. Request bodies are
+//! limited to [`MAX_BODY_LEN`] bytes, at most [`MAX_JOBS`] signing requests are
+//! kept, and requests are forgotten [`JOB_TTL`] after they finish, or after they
+//! were submitted if they are still pending then, so a stuck worker cannot fill
+//! the table. An expired pending request is never signed.
+//! With an audit log, every signing request is recorded in it, and a signature
+//! that cannot be recorded is not handed out.
+
+use crate::audit::{self, message_hash, outcome, AuditRecord};
+use crate::policy::{load_policy, Policy, DEFAULT_CONTEXT};
+use crate::{
+    fingerprint, load_keys, signer_indices, threshold_sign_with, verify_signature_with_context,
+    FrostKeys,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::net::ToSocketAddrs;
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use tiny_http::{Header, Response, Server};
+
+/// Environment variable holding the API token of [`serve`].
+pub const TOKEN_VAR: &str = "FROST_API_TOKEN";
+
+/// Largest request body accepted, in bytes.
+pub const MAX_BODY_LEN: u64 = 1 << 20;
+
+/// Most signing requests kept at once, pending or finished.
+pub const MAX_JOBS: usize = 1024;
+
+/// How long a finished signing request can still be queried, and a pending one waits to be signed.
+pub const JOB_TTL: Duration = Duration::from_secs(600);
+
+/// Status of a queued signing request.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SigningStatus {
+    Pending,
+    Done,
+    Failed,
+}
+
+#[derive(Deserialize)]
+struct SignRequest {
+    message: String,
+    signers: Vec<u32>,
+    #[serde(default = "default_context")]
+    context: String,
+}
+
+#[derive(Deserialize)]
+struct VerifyRequest {
+    message: String,
+    signature: Vec<u8>,
+    #[serde(default = "default_context")]
+    context: String,
+}
+
+fn default_context() -> String {
+    DEFAULT_CONTEXT.to_string()
+}
+
+struct SigningJob {
+    message: String,
+    signers: Vec<u32>,
+    context: String,
+    status: SigningStatus,
+    signature: Option<[u8; 64]>,
+    error: Option<String>,
+    /// When the request was submitted.
+    submitted: Instant,
+    /// When the request stopped being pending.
+    finished: Option<Instant>,
+}
+
+impl SigningJob {
+    /// Returns true once the request is [`JOB_TTL`] past its end, or past its submission while pending.
+    fn expired(&self) -> bool {
+        self.finished.unwrap_or(self.submitted).elapsed() >= JOB_TTL
+    }
+}
+
+/// The signing requests kept by the service, by id.
+struct Jobs {
+    next_id: u64,
+    jobs: BTreeMap<u64, SigningJob>,
+}
+
+/// The state behind the HTTP API: the key set, its policy and the signing requests still kept.
+pub struct SigningService {
+    frost_keys: FrostKeys,
+    policy: Option<Policy>,
+    token: Option<String>,
+    audit_log: Option<String>,
+    jobs: Mutex<Jobs>,
+    queue: Mutex<Sender<u64>>,
+}
+
+impl SigningService {
+    /// Creates the service and starts the worker that processes signing requests.
+    ///
+    /// With a `policy`, every signing request is evaluated against it before signing.
+    /// With a `token`, [`authorize`](Self::authorize) only accepts requests bearing it.
+    /// With an `audit_log`, every signing request is appended to it.
+    pub fn start(
+        frost_keys: FrostKeys,
+        policy: Option<Policy>,
+        token: Option<String>,
+        audit_log: Option<String>,
+    ) -> Arc<Self> {
+        let (sender, receiver) = mpsc::channel();
+        let service = Arc::new(SigningService {
+            frost_keys,
+            policy,
+            token,
+            audit_log,
+            jobs: Mutex::new(Jobs {
+                next_id: 0,
+                jobs: BTreeMap::new(),
+            }),
+            queue: Mutex::new(sender),
+        });
+
+        let worker = Arc::clone(&service);
+        thread::spawn(move || {
+            for id in receiver {
+                worker.run_job(id);
+            }
+        });
+        service
+    }
+
+    fn run_job(&self, id: u64) {
+        let Some((message, signers, context)) = self.jobs.lock().unwrap().get(id).map(|job| {
+            (
+                job.message.clone(),
+                job.signers.clone(),
+                job.context.clone(),
+            )
+        }) else {
+            return;
+        };
+        let mut result = self.sign(message.as_bytes(), &signers, &context);
+        if let Some(log_file) = &self.audit_log {
+            let record = AuditRecord {
+                operation: "http-sign".to_string(),
+                key_fingerprint: Some(fingerprint(&self.frost_keys.group_key)),
+                message_hashes: vec![message_hash(message.as_bytes())],
+                context: Some(context),
+                signers: signer_indices(&self.frost_keys, &signers).unwrap_or_default(),
+                outcome: outcome(&result),
+            };
+            if let Err(err) = audit::append(log_file, record) {
+                result = Err(format!("Failed to append to the audit log: {}", err).into());
+            }
+        }
+
+        let mut jobs = self.jobs.lock().unwrap();
+        let Some(job) = jobs.jobs.get_mut(&id) else {
+            return;
+        };
+        match result {
+            Ok(signature) => {
+                job.status = SigningStatus::Done;
+                job.signature = Some(signature);
+            }
+            Err(err) => {
+                job.status = SigningStatus::Failed;
+                job.error = Some(err.to_string());
+            }
+        }
+        job.finished = Some(Instant::now());
+    }
+
+    /// Evaluates a signing request against the policy, if any, and signs it.
+    fn sign(
+        &self,
+        message: &[u8],
+        signers: &[u32],
+        context: &str,
+    ) -> Result<[u8; 64], Box<dyn std::error::Error>> {
+        if let Some(policy) = &self.policy {
+            let indices = signer_indices(&self.frost_keys, signers)?;
+            policy.enforce(&self.frost_keys.group_key, &indices, message, context)?;
+        }
+        let n = self.frost_keys.private_shares.len() as u32;
+        threshold_sign_with(&self.frost_keys, message, context, signers, n, &mut |_| {})
+    }
+
+    /// Returns true if a request with this `Authorization` header may use the API.
+    pub fn authorize(&self, authorization: Option<&str>) -> bool {
+        let Some(token) = &self.token else {
+            return true;
+        };
+        let presented = authorization
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .unwrap_or_default();
+        // Compare every byte, so the time taken does not tell how much of the token matched.
+        presented.len() == token.len()
+            && presented
+                .bytes()
+                .zip(token.bytes())
+                .fold(0, |diff, (a, b)| diff | (a ^ b))
+                == 0
+    }
+
+    /// Answers one API request with a status code and a JSON body.
+    pub fn handle(&self, method: &str, path: &str, body: &str) -> (u16, serde_json::Value) {
+        let segments: Vec<&str> = path
+            .split('?')
+            .next()
+            .unwrap_or_default()
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .collect();
+
+        match (method, segments.as_slice()) {
+            ("POST", ["signatures"]) => self.submit(body),
+            ("GET", ["signatures", id]) => self.status(id),
+            ("GET", ["signatures", id, "signature"]) => self.signature(id),
+            ("GET", ["group-key"]) => (
+                200,
+                json!({
+                    "group_key": self.frost_keys.group_key,
+                    "threshold": self.frost_keys.threshold,
+                    "n": self.frost_keys.private_shares.len(),
+                }),
+            ),
+            ("POST", ["verify"]) => self.verify(body),
+            _ => error(404, "Not found"),
+        }
+    }
+
+    fn submit(&self, body: &str) -> (u16, serde_json::Value) {
+        let request: SignRequest = match serde_json::from_str(body) {
+            Ok(request) => request,
+            Err(err) => return error(400, &format!("Invalid signing request: {}", err)),
+        };
+
+        let id = {
+            let mut jobs = self.jobs.lock().unwrap();
+            jobs.jobs.retain(|_, job| !job.expired());
+            if jobs.jobs.len() >= MAX_JOBS {
+                return error(429, "Too many signing requests");
+            }
+            let id = jobs.next_id;
+            jobs.next_id += 1;
+            jobs.jobs.insert(
+                id,
+                SigningJob {
+                    message: request.message,
+                    signers: request.signers,
+                    context: request.context,
+                    status: SigningStatus::Pending,
+                    signature: None,
+                    error: None,
+                    submitted: Instant::now(),
+                    finished: None,
+                },
+            );
+            id
+        };
+        if self.queue.lock().unwrap().send(id).is_err() {
+            return error(503, "Signing worker is not running");
+        }
+        (202, json!({ "id": id, "status": SigningStatus::Pending }))
+    }
+
+    fn status(&self, id: &str) -> (u16, serde_json::Value) {
+        let jobs = self.jobs.lock().unwrap();
+        match id.parse::<u64>().ok().and_then(|id| jobs.get(id)) {
+            Some(job) => (
+                200,
+                json!({ "id": id, "status": job.status, "error": job.error }),
+            ),
+            None => error(404, "Unknown signing request"),
+        }
+    }
+
+    fn signature(&self, id: &str) -> (u16, serde_json::Value) {
+        let jobs = self.jobs.lock().unwrap();
+        match id.parse::<u64>().ok().and_then(|id| jobs.get(id)) {
+            Some(SigningJob {
+                signature: Some(signature),
+                ..
+            }) => (200, json!({ "signature": signature.to_vec() })),
+            Some(job) => (
+                409,
+                json!({ "error": "Signature is not available", "status": job.status }),
+            ),
+            None => error(404, "Unknown signing request"),
+        }
+    }
+
+    fn verify(&self, body: &str) -> (u16, serde_json::Value) {
+        let request: VerifyRequest = match serde_json::from_str(body) {
+            Ok(request) => request,
+            Err(err) => return error(400, &format!("Invalid verification request: {}", err)),
+        };
+        let signature: [u8; 64] = match request.signature.try_into() {
+            Ok(signature) => signature,
+            Err(_) => return error(400, "Invalid length for threshold signature"),
+        };
+
+        match verify_signature_with_context(
+            &self.frost_keys.group_key,
+            request.message.as_bytes(),
+            &request.context,
+            &signature,
+        ) {
+            Ok(()) => (200, json!({ "valid": true, "error": null })),
+            Err(err) => (200, json!({ "valid": false, "error": err.to_string() })),
+        }
+    }
+}
+
+impl Jobs {
+    /// Returns a signing request that has not expired yet.
+    fn get(&self, id: u64) -> Option<&SigningJob> {
+        self.jobs.get(&id).filter(|job| !job.expired())
+    }
+}
+
+fn error(status: u16, message: &str) -> (u16, serde_json::Value) {
+    (status, json!({ "error": message }))
+}
+
+/// Serves the HTTP signing API for the keys in `key_file`.
+///
+/// # Arguments
+/// - `key_file`: Path to the JSON file containing the keys.
+/// - `address`: Address to listen on, e.g. `127.0.0.1:8080`.
+/// - `policy_file`: Path to a signing policy every request is evaluated against, if any.
+/// - `token`: API token every request must bear, or `None` to serve loopback clients only.
+/// - `audit_log`: Path to the audit log every signing request is recorded in, if any.
+///
+/// # Errors
+/// Returns an error if the keys or the policy cannot be loaded, or if `address`
+/// is not a loopback address and no token is given.
+pub fn serve(
+    key_file: &str,
+    address: &str,
+    policy_file: Option<&str>,
+    token: Option<String>,
+    audit_log: Option<String>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let token = token.filter(|token| !token.is_empty());
+    if token.is_none() {
+        let loopback = address
+            .to_socket_addrs()
+            .map_err(|err| format!("Invalid address {}: {}", address, err))?
+            .all(|socket_address| socket_address.ip().is_loopback());
+        if !loopback {
+            return Err(format!(
+                "Refusing to serve on {} without an API token; set {}",
+                address, TOKEN_VAR
+            )
+            .into());
+        }
+        eprintln!(
+            "Warning: no API token set ({}), so any local process can request signatures",
+            TOKEN_VAR
+        );
+    }
+    let policy = policy_file.map(load_policy).transpose()?;
+    let service = SigningService::start(load_keys(key_file)?, policy, token, audit_log);
+    let server =
+        Server::http(address).map_err(|err| format!("Failed to listen on {}: {}", address, err))?;
+    println!("Signing service listening on http://{}", address);
+
+    let content_type = Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..])
+        .map_err(|_| "Invalid Content-Type header")?;
+    for mut request in server.incoming_requests() {
+        let authorization = request
+            .headers()
+            .iter()
+            .find(|header| header.field.equiv("Authorization"))
+            .map(|header| header.value.to_string());
+        let (status, answer) = if !service.authorize(authorization.as_deref()) {
+            error(401, "Missing or invalid API token")
+        } else if request
+            .body_length()
+            .is_some_and(|len| len as u64 > MAX_BODY_LEN)
+        {
+            error(413, "Request body exceeds the size limit")
+        } else {
+            let mut body = String::new();
+            match request
+                .as_reader()
+                .take(MAX_BODY_LEN + 1)
+                .read_to_string(&mut body)
+            {
+                Ok(len) if len as u64 > MAX_BODY_LEN => {
+                    error(413, "Request body exceeds the size limit")
+                }
+                Ok(_) => service.handle(request.method().as_str(), request.url(), &body),
+                Err(err) => error(400, &format!("Invalid request body: {}", err)),
+            }
+        };
+
+        let response = Response::from_string(answer.to_string())
+            .with_status_code(status)
+            .with_header(content_type.clone());
+        if let Err(err) = request.respond(response) {
+            eprintln!("Failed to send response: {}", err);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,373 @@
+//! Inspection of key sets, shares, encrypted shares, public packages and signature files.
+//!
+//! [`inspect_file`] recognises which kind of file it is given, summarises it and
+//! checks that every embedded key and signature decodes and that the file passes
+//! its [`integrity`](crate::integrity) check, so that a broken file is found before
+//! it is used for signing. Encrypted shares are inspected without decrypting
+//! them, from the fields they keep in the clear.
+//!
+//! A bare signature file does not say which ciphersuite made it, and the suites
+//! share signature lengths, so its signatures are only decoded when the caller
+//! names the suite; otherwise just their lengths are reported.
+
+use crate::ceremony::{EncryptedShare, MAX_M_COST, MAX_P_COST, MAX_T_COST};
+use crate::ciphersuite::SuiteId;
+use crate::integrity::{check_key_set, check_public_package, check_share};
+use crate::rfc9591::{self, check_keys, SuiteKeys};
+use crate::{fingerprint, BatchSignature, FrostKeys, FrostPublicPackage, FrostShare};
+use curve25519_dalek::ristretto::CompressedRistretto;
+use frost_dalek::signature::SecretKey as SignatureSecretKey;
+use frost_dalek::signature::ThresholdSignature;
+use frost_dalek::GroupKey;
+use serde::de::IgnoredAny;
+use serde::Deserialize;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::fs;
+use zeroize::Zeroizing;
+
+/// The kinds of file produced by this tool.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileKind {
+    KeySet,
+    Share,
+    EncryptedShare,
+    PublicPackage,
+    Signature,
+    BatchSignature,
+}
+
+impl fmt::Display for FileKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            FileKind::KeySet => "key set",
+            FileKind::Share => "share",
+            FileKind::EncryptedShare => "encrypted share",
+            FileKind::PublicPackage => "public package",
+            FileKind::Signature => "signature",
+            FileKind::BatchSignature => "batch signature",
+        };
+        f.write_str(name)
+    }
+}
+
+/// What [`inspect_file`] found in a file.
+#[derive(Debug)]
+pub struct Inspection {
+    pub kind: FileKind,
+    pub threshold: Option<u32>,
+    pub n: Option<u32>,
+    pub indices: Vec<u32>,
+    /// The ciphersuite of a key set, or the one signatures were decoded with.
+    pub ciphersuite: Option<SuiteId>,
+    pub group_key: Option<Vec<u8>>,
+    /// The Argon2id memory cost in KiB, passes and parallelism of an encrypted share.
+    pub key_derivation: Option<(u32, u32, u32)>,
+    pub signatures: usize,
+    /// The distinct lengths of the signatures, in bytes.
+    pub signature_lengths: Vec<usize>,
+    pub problems: Vec<String>,
+}
+
+impl Inspection {
+    fn new(kind: FileKind) -> Self {
+        Inspection {
+            kind,
+            threshold: None,
+            n: None,
+            indices: Vec::new(),
+            ciphersuite: None,
+            group_key: None,
+            key_derivation: None,
+            signatures: 0,
+            signature_lengths: Vec::new(),
+            problems: Vec::new(),
+        }
+    }
+
+    /// Returns `true` if every embedded value decoded and the file is consistent.
+    pub fn is_valid(&self) -> bool {
+        self.problems.is_empty()
+    }
+
+    fn check_group_key(&mut self, group_key: [u8; 32]) {
+        self.group_key = Some(group_key.to_vec());
+        if GroupKey::from_bytes(group_key).is_err() {
+            self.problems
+                .push("Group public key does not decode".to_string());
+        }
+    }
+
+    fn check_secret_share(&mut self, key_bytes: [u8; 32], index: u32) {
+        self.indices.push(index);
+        if SignatureSecretKey::from_bytes(index, key_bytes).is_err() {
+            self.problems.push(format!(
+                "Private share of participant {} does not decode",
+                index
+            ));
+        }
+    }
+
+    fn check_public_share(&mut self, share_bytes: [u8; 32], index: u32) {
+        self.indices.push(index);
+        if CompressedRistretto(share_bytes).decompress().is_none() {
+            self.problems.push(format!(
+                "Public share of participant {} does not decode",
+                index
+            ));
+        }
+    }
+
+    /// Decodes a signature with the inspection's ciphersuite, or only records its length if there is none.
+    fn check_signature(&mut self, signature: &[u8], label: &str) {
+        self.signatures += 1;
+        if !self.signature_lengths.contains(&signature.len()) {
+            self.signature_lengths.push(signature.len());
+        }
+        let Some(suite) = self.ciphersuite else {
+            return;
+        };
+        let decodes = match suite {
+            SuiteId::FrostDalek => <[u8; 64]>::try_from(signature)
+                .map(|bytes| ThresholdSignature::from_bytes(bytes).is_ok())
+                .unwrap_or(false),
+            _ => rfc9591::check_signature_encoding(suite, signature).is_ok(),
+        };
+        if !decodes {
+            self.problems.push(format!(
+                "{} of {} bytes does not decode as a {} signature",
+                label,
+                signature.len(),
+                suite
+            ));
+        }
+    }
+
+    /// Checks the fields an encrypted share keeps in the clear, without decrypting it.
+    fn check_encrypted_share(&mut self, encrypted: &EncryptedShare) {
+        self.check_group_key(encrypted.group_key);
+        self.indices.push(encrypted.index);
+        let (m_cost, t_cost, p_cost) = (encrypted.m_cost, encrypted.t_cost, encrypted.p_cost);
+        self.key_derivation = Some((m_cost, t_cost, p_cost));
+        if m_cost > MAX_M_COST || t_cost > MAX_T_COST || p_cost > MAX_P_COST {
+            self.problems.push(format!(
+                "Key derivation parameters exceed the limits m_cost <= {}, t_cost <= {}, p_cost <= {}",
+                MAX_M_COST, MAX_T_COST, MAX_P_COST
+            ));
+        }
+        for (name, value) in [
+            ("Salt", &encrypted.salt),
+            ("Nonce", &encrypted.nonce),
+            ("Ciphertext", &encrypted.ciphertext),
+        ] {
+            if hex::decode(value).is_err() {
+                self.problems.push(format!("{} is not valid hex", name));
+            }
+        }
+    }
+
+    /// Records the outcome of the file's integrity check, unless a problem was already found.
+    fn check_integrity(&mut self, result: Result<(), Box<dyn std::error::Error>>) {
+        if let (true, Err(err)) = (self.is_valid(), result) {
+            self.problems.push(err.to_string());
+        }
+    }
+
+    /// Checks threshold and participant indices against each other.
+    fn check_parameters(&mut self, threshold: u32, n: u32) {
+        self.threshold = Some(threshold);
+        self.n = Some(n);
+        if threshold == 0 || threshold > n {
+            self.problems.push(format!(
+                "Threshold {} is not between 1 and the {} participants",
+                threshold, n
+            ));
+        }
+
+        let mut seen = BTreeSet::new();
+        for &index in &self.indices {
+            if index == 0 || index > n {
+                self.problems.push(format!(
+                    "Participant index {} is not between 1 and {}",
+                    index, n
+                ));
+            }
+            if !seen.insert(index) {
+                self.problems
+                    .push(format!("Participant index {} appears twice", index));
+            }
+        }
+    }
+}
+
+impl fmt::Display for Inspection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "File type:    {}", self.kind)?;
+        if let Some(ciphersuite) = self.ciphersuite {
+            writeln!(f, "Ciphersuite:  {}", ciphersuite)?;
+        }
+        if let Some(threshold) = self.threshold {
+            writeln!(f, "Threshold:    {}", threshold)?;
+        }
+        if let Some(n) = self.n {
+            writeln!(f, "Participants: {}", n)?;
+        }
+        if !self.indices.is_empty() {
+            let indices: Vec<String> = self.indices.iter().map(u32::to_string).collect();
+            writeln!(f, "Indices:      {}", indices.join(", "))?;
+        }
+        if let Some(group_key) = &self.group_key {
+            writeln!(f, "Group key:    {}", hex::encode(group_key))?;
+            writeln!(f, "Fingerprint:  {}", fingerprint(group_key))?;
+        }
+        if let Some((m_cost, t_cost, p_cost)) = self.key_derivation {
+            writeln!(
+                f,
+                "Argon2id:     m_cost = {} KiB, t_cost = {}, p_cost = {}",
+                m_cost, t_cost, p_cost
+            )?;
+        }
+        if self.signatures > 0 {
+            let lengths: Vec<String> = self
+                .signature_lengths
+                .iter()
+                .map(usize::to_string)
+                .collect();
+            writeln!(
+                f,
+                "Signatures:   {} of {} bytes",
+                self.signatures,
+                lengths.join(" or ")
+            )?;
+        }
+        if self.signatures > 0 && self.ciphersuite.is_none() {
+            write!(
+                f,
+                "Status:       signatures not decoded; name their ciphersuite to check them"
+            )
+        } else if self.is_valid() {
+            write!(f, "Status:       all embedded values decode")
+        } else {
+            write!(f, "Status:       {} problem(s) found", self.problems.len())?;
+            for problem in &self.problems {
+                write!(f, "\n  - {}", problem)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// The fields that tell the object file formats apart. Every value is skipped
+/// without being parsed, so no copy of a private share is made before the typed one.
+#[derive(Deserialize)]
+struct ObjectFormat {
+    ciphersuite: Option<IgnoredAny>,
+    private_shares: Option<IgnoredAny>,
+    private_share: Option<IgnoredAny>,
+    public_shares: Option<IgnoredAny>,
+    ciphertext: Option<IgnoredAny>,
+}
+
+/// Recognises and checks a key set, share, encrypted share, public package or signature file.
+///
+/// Signatures are not decoded, as their ciphersuite is unknown; see
+/// [`inspect_file_with_suite`].
+pub fn inspect_file(path: &str) -> Result<Inspection, Box<dyn std::error::Error>> {
+    inspect_file_with_suite(path, None)
+}
+
+/// Recognises and checks a file like [`inspect_file`], decoding the signatures
+/// of a signature file with `ciphersuite` if given.
+///
+/// The file is deserialized straight into its typed form, and its text is
+/// zeroized once parsed, as in [`load_key_file`](crate::load_key_file).
+///
+/// # Errors
+/// Returns an error if the file cannot be read or is not one of the known formats.
+/// Values that fail to decode are not errors: they are listed in [`Inspection::problems`].
+pub fn inspect_file_with_suite(
+    path: &str,
+    ciphersuite: Option<SuiteId>,
+) -> Result<Inspection, Box<dyn std::error::Error>> {
+    let contents = Zeroizing::new(fs::read_to_string(path)?);
+    if contents.trim_start().starts_with('[') {
+        return inspect_signatures(&contents, ciphersuite);
+    }
+
+    let format: ObjectFormat = serde_json::from_str(&contents)?;
+    let inspection = if format.ciphersuite.is_some() {
+        let keys: SuiteKeys = serde_json::from_str(&contents)?;
+        let mut inspection = Inspection::new(FileKind::KeySet);
+        inspection.ciphersuite = Some(keys.suite()?);
+        inspection.group_key = Some(keys.group_key.clone());
+        inspection.indices = keys.private_shares.iter().map(|(_, i)| *i).collect();
+        inspection.check_parameters(keys.threshold, keys.private_shares.len() as u32);
+        inspection.check_integrity(check_keys(&keys));
+        inspection
+    } else if format.private_shares.is_some() {
+        let frost_keys: FrostKeys = serde_json::from_str(&contents)?;
+        let mut inspection = Inspection::new(FileKind::KeySet);
+        inspection.ciphersuite = Some(SuiteId::FrostDalek);
+        inspection.check_group_key(frost_keys.group_key);
+        for &(key_bytes, index) in &frost_keys.private_shares {
+            inspection.check_secret_share(key_bytes, index);
+        }
+        inspection.check_parameters(frost_keys.threshold, frost_keys.private_shares.len() as u32);
+        inspection.check_integrity(check_key_set(&frost_keys));
+        inspection
+    } else if format.private_share.is_some() {
+        let frost_share: FrostShare = serde_json::from_str(&contents)?;
+        let mut inspection = Inspection::new(FileKind::Share);
+        inspection.check_group_key(frost_share.group_key);
+        let (key_bytes, index) = frost_share.private_share;
+        inspection.check_secret_share(key_bytes, index);
+        inspection.check_parameters(frost_share.threshold, frost_share.n);
+        inspection.check_integrity(check_share(&frost_share));
+        inspection
+    } else if format.public_shares.is_some() {
+        let public_package: FrostPublicPackage = serde_json::from_str(&contents)?;
+        let mut inspection = Inspection::new(FileKind::PublicPackage);
+        inspection.check_group_key(public_package.group_key);
+        for &(share_bytes, index) in &public_package.public_shares {
+            inspection.check_public_share(share_bytes, index);
+        }
+        inspection.check_parameters(
+            public_package.threshold,
+            public_package.public_shares.len() as u32,
+        );
+        inspection.check_integrity(check_public_package(&public_package));
+        inspection
+    } else if format.ciphertext.is_some() {
+        let encrypted: EncryptedShare = serde_json::from_str(&contents)?;
+        let mut inspection = Inspection::new(FileKind::EncryptedShare);
+        inspection.check_encrypted_share(&encrypted);
+        inspection
+    } else {
+        return Err(unrecognized());
+    };
+    Ok(inspection)
+}
+
+/// Checks a signature file, or a batch signature file.
+fn inspect_signatures(
+    contents: &str,
+    ciphersuite: Option<SuiteId>,
+) -> Result<Inspection, Box<dyn std::error::Error>> {
+    if let Ok(signature) = serde_json::from_str::<Vec<u8>>(contents) {
+        let mut inspection = Inspection::new(FileKind::Signature);
+        inspection.ciphersuite = ciphersuite;
+        inspection.check_signature(&signature, "Signature");
+        return Ok(inspection);
+    }
+    let batch: Vec<BatchSignature> = serde_json::from_str(contents).map_err(|_| unrecognized())?;
+    let mut inspection = Inspection::new(FileKind::BatchSignature);
+    inspection.ciphersuite = ciphersuite;
+    for (k, entry) in batch.iter().enumerate() {
+        inspection.check_signature(&entry.signature, &format!("Signature {}", k));
+    }
+    Ok(inspection)
+}
+
+fn unrecognized() -> Box<dyn std::error::Error> {
+    "Unrecognized file: not a key set, share, public package or signature".into()
+}
@@ -0,0 +1,281 @@
+//! Integrity checks for key, share and public package files.
+//!
+//! Every file written by this tool carries the commitments to the group's secret
+//! polynomial, produced during key generation, and a SHA-256 checksum over its
+//! contents. When a file is loaded, each value is checked against the others:
+//!
+//! - the group key and every commitment must decode, and the first commitment is the group key;
+//! - the threshold is the number of commitments;
+//! - every share's public key must lie on the committed polynomial;
+//! - the checksum must match the contents.
+//!
+//! The first failing check is reported as an error naming the corrupted field.
+//! A file with commitments must also carry its checksum. Files written before
+//! commitments and checksums were introduced cannot be checked against them, so
+//! they are rejected unless [`set_allow_legacy`] is enabled (the CLI's
+//! `--allow-legacy`), in which case a warning is printed and only the checks that
+//! do not depend on them are run.
+
+use crate::{FrostKeys, FrostPublicPackage, FrostShare};
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_TABLE;
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::Identity;
+use sha2::{Digest, Sha256};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static ALLOW_LEGACY: AtomicBool = AtomicBool::new(false);
+
+/// Allows or rejects files written without commitments and checksum.
+pub fn set_allow_legacy(enabled: bool) {
+    ALLOW_LEGACY.store(enabled, Ordering::Relaxed);
+}
+
+/// Returns `true` if files without commitments and checksum are loaded.
+pub fn allow_legacy() -> bool {
+    ALLOW_LEGACY.load(Ordering::Relaxed)
+}
+
+/// Returns the checksum of a key set, over every field except the checksum itself.
+pub fn key_set_checksum(frost_keys: &FrostKeys) -> String {
+    let mut h = Sha256::new();
+    h.update(b"frost-cli key set");
+    h.update(frost_keys.group_key);
+    h.update(frost_keys.threshold.to_be_bytes());
+    for (key_bytes, index) in &frost_keys.private_shares {
+        h.update(index.to_be_bytes());
+        h.update(key_bytes);
+    }
+    update_commitments(&mut h, &frost_keys.commitments);
+    hex::encode(h.finalize())
+}
+
+/// Returns the checksum of a share, over every field except the checksum itself.
+pub fn share_checksum(frost_share: &FrostShare) -> String {
+    let mut h = Sha256::new();
+    h.update(b"frost-cli share");
+    h.update(frost_share.group_key);
+    h.update(frost_share.threshold.to_be_bytes());
+    h.update(frost_share.n.to_be_bytes());
+    let (key_bytes, index) = &frost_share.private_share;
+    h.update(index.to_be_bytes());
+    h.update(key_bytes);
+    update_commitments(&mut h, &frost_share.commitments);
+    hex::encode(h.finalize())
+}
+
+/// Returns the checksum of a public package, over every field except the checksum itself.
+pub fn public_package_checksum(public_package: &FrostPublicPackage) -> String {
+    let mut h = Sha256::new();
+    h.update(b"frost-cli public package");
+    h.update(public_package.group_key);
+    h.update(public_package.threshold.to_be_bytes());
+    for (share_bytes, index) in &public_package.public_shares {
+        h.update(index.to_be_bytes());
+        h.update(share_bytes);
+    }
+    update_commitments(&mut h, &public_package.commitments);
+    hex::encode(h.finalize())
+}
+
+fn update_commitments(h: &mut Sha256, commitments: &[[u8; 32]]) {
+    h.update((commitments.len() as u32).to_be_bytes());
+    for commitment in commitments {
+        h.update(commitment);
+    }
+}
+
+/// Checks a key set for corruption or tampering.
+///
+/// # Errors
+/// Returns an error naming the first field that fails its check.
+pub fn check_key_set(frost_keys: &FrostKeys) -> Result<(), Box<dyn std::error::Error>> {
+    let commitments = check_commitments(
+        &frost_keys.group_key,
+        &frost_keys.commitments,
+        frost_keys.threshold,
+    )?;
+    for (position, (key_bytes, index)) in frost_keys.private_shares.iter().enumerate() {
+        check_private_share(
+            &format!("private_shares[{}]", position),
+            key_bytes,
+            *index,
+            &commitments,
+        )?;
+    }
+    check_checksum(
+        frost_keys.checksum.as_deref(),
+        &key_set_checksum(frost_keys),
+        !frost_keys.commitments.is_empty(),
+    )
+}
+
+/// Checks a participant's share for corruption or tampering.
+///
+/// # Errors
+/// Returns an error naming the first field that fails its check.
+pub fn check_share(frost_share: &FrostShare) -> Result<(), Box<dyn std::error::Error>> {
+    let commitments = check_commitments(
+        &frost_share.group_key,
+        &frost_share.commitments,
+        frost_share.threshold,
+    )?;
+    let (key_bytes, index) = &frost_share.private_share;
+    check_private_share("private_share", key_bytes, *index, &commitments)?;
+    check_checksum(
+        frost_share.checksum.as_deref(),
+        &share_checksum(frost_share),
+        !frost_share.commitments.is_empty(),
+    )
+}
+
+/// Checks a public package for corruption or tampering.
+///
+/// # Errors
+/// Returns an error naming the first field that fails its check.
+pub fn check_public_package(
+    public_package: &FrostPublicPackage,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let commitments = check_commitments(
+        &public_package.group_key,
+        &public_package.commitments,
+        public_package.threshold,
+    )?;
+    for (position, (share_bytes, index)) in public_package.public_shares.iter().enumerate() {
+        let field = format!("public_shares[{}]", position);
+        let public_share = decode_point(&field, share_bytes)?;
+        check_on_polynomial(&field, &public_share, *index, &commitments)?;
+    }
+    check_checksum(
+        public_package.checksum.as_deref(),
+        &public_package_checksum(public_package),
+        !public_package.commitments.is_empty(),
+    )
+}
+
+/// Returns the public key share of participant `index` implied by the commitments.
+pub fn evaluate_commitments(commitments: &[RistrettoPoint], index: u32) -> RistrettoPoint {
+    let x = Scalar::from(index);
+    commitments
+        .iter()
+        .rev()
+        .fold(RistrettoPoint::identity(), |acc, commitment| {
+            acc * x + commitment
+        })
+}
+
+/// Checks the group key and commitments, returning the decoded commitments.
+fn check_commitments(
+    group_key: &[u8; 32],
+    commitments: &[[u8; 32]],
+    threshold: u32,
+) -> Result<Vec<RistrettoPoint>, Box<dyn std::error::Error>> {
+    decode_point("group_key", group_key)?;
+    if commitments.is_empty() {
+        if !allow_legacy() {
+            return Err(
+                "The file has no commitments, so it was written by an earlier version \
+                 and cannot be checked for tampering; pass --allow-legacy to load it anyway"
+                    .into(),
+            );
+        }
+        eprintln!(
+            "WARNING: loading a file without commitments, written by an earlier version; \
+             its shares cannot be checked for tampering. Regenerate the keys to restore the checks."
+        );
+        return Ok(Vec::new());
+    }
+
+    if commitments.len() != threshold as usize {
+        return Err(corrupted(
+            "threshold",
+            &format!(
+                "it is {} but the polynomial has {} commitments",
+                threshold,
+                commitments.len()
+            ),
+        ));
+    }
+    if commitments[0] != *group_key {
+        return Err(corrupted(
+            "group_key",
+            "it does not match the first commitment",
+        ));
+    }
+
+    commitments
+        .iter()
+        .enumerate()
+        .map(|(k, commitment)| decode_point(&format!("commitments[{}]", k), commitment))
+        .collect()
+}
+
+fn check_private_share(
+    field: &str,
+    key_bytes: &[u8; 32],
+    index: u32,
+    commitments: &[RistrettoPoint],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let key = Scalar::from_canonical_bytes(*key_bytes)
+        .ok_or_else(|| corrupted(field, "it does not decode to a scalar"))?;
+    check_on_polynomial(
+        field,
+        &(&key * &RISTRETTO_BASEPOINT_TABLE),
+        index,
+        commitments,
+    )
+}
+
+fn check_on_polynomial(
+    field: &str,
+    public_share: &RistrettoPoint,
+    index: u32,
+    commitments: &[RistrettoPoint],
+) -> Result<(), Box<dyn std::error::Error>> {
+    if index == 0 {
+        return Err(corrupted(field, "its participant index is 0"));
+    }
+    if !commitments.is_empty() && evaluate_commitments(commitments, index) != *public_share {
+        return Err(corrupted(
+            field,
+            &format!(
+                "the share of participant {} does not lie on the committed polynomial",
+                index
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Checks the checksum, which is required once the file has commitments.
+pub(crate) fn check_checksum(
+    checksum: Option<&str>,
+    expected: &str,
+    required: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match checksum {
+        Some(checksum) if checksum != expected => Err(corrupted(
+            "checksum",
+            "it does not match the file contents, so a field was modified",
+        )),
+        None if required => Err(corrupted(
+            "checksum",
+            "it is missing although the file has commitments, so it was removed",
+        )),
+        _ => Ok(()),
+    }
+}
+
+fn decode_point(
+    field: &str,
+    bytes: &[u8; 32],
+) -> Result<RistrettoPoint, Box<dyn std::error::Error>> {
+    CompressedRistretto(*bytes)
+        .decompress()
+        .ok_or_else(|| corrupted(field, "it does not decode to a group element"))
+}
+
+/// Returns the error reporting a field that failed its check.
+pub(crate) fn corrupted(field: &str, reason: &str) -> Box<dyn std::error::Error> {
+    format!("Corrupted field `{}`: {}", field, reason).into()
+}
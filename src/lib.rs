@@ -1,3 +1,7 @@
+use bench::Phase;
+use ciphersuite::SuiteId;
+use curve25519_dalek::ristretto::RistrettoPoint;
+use frost_dalek::keygen::{Coefficients, RoundOne};
 use frost_dalek::signature::SecretKey as SignatureSecretKey;
 use frost_dalek::signature::ThresholdSignature;
 use frost_dalek::{
@@ -5,16 +9,140 @@ use frost_dalek::{
     Parameters, Participant, SignatureAggregator,
 };
 use rand::rngs::OsRng;
+use rayon::prelude::*;
+use rfc9591::SuiteKeys;
+use secret::{Secret, SecretVec};
 use serde::{Deserialize, Serialize};
 use serde_json::from_reader;
+use sha2::{Digest, Sha256};
 use std::fs::File;
-use std::io::BufReader;
+use std::io::{BufRead, BufReader};
+use zeroize::{Zeroize, Zeroizing};
+
+pub mod audit;
+pub mod bench;
+pub mod bip340;
+pub mod ceremony;
+pub mod ciphersuite;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod dashboard;
+pub mod ed25519;
+pub mod enroll;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod files;
+pub mod http;
+pub mod inspect;
+pub mod integrity;
+pub mod net;
+pub mod policy;
+pub mod proposal;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod refresh;
+pub mod repair;
+pub mod reshare;
+pub mod rfc9591;
+pub mod roast;
+pub mod rounds;
+pub mod secret;
+pub mod vss;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
 #[derive(Serialize, Deserialize)]
 pub struct FrostKeys {
     pub group_key: [u8; 32],
     pub private_shares: Vec<([u8; 32], u32)>,
     pub threshold: u32,
+    /// Commitments to the coefficients of the group's secret polynomial.
+    #[serde(default)]
+    pub commitments: Vec<[u8; 32]>,
+    /// SHA-256 checksum over all other fields, see [`integrity`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<String>,
+}
+
+impl Drop for FrostKeys {
+    fn drop(&mut self) {
+        self.private_shares
+            .iter_mut()
+            .for_each(|(key_bytes, _)| key_bytes.zeroize());
+        secret::unlock(self.private_shares.as_slice());
+    }
+}
+
+/// The key share of a single participant, as held by that participant alone.
+#[derive(Serialize, Deserialize)]
+pub struct FrostShare {
+    pub group_key: [u8; 32],
+    pub private_share: ([u8; 32], u32),
+    pub threshold: u32,
+    pub n: u32,
+    #[serde(default)]
+    pub commitments: Vec<[u8; 32]>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<String>,
+}
+
+impl Drop for FrostShare {
+    fn drop(&mut self) {
+        self.private_share.0.zeroize();
+    }
+}
+
+/// The public half of a key set: everything needed to verify signatures and
+/// partial signatures, without any private share.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FrostPublicPackage {
+    pub group_key: [u8; 32],
+    pub public_shares: Vec<([u8; 32], u32)>,
+    pub threshold: u32,
+    #[serde(default)]
+    pub commitments: Vec<[u8; 32]>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<String>,
+}
+
+/// A single entry of a batch signature file, pairing a message with its threshold signature.
+#[derive(Serialize, Deserialize)]
+pub struct BatchSignature {
+    pub message: String,
+    pub signature: Vec<u8>,
+}
+
+/// A key file of either protocol: the frost-dalek key set, or an RFC 9591 key set with its ciphersuite.
+pub enum KeyFile {
+    Frost(FrostKeys),
+    Suite(SuiteKeys),
+}
+
+impl KeyFile {
+    /// Returns the ciphersuite of the keys.
+    pub fn suite(&self) -> Result<SuiteId, Box<dyn std::error::Error>> {
+        match self {
+            KeyFile::Frost(_) => Ok(SuiteId::FrostDalek),
+            KeyFile::Suite(keys) => keys.suite(),
+        }
+    }
+
+    /// Returns the encoded group public key.
+    pub fn group_key(&self) -> &[u8] {
+        match self {
+            KeyFile::Frost(frost_keys) => &frost_keys.group_key,
+            KeyFile::Suite(keys) => &keys.group_key,
+        }
+    }
+
+    /// Returns the participant index of every share, in file order.
+    pub fn indices(&self) -> Vec<u32> {
+        match self {
+            KeyFile::Frost(frost_keys) => {
+                frost_keys.private_shares.iter().map(|(_, i)| *i).collect()
+            }
+            KeyFile::Suite(keys) => keys.private_shares.iter().map(|(_, i)| *i).collect(),
+        }
+    }
 }
 
 /// Generates a public key and private key shares using FROST.
@@ -22,6 +150,7 @@ pub struct FrostKeys {
 /// # Parameters
 /// - `t`: Threshold value, the minimum number of participants required to reconstruct the private key.
 /// - `n`: Total number of participants (key shares).
+/// - `force`: Replace `output_key_file` if it exists.
 ///
 /// # Returns
 /// - Saves the keys to `./results/frost_keys.json` in JSON format.
@@ -31,7 +160,34 @@ pub fn generate_keys(
     t: u32,
     n: u32,
     output_key_file: &str,
+    force: bool,
 ) -> Result<(), Box<dyn std::error::Error>> {
+    files::check_overwrite(output_key_file, force)?;
+    let frost_keys = generate_key_set(t, n)?;
+
+    // Save the keys to a JSON file.
+    files::write_secret_json(output_key_file, &frost_keys, force)?;
+
+    println!("Generated {} shares with threshold {}. Keys saved.", n, t);
+    Ok(())
+}
+
+/// Runs the distributed key generation of [`generate_keys`] in memory and returns the key set.
+pub fn generate_key_set(t: u32, n: u32) -> Result<FrostKeys, Box<dyn std::error::Error>> {
+    generate_key_set_with(t, n, &mut |phase| match phase {
+        Phase::Proofs => println!("All participants verified their proofs of secret keys!"),
+        Phase::DkgRoundOne => println!("DKG Round 1 complete"),
+        Phase::DkgRoundTwo => println!("Share secret shares Round 2 complete"),
+        _ => {}
+    })
+}
+
+/// Runs the key generation of [`generate_key_set`], calling `report` as each phase completes.
+pub fn generate_key_set_with(
+    t: u32,
+    n: u32,
+    report: &mut dyn FnMut(Phase),
+) -> Result<FrostKeys, Box<dyn std::error::Error>> {
     // check if the threshold is less than the total number of participants
     if t > n {
         return Err(
@@ -42,17 +198,18 @@ pub fn generate_keys(
     // Initialize the parameters for the key generation.
     let params = Parameters { t, n };
 
+    // Every participant's work in a step is independent of the others', so each
+    // step runs on all cores.
+
     // Step 1: Create participants and their polynomial coefficients.
-    let mut participants = Vec::new();
-    let mut coefficients = Vec::new();
-    for i in 1..=n {
-        let (participant, coeff) = Participant::new(&params, i);
-        participants.push(participant);
-        coefficients.push(coeff);
-    }
+    let (participants, coefficients): (Vec<_>, Vec<_>) = (1..=n)
+        .into_par_iter()
+        .map(|i| Participant::new(&params, i))
+        .unzip();
+    report(Phase::Participants);
 
     // Step 2: Verify zero-knowledge proof of secret keys for all participants.
-    for participant in &participants {
+    participants.par_iter().try_for_each(|participant| {
         participant
             .proof_of_secret_key
             .verify(&participant.index, &participant.public_key().unwrap())
@@ -61,115 +218,427 @@ pub fn generate_keys(
                     "Proof of secret key verification failed for participant {}",
                     participant.index
                 )
-            })?;
+            })
+    })?;
+    report(Phase::Proofs);
+
+    // Step 3: Perform the first round of Distributed Key Generation (DKG). A
+    // round-one state holds the commitments of every other participant, n * t
+    // points, so keeping all n of them until round 2 would take memory cubic in
+    // the group size (about 10 GB at n = 500). Each state is dropped once its
+    // secret shares are dealt and rebuilt in round 2 instead. Rebuilding costs
+    // one more round 1, which checks n proofs and evaluates n shares, against
+    // the n * t point multiplications of checking the shares in round 2; the
+    // `round 1` and `round 2` columns of `cargo bench --bench dkg` show both.
+    let dealt = par_with_participants(
+        &participants,
+        coefficients.iter().collect(),
+        |all_participants, i, coefficients| {
+            let dkg_state = dkg_round_one(&params, all_participants, i, coefficients)?;
+            // frost-dalek only lends the shares a participant deals, so they are
+            // copied out once; dropping the state right after zeroizes the originals.
+            let their_secret_shares = dkg_state
+                .their_secret_shares()
+                .map_err(|_| {
+                    format!(
+                        "Secret shares retrieval failed for participant {}",
+                        participants[i].index
+                    )
+                })?
+                .clone();
+            drop(dkg_state);
+            Ok(their_secret_shares)
+        },
+    )?;
+    report(Phase::DkgRoundOne);
+
+    // Step 4: Share secret shares and complete Round 2 of DKG. Each share is moved
+    // to its recipient rather than copied, so the copy made in step 3 is the only
+    // one. Every participant receives the shares in the order of the other participants.
+    let mut received: Vec<Vec<_>> = (0..n).map(|_| Vec::with_capacity(n as usize)).collect();
+    for secret_shares in dealt {
+        for secret_share in secret_shares {
+            let recipient = secret_share.index as usize;
+            received
+                .get_mut(recipient.wrapping_sub(1))
+                .ok_or_else(|| format!("Secret share for unknown participant {}", recipient))?
+                .push(secret_share);
+        }
     }
-    println!("All participants verified their proofs of secret keys!");
-
-    // Step 3: Perform the first round of Distributed Key Generation (DKG).
-    let mut dkg_states = Vec::new();
-    let mut all_secret_shares = Vec::new();
-    for (i, participant) in participants.iter().enumerate() {
-        let mut other_participants = participants.clone();
-        other_participants.remove(i);
-
-        let participant_state = DistributedKeyGeneration::<_>::new(
-            &params,
-            &participant.index,
-            &coefficients[i],
-            &mut other_participants,
-        )
-        .map_err(|err| {
-            format!(
-                "DistributedKeyGeneration failed for participant: {}: {:?}",
-                &participant.index, err
-            )
-        })?;
 
-        let participant_their_secret_shares = participant_state
-            .their_secret_shares()
-            .map_err(|_| {
-                format!(
-                    "Secret shares retrieval failed for participant {}",
-                    participant.index
-                )
-            })?
-            .to_vec();
+    // Step 5: Finalize DKG and save the keys.
+    let finished = par_with_participants(
+        &participants,
+        received,
+        |all_participants, i, my_secret_shares| {
+            // Ensure the correct number of shares are received.
+            if my_secret_shares.len() != (params.n - 1) as usize {
+                return Err(format!(
+                    "Participant {} received incorrect number of shares: expected {}, got {}",
+                    participants[i].index,
+                    params.n - 1,
+                    my_secret_shares.len()
+                ));
+            }
+
+            let dkg_state = dkg_round_one(&params, all_participants, i, &coefficients[i])?
+                .to_round_two(my_secret_shares)
+                .map_err(|_| format!("Round 2 failed for participant {}", participants[i].index))?;
+            let (dkg_group_key, dkg_secret_key) = dkg_state
+                .finish(participants[i].public_key().unwrap())
+                .map_err(|_| {
+                    format!(
+                        "Failed to finish DKG for participant {}",
+                        participants[i].index
+                    )
+                })?;
+            // Box the share so that collecting the results only moves a pointer.
+            let (mut key_bytes, index) = dkg_secret_key.to_bytes();
+            let private_share = Secret::new(key_bytes);
+            key_bytes.zeroize();
+            Ok((dkg_group_key, private_share, index))
+        },
+    )?;
 
-        dkg_states.push(participant_state);
-        all_secret_shares.push(participant_their_secret_shares);
+    // Ensure all group keys are identical.
+    let group_key = finished
+        .first()
+        .ok_or("There must be at least one participant")?
+        .0;
+    if finished
+        .iter()
+        .any(|(dkg_group_key, _, _)| *dkg_group_key != group_key)
+    {
+        return Err("Participants computed different group keys".into());
     }
-    println!("DKG Round 1 complete");
+    let private_shares = finished
+        .iter()
+        .map(|(_, private_share, index)| (**private_share, *index))
+        .collect();
+    report(Phase::DkgRoundTwo);
 
-    // Step 4: Share secret shares and complete Round 2 of DKG.
-    let mut dkg_states_round_two = Vec::new();
-    for (i, dkg_state) in dkg_states.into_iter().enumerate() {
-        let my_secret_shares: Vec<_> = all_secret_shares
-            .iter()
-            .enumerate()
-            .filter(|(j, _)| *j != i)
-            .filter_map(|(j, shares)| {
-                let pos = if i < j { i } else { i - 1 };
-                shares.get(pos).cloned()
-            })
-            .collect();
+    // Commit to the group's secret polynomial: the sum of every participant's commitments.
+    let commitments = (0..t as usize)
+        .map(|k| {
+            participants
+                .iter()
+                .map(|participant| participant.commitments[k])
+                .sum::<RistrettoPoint>()
+                .compress()
+                .to_bytes()
+        })
+        .collect();
 
-        // Ensure the correct number of shares are received.
-        if my_secret_shares.len() != (params.n - 1) as usize {
-            return Err(format!(
-                "Participant {} received incorrect number of shares: expected {}, got {}",
-                participants[i].index,
-                params.n - 1,
-                my_secret_shares.len()
-            )
-            .into());
-        }
+    // Combine group key and private shares into a single structure.
+    let mut frost_keys = FrostKeys {
+        group_key: group_key.to_bytes(),
+        private_shares,
+        threshold: t,
+        commitments,
+        checksum: None,
+    };
+    frost_keys.checksum = Some(integrity::key_set_checksum(&frost_keys));
+    report(Phase::GroupCommitments);
+    Ok(frost_keys)
+}
+
+/// Runs `f` on all cores for the participant at every position, with its item.
+///
+/// frost-dalek's DKG rounds take the other participants as a mutable vector, so
+/// each thread needs its own copy of them. The positions are split into one
+/// chunk per thread, and each chunk copies the participants once, so at most
+/// one copy per thread exists however rayon schedules the work.
+fn par_with_participants<I: Send, T: Send>(
+    participants: &[Participant],
+    items: Vec<I>,
+    f: impl Fn(&mut Vec<Participant>, usize, I) -> Result<T, String> + Sync,
+) -> Result<Vec<T>, String> {
+    let chunk_size = items.len().div_ceil(rayon::current_num_threads()).max(1);
+    let chunks = items
+        .into_par_iter()
+        .enumerate()
+        .chunks(chunk_size)
+        .map(|chunk| {
+            let mut all_participants = participants.to_vec();
+            chunk
+                .into_iter()
+                .map(|(position, item)| f(&mut all_participants, position, item))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(chunks.into_iter().flatten().collect())
+}
 
-        let round_two_state = dkg_state
-            .to_round_two(my_secret_shares)
-            .map_err(|_| format!("Round 2 failed for participant {}", participants[i].index))?;
+/// Runs the first DKG round of the participant at `position` in `participants`.
+///
+/// frost-dalek takes the other participants as a mutable vector; the participant
+/// is taken out of `participants` for the call and put back after, so the others
+/// keep their order and nothing is copied.
+fn dkg_round_one(
+    params: &Parameters,
+    participants: &mut Vec<Participant>,
+    position: usize,
+    coefficients: &Coefficients,
+) -> Result<DistributedKeyGeneration<RoundOne>, String> {
+    let participant = participants.remove(position);
+    let dkg_state = DistributedKeyGeneration::<RoundOne>::new(
+        params,
+        &participant.index,
+        coefficients,
+        participants,
+    )
+    .map_err(|err| {
+        format!(
+            "DistributedKeyGeneration failed for participant: {}: {:?}",
+            participant.index, err
+        )
+    });
+    participants.insert(position, participant);
+    dkg_state
+}
 
-        dkg_states_round_two.push(round_two_state);
+/// Generates keys like [`generate_keys`], for any supported ciphersuite.
+///
+/// frost-dalek keys come from the distributed key generation of [`generate_keys`].
+/// Keys for an RFC 9591 suite are split by a trusted dealer, see [`rfc9591`]: this
+/// process holds the whole group secret while dealing, and the keys only work with
+/// the in-process signing of [`sign_message`], not with the distributed commands.
+pub fn generate_keys_with_suite(
+    t: u32,
+    n: u32,
+    output_key_file: &str,
+    suite: SuiteId,
+    force: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    files::check_overwrite(output_key_file, force)?;
+    if suite == SuiteId::FrostDalek {
+        return generate_keys(t, n, output_key_file, force);
     }
-    println!("Share secret shares Round 2 complete");
 
-    // Step 5: Finalize DKG and save the keys.
-    let mut group_keys = Vec::new();
-    let mut private_shares = Vec::new();
-    for (i, dkg_state) in dkg_states_round_two.iter().enumerate() {
-        let (dkg_group_key, dkg_secret_key) = dkg_state
-            .clone()
-            .finish(participants[i].public_key().unwrap())
-            .map_err(|_| {
-                format!(
-                    "Failed to finish DKG for participant {}",
-                    participants[i].index
-                )
-            })?;
+    eprintln!(
+        "WARNING: {} keys are split by a trusted dealer, not by a distributed key \
+         generation: this process holds the whole group secret while dealing. They \
+         can only be used with `sign` and `verify`.",
+        suite
+    );
+    let keys = rfc9591::generate(&mut OsRng, suite, t, n)?;
+    files::write_secret_json(output_key_file, &keys, force)?;
+
+    println!(
+        "Generated {} {} shares with threshold {}. Keys saved.",
+        n, suite, t
+    );
+    Ok(())
+}
+
+/// Exports the share of a single participant from a key file.
+///
+/// # Arguments
+/// - `key_file`: Path to the file containing the generated keys.
+/// - `index`: The participant index (1 to n) whose share is exported.
+/// - `share_file`: Path to save the participant's share.
+/// - `force`: Replace `share_file` if it exists.
+///
+/// # Errors
+/// Returns an error if the key file cannot be read or holds no share for `index`.
+pub fn export_share(
+    key_file: &str,
+    index: u32,
+    share_file: &str,
+    force: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    files::check_overwrite(share_file, force)?;
+    let frost_keys = load_keys(key_file)?;
+    let frost_share = share_of(&frost_keys, index)?;
+
+    files::write_secret_json(share_file, &frost_share, force)?;
+
+    println!("Share of participant {} saved to: {}", index, share_file);
+    Ok(())
+}
 
-        group_keys.push(dkg_group_key);
-        private_shares.push(dkg_secret_key.to_bytes());
+/// Returns the share of participant `index` from a key set.
+pub fn share_of(
+    frost_keys: &FrostKeys,
+    index: u32,
+) -> Result<FrostShare, Box<dyn std::error::Error>> {
+    let private_share = frost_keys
+        .private_shares
+        .iter()
+        .find(|(_, share_index)| *share_index == index)
+        .copied()
+        .ok_or_else(|| format!("No share for participant {} in the key file", index))?;
 
-        // Ensure all group keys are identical.
-        if i > 0 {
-            assert_eq!(dkg_group_key, group_keys[i - 1]);
+    let mut frost_share = FrostShare {
+        group_key: frost_keys.group_key,
+        private_share,
+        threshold: frost_keys.threshold,
+        n: frost_keys.private_shares.len() as u32,
+        commitments: frost_keys.commitments.clone(),
+        checksum: None,
+    };
+    frost_share.checksum = Some(integrity::share_checksum(&frost_share));
+    Ok(frost_share)
+}
+
+/// Combines the shares of every participant back into a key set.
+///
+/// # Errors
+/// Returns an error if the shares belong to different key sets or a participant appears twice.
+pub fn keys_from_shares(shares: &[FrostShare]) -> Result<FrostKeys, Box<dyn std::error::Error>> {
+    let first = shares.first().ok_or("No shares to combine")?;
+    let mut private_shares = Vec::new();
+    for share in shares {
+        if share.group_key != first.group_key
+            || share.threshold != first.threshold
+            || share.commitments != first.commitments
+        {
+            return Err(format!(
+                "Share of participant {} belongs to a different key set",
+                share.private_share.1
+            )
+            .into());
         }
+        if private_shares
+            .iter()
+            .any(|(_, index)| *index == share.private_share.1)
+        {
+            return Err(format!("Participant {} appears twice", share.private_share.1).into());
+        }
+        private_shares.push(share.private_share);
     }
+    private_shares.sort_by_key(|(_, index)| *index);
 
-    // Combine group key and private shares into a single structure.
-    let frost_keys = FrostKeys {
-        group_key: group_keys[0].to_bytes(),
+    let mut frost_keys = FrostKeys {
+        group_key: first.group_key,
         private_shares,
-        threshold: t,
+        threshold: first.threshold,
+        commitments: first.commitments.clone(),
+        checksum: None,
     };
+    frost_keys.checksum = Some(integrity::key_set_checksum(&frost_keys));
+    Ok(frost_keys)
+}
 
-    // Save the keys to a JSON file.
-    let file = File::create(output_key_file)?;
-    serde_json::to_writer_pretty(file, &frost_keys)?;
+/// Exports the public package of a key file: the group key, the threshold and
+/// the public key share of every participant.
+///
+/// # Errors
+/// Returns an error if the key file cannot be read or holds an invalid share.
+pub fn export_public_package(
+    key_file: &str,
+    public_file: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let frost_keys = load_keys(key_file)?;
+    let public_package = public_package_of(&frost_keys)?;
 
-    println!("Generated {} shares with threshold {}. Keys saved.", n, t);
+    files::write_json(public_file, &public_package)?;
+
+    println!("Public package saved to: {}", public_file);
     Ok(())
 }
 
+/// Returns the public package of a key set.
+pub fn public_package_of(
+    frost_keys: &FrostKeys,
+) -> Result<FrostPublicPackage, Box<dyn std::error::Error>> {
+    let mut public_shares = Vec::new();
+    for &(key_bytes, index) in &frost_keys.private_shares {
+        let secret_key = SignatureSecretKey::from_bytes(index, key_bytes)
+            .map_err(|_| format!("Invalid private key bytes for participant {}", index))?;
+        public_shares.push((rounds::public_share(&secret_key), index));
+    }
+
+    let mut public_package = FrostPublicPackage {
+        group_key: frost_keys.group_key,
+        public_shares,
+        threshold: frost_keys.threshold,
+        commitments: frost_keys.commitments.clone(),
+        checksum: None,
+    };
+    public_package.checksum = Some(integrity::public_package_checksum(&public_package));
+    Ok(public_package)
+}
+
+/// Builds the public package of participants `1..=n` from the commitments to the group's polynomial.
+pub(crate) fn public_package_from_commitments(
+    group_key: [u8; 32],
+    commitments: &[RistrettoPoint],
+    n: u32,
+) -> FrostPublicPackage {
+    let public_shares = (1..=n)
+        .map(|index| {
+            let public_share = integrity::evaluate_commitments(commitments, index);
+            (public_share.compress().to_bytes(), index)
+        })
+        .collect();
+
+    let mut public_package = FrostPublicPackage {
+        group_key,
+        public_shares,
+        threshold: commitments.len() as u32,
+        commitments: vss::encode_points(commitments),
+        checksum: None,
+    };
+    public_package.checksum = Some(integrity::public_package_checksum(&public_package));
+    public_package
+}
+
+/// Returns a short, human-comparable fingerprint of a group public key.
+///
+/// The fingerprint is the first 8 bytes of the SHA-256 hash of the key, in hex.
+pub fn fingerprint(group_key: &[u8]) -> String {
+    hex::encode(&Sha256::digest(group_key)[..8])
+}
+
+/// Loads a participant's share from a share file and checks its integrity.
+///
+/// A share file encrypted by a [ceremony](ceremony) is decrypted with the
+/// passphrase from `FROST_PASSPHRASE`, or one asked for on the terminal.
+pub fn load_share(share_file: &str) -> Result<FrostShare, Box<dyn std::error::Error>> {
+    load_share_from(share_file, || ceremony::passphrase_for(share_file))
+}
+
+/// Loads a share file like [`load_share`], decrypting it with `passphrase` if it is encrypted.
+pub fn load_share_with_passphrase(
+    share_file: &str,
+    passphrase: &str,
+) -> Result<FrostShare, Box<dyn std::error::Error>> {
+    load_share_from(share_file, || Ok(passphrase.to_string()))
+}
+
+/// Loads a share file, asking `passphrase` for the passphrase only if the file is encrypted.
+fn load_share_from(
+    share_file: &str,
+    passphrase: impl FnOnce() -> Result<String, Box<dyn std::error::Error>>,
+) -> Result<FrostShare, Box<dyn std::error::Error>> {
+    let contents = Zeroizing::new(std::fs::read_to_string(share_file)?);
+    let frost_share: FrostShare =
+        if let Ok(encrypted) = serde_json::from_str::<ceremony::EncryptedShare>(&contents) {
+            let mut passphrase = passphrase()?;
+            let share = ceremony::decrypt_share(&encrypted, &passphrase);
+            passphrase.zeroize();
+            share.map_err(|err| format!("Cannot decrypt share file {}: {}", share_file, err))?
+        } else {
+            serde_json::from_str(&contents)?
+        };
+    integrity::check_share(&frost_share)
+        .map_err(|err| format!("Share file {} is corrupted: {}", share_file, err))?;
+    Ok(frost_share)
+}
+
+/// Loads a public package and checks its integrity.
+pub fn load_public_package(
+    public_file: &str,
+) -> Result<FrostPublicPackage, Box<dyn std::error::Error>> {
+    let file = File::open(public_file)?;
+    let reader = BufReader::new(file);
+    let public_package: FrostPublicPackage = from_reader(reader)?;
+    integrity::check_public_package(&public_package)
+        .map_err(|err| format!("Public package {} is corrupted: {}", public_file, err))?;
+    Ok(public_package)
+}
+
 /// Signs a message using threshold signing.
 ///
 /// # Arguments
@@ -187,57 +656,238 @@ pub fn sign_message(
     n: u32,
     key_file: &str,
     signature_file: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    sign_message_with_policy(
+        message,
+        signers,
+        n,
+        key_file,
+        signature_file,
+        policy::DEFAULT_CONTEXT,
+        None,
+    )
+}
+
+/// Signs a message like [`sign_message`], after every signer has checked the request against `policy`.
+///
+/// # Arguments
+/// - `context`: The context the message is signed under, bound into the signature and checked by the policy.
+/// - `policy`: The signing policy, or `None` to sign without one.
+///
+/// # Errors
+/// Returns a [`policy::PolicyViolation`] if the policy does not allow the request,
+/// or another error if loading keys or signing fails.
+pub fn sign_message_with_policy(
+    message: &str,
+    signers: Vec<u32>,
+    n: u32,
+    key_file: &str,
+    signature_file: &str,
+    context: &str,
+    policy: Option<&policy::Policy>,
 ) -> Result<(), Box<dyn std::error::Error>> {
     // Step 1: Load keys from file
-    let file = File::open(key_file)?;
-    let reader = BufReader::new(file);
-    let frost_keys: FrostKeys = from_reader(reader)?;
+    let key_file = load_key_file(key_file)?;
+
+    // Step 2: Every signer checks the request against the policy
+    let indices = key_file_signer_indices(&key_file, &signers)?;
+    if let Some(policy) = policy {
+        policy.enforce(key_file.group_key(), &indices, message.as_bytes(), context)?;
+    }
+
+    // Step 3: Produce the threshold signature
+    let threshold_signature = match &key_file {
+        KeyFile::Frost(frost_keys) => threshold_sign_with(
+            frost_keys,
+            message.as_bytes(),
+            context,
+            &signers,
+            n,
+            &mut |_| {},
+        )?
+        .to_vec(),
+        KeyFile::Suite(keys) => {
+            if context != policy::DEFAULT_CONTEXT {
+                return Err(
+                    "RFC 9591 signatures cannot bind a context; sign under the default context"
+                        .into(),
+                );
+            }
+            if keys.private_shares.len() != n as usize {
+                return Err("Number of participants does not match the key file".into());
+            }
+            rfc9591::sign(&mut OsRng, keys, message.as_bytes(), &indices)?
+        }
+    };
+
+    // Step 4: Save the signature as a JSON file
+    files::write_json(signature_file, &threshold_signature)?;
 
-    // Step 2: Check if the number of participants matches the key file
+    println!("Threshold signature saved to: {}", signature_file);
+    Ok(())
+}
+
+/// Returns the participant indices of the signers, given as positions in the key file.
+pub fn signer_indices(
+    frost_keys: &FrostKeys,
+    signers: &[u32],
+) -> Result<Vec<u32>, Box<dyn std::error::Error>> {
+    signers
+        .iter()
+        .map(|&signer| {
+            frost_keys
+                .private_shares
+                .get(signer as usize)
+                .map(|(_, index)| *index)
+                .ok_or_else(|| format!("Invalid signer index: {}", signer).into())
+        })
+        .collect()
+}
+
+/// Returns the participant indices of the signers of any key file, given as positions in the file.
+fn key_file_signer_indices(
+    key_file: &KeyFile,
+    signers: &[u32],
+) -> Result<Vec<u32>, Box<dyn std::error::Error>> {
+    let indices = key_file.indices();
+    signers
+        .iter()
+        .map(|&signer| {
+            indices
+                .get(signer as usize)
+                .copied()
+                .ok_or_else(|| format!("Invalid signer index: {}", signer).into())
+        })
+        .collect()
+}
+
+/// Loads the group public key and private key shares from a frost-dalek key file and checks their integrity.
+///
+/// # Errors
+/// Returns an error for key files of an RFC 9591 ciphersuite, which only
+/// [`load_key_file`] reads.
+pub fn load_keys(key_file: &str) -> Result<FrostKeys, Box<dyn std::error::Error>> {
+    match load_key_file(key_file)? {
+        KeyFile::Frost(frost_keys) => Ok(frost_keys),
+        KeyFile::Suite(keys) => Err(format!(
+            "Key file {} uses the {} ciphersuite; this command only supports frost-dalek keys",
+            key_file,
+            keys.suite()?
+        )
+        .into()),
+    }
+}
+
+/// The field that tells the key file formats apart. Every other field is skipped
+/// without being parsed, so no copy of the shares is made before the typed one.
+#[derive(Deserialize)]
+struct KeyFileFormat {
+    ciphersuite: Option<String>,
+}
+
+/// Loads a key file of any ciphersuite and checks its integrity.
+///
+/// Key files that name no ciphersuite are frost-dalek key files. The file is
+/// deserialized straight into its key set, and its text is zeroized once parsed.
+pub fn load_key_file(key_file: &str) -> Result<KeyFile, Box<dyn std::error::Error>> {
+    let contents = Zeroizing::new(std::fs::read_to_string(key_file)?);
+    let format: KeyFileFormat = serde_json::from_str(&contents)?;
+    if format.ciphersuite.is_some() {
+        let keys: SuiteKeys = serde_json::from_str(&contents)?;
+        keys.private_shares
+            .iter()
+            .for_each(|(key_bytes, _)| secret::lock(key_bytes.as_slice()));
+        rfc9591::check_keys(&keys)
+            .map_err(|err| format!("Key file {} is corrupted: {}", key_file, err))?;
+        return Ok(KeyFile::Suite(keys));
+    }
+
+    let frost_keys: FrostKeys = serde_json::from_str(&contents)?;
+    secret::lock(frost_keys.private_shares.as_slice());
+    integrity::check_key_set(&frost_keys)
+        .map_err(|err| format!("Key file {} is corrupted: {}", key_file, err))?;
+    Ok(KeyFile::Frost(frost_keys))
+}
+
+/// Signs a message in memory with the given signers and returns the 64-byte threshold signature.
+///
+/// This is the signing core of [`sign_message`], for callers that hold the keys
+/// themselves rather than in a key file.
+///
+/// # Errors
+/// Returns an error if the signers do not match the keys, or if signing fails.
+pub fn threshold_sign(
+    frost_keys: &FrostKeys,
+    message: &[u8],
+    signers: &[u32],
+    n: u32,
+) -> Result<[u8; 64], Box<dyn std::error::Error>> {
+    threshold_sign_with(
+        frost_keys,
+        message,
+        policy::DEFAULT_CONTEXT,
+        signers,
+        n,
+        &mut |_| {},
+    )
+}
+
+/// Signs a message like [`threshold_sign`] under the application `context`,
+/// calling `report` as each phase completes.
+pub fn threshold_sign_with(
+    frost_keys: &FrostKeys,
+    message: &[u8],
+    context: &str,
+    signers: &[u32],
+    n: u32,
+    report: &mut dyn FnMut(Phase),
+) -> Result<[u8; 64], Box<dyn std::error::Error>> {
+    // Step 1: Check if the number of participants matches the key file
     if frost_keys.private_shares.len() != n as usize {
         return Err("Number of participants does not match the key file".into());
     }
 
-    // Step 3: Check if the number of signers is at least the threshold
+    // Step 2: Check if the number of signers is at least the threshold
     if signers.len() < frost_keys.threshold as usize {
         return Err("Number of signers is less than the threshold".into());
     }
 
-    // Step 4: Ensure all specified signers are valid
-    for &signer in &signers {
+    // Step 3: Ensure all specified signers are valid
+    for &signer in signers {
         if signer as usize >= frost_keys.private_shares.len() {
             return Err(format!("Invalid signer index: {}", signer).into());
         }
     }
 
-    // Step 5: Load the group public key
+    // Step 4: Load the group public key
     let group_key =
         GroupKey::from_bytes(frost_keys.group_key).map_err(|_| "Invalid group public key")?;
 
-    // Step 6: Reconstruct secret keys for the specified signers
-    let mut secret_keys = Vec::new();
-    for &signer in &signers {
-        let (key_bytes, index) = frost_keys.private_shares[signer as usize];
-        let secret_key = SignatureSecretKey::from_bytes(index, key_bytes)
-            .map_err(|_| "Invalid private key bytes")?;
-        secret_keys.push(secret_key);
+    // Step 5: Reconstruct secret keys for the specified signers
+    let mut secret_keys = Secret::new(SecretVec::with_capacity(signers.len()));
+    for &signer in signers {
+        let (mut key_bytes, index) = frost_keys.private_shares[signer as usize];
+        let secret_key = SignatureSecretKey::from_bytes(index, key_bytes);
+        key_bytes.zeroize();
+        secret_keys.push(secret_key.map_err(|_| "Invalid private key bytes")?);
     }
+    report(Phase::SignerKeys);
 
-    // Step 7: Generate commitment shares for the chosen signers
-    let mut public_comshares = Vec::new();
-    let mut secret_comshares = Vec::new();
-    for signer in &secret_keys {
+    // Step 6: Generate commitment shares for the chosen signers
+    let mut public_comshares = Vec::with_capacity(signers.len());
+    let mut secret_comshares = Secret::new(SecretVec::with_capacity(signers.len()));
+    for signer in secret_keys.iter() {
         let (pub_com, sec_com) = generate_commitment_share_lists(&mut OsRng, signer.get_index(), 1);
         public_comshares.push((signer.get_index(), pub_com));
         secret_comshares.push((signer.get_index(), sec_com));
     }
+    report(Phase::Commitments);
 
-    // Step 8: Hash the message to create a signing context
-    let context = b"THRESHOLD SIGNING CONTEXT";
-    let message_bytes = message.as_bytes();
-    let message_hash = compute_message_hash(&context[..], &message_bytes[..]);
+    // Step 7: Hash the message under the signing context
+    let context = rounds::signing_context(context);
+    let message_hash = compute_message_hash(&context, message);
 
-    // Step 9: Initialize a signature aggregator
+    // Step 8: Initialize a signature aggregator
     let mut aggregator = SignatureAggregator::new(
         Parameters {
             t: frost_keys.threshold,
@@ -245,25 +895,26 @@ pub fn sign_message(
         },
         group_key,
         &context[..],
-        &message_bytes[..],
+        message,
     );
 
-    // Step 10: Include signers and their commitment shares in the aggregator
+    // Step 9: Include signers and their commitment shares in the aggregator
     for (signer, (index, pub_com)) in secret_keys.iter().zip(public_comshares.iter()) {
         let public_key = signer.to_public();
         aggregator.include_signer(*index, pub_com.commitments[0], public_key);
     }
 
-    // Step 11: Get the list of participating signers
+    // Step 10: Get the list of participating signers
     let signers = aggregator.get_signers().clone();
 
-    // Step 12: Create and include partial signatures
+    // Step 11: Create and include partial signatures
     for (secret_key, (_, sec_com)) in secret_keys.iter().zip(secret_comshares.iter_mut()) {
         let partial_sig = secret_key.sign(&message_hash, &group_key, sec_com, 0, &signers)?;
         aggregator.include_partial_signature(partial_sig);
     }
+    report(Phase::PartialSignatures);
 
-    // Step 13: Finalize and aggregate the threshold signature
+    // Step 12: Finalize and aggregate the threshold signature
     let aggregator = aggregator.finalize().map_err(|err| {
         let error_message = format!("Failed to finalize aggregator: {:?}", err);
         Box::<dyn std::error::Error>::from(error_message)
@@ -273,15 +924,154 @@ pub fn sign_message(
         let error_message = format!("Failed to aggregate signature: {:?}", err);
         Box::<dyn std::error::Error>::from(error_message)
     })?;
+    report(Phase::Aggregation);
 
-    // Step 14: Save the signature as a JSON file
-    let file = File::create(signature_file)?;
-    serde_json::to_writer_pretty(file, &threshold_signature.to_bytes().to_vec())?;
+    Ok(threshold_signature.to_bytes())
+}
 
-    println!("Threshold signature saved to: {}", signature_file);
+/// Signs a batch of messages using threshold signing in a single session.
+///
+/// The key file is read once and every signer precomputes a single commitment
+/// share list with one commitment per message, instead of redoing the whole
+/// setup for each message as repeated calls to [`sign_message`] would.
+///
+/// # Arguments
+/// - `messages`: The messages to be signed, in order.
+/// - `signers`: The signers taking part in the session.
+/// - `n`: The total number of participants.
+/// - `key_file`: Path to the file containing the generated keys.
+/// - `signature_file`: Path to save the generated signatures.
+///
+/// # Errors
+/// Returns an error if loading keys, generating commitment shares, or signing any message fails.
+pub fn sign_batch(
+    messages: &[String],
+    signers: Vec<u32>,
+    n: u32,
+    key_file: &str,
+    signature_file: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Step 1: Load keys from file
+    let frost_keys = load_keys(key_file)?;
+
+    // Step 2: Check the batch and the signers against the key file
+    if messages.is_empty() {
+        return Err("No messages to sign".into());
+    }
+    if frost_keys.private_shares.len() != n as usize {
+        return Err("Number of participants does not match the key file".into());
+    }
+    if signers.len() < frost_keys.threshold as usize {
+        return Err("Number of signers is less than the threshold".into());
+    }
+    for &signer in &signers {
+        if signer as usize >= frost_keys.private_shares.len() {
+            return Err(format!("Invalid signer index: {}", signer).into());
+        }
+    }
+
+    // Step 3: Load the group public key
+    let group_key =
+        GroupKey::from_bytes(frost_keys.group_key).map_err(|_| "Invalid group public key")?;
+
+    // Step 4: Reconstruct secret keys for the specified signers
+    let mut secret_keys = Secret::new(SecretVec::with_capacity(signers.len()));
+    for &signer in &signers {
+        let (mut key_bytes, index) = frost_keys.private_shares[signer as usize];
+        let secret_key = SignatureSecretKey::from_bytes(index, key_bytes);
+        key_bytes.zeroize();
+        secret_keys.push(secret_key.map_err(|_| "Invalid private key bytes")?);
+    }
+
+    // Step 5: Precompute one commitment per message for every signer
+    let mut public_comshares = Vec::with_capacity(signers.len());
+    let mut secret_comshares = Secret::new(SecretVec::with_capacity(signers.len()));
+    for signer in secret_keys.iter() {
+        let (pub_com, sec_com) =
+            generate_commitment_share_lists(&mut OsRng, signer.get_index(), messages.len());
+        public_comshares.push((signer.get_index(), pub_com));
+        secret_comshares.push((signer.get_index(), sec_com));
+    }
+
+    // Step 6: Sign every message with its own commitment, under the default context
+    let context = rounds::signing_context(policy::DEFAULT_CONTEXT);
+    let mut batch_signatures = Vec::with_capacity(messages.len());
+    for (k, message) in messages.iter().enumerate() {
+        let message_bytes = message.as_bytes();
+        let message_hash = compute_message_hash(&context[..], message_bytes);
+
+        let mut aggregator = SignatureAggregator::new(
+            Parameters {
+                t: frost_keys.threshold,
+                n,
+            },
+            group_key,
+            &context[..],
+            message_bytes,
+        );
+
+        for (signer, (index, pub_com)) in secret_keys.iter().zip(public_comshares.iter()) {
+            aggregator.include_signer(*index, pub_com.commitments[k], signer.to_public());
+        }
+
+        let signers = aggregator.get_signers().clone();
+
+        // Signing drops the used commitment from the secret list, so the
+        // commitment for message `k` is always the first one left.
+        for (secret_key, (_, sec_com)) in secret_keys.iter().zip(secret_comshares.iter_mut()) {
+            let partial_sig = secret_key.sign(&message_hash, &group_key, sec_com, 0, &signers)?;
+            aggregator.include_partial_signature(partial_sig);
+        }
+
+        let aggregator = aggregator
+            .finalize()
+            .map_err(|err| format!("Failed to finalize aggregator for message {}: {:?}", k, err))?;
+        let threshold_signature = aggregator
+            .aggregate()
+            .map_err(|err| format!("Failed to aggregate signature for message {}: {:?}", k, err))?;
+
+        batch_signatures.push(BatchSignature {
+            message: message.clone(),
+            signature: threshold_signature.to_bytes().to_vec(),
+        });
+    }
+
+    // Step 7: Save the signatures as a JSON file
+    files::write_json(signature_file, &batch_signatures)?;
+
+    println!(
+        "{} threshold signatures saved to: {}",
+        batch_signatures.len(),
+        signature_file
+    );
     Ok(())
 }
 
+/// Reads the messages of a batch, one per line.
+///
+/// With `jsonl` set, every non-empty line must be a JSON string; otherwise each
+/// non-empty line is taken verbatim as a message.
+pub fn read_messages<R: BufRead>(
+    reader: R,
+    jsonl: bool,
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let mut messages = Vec::new();
+    for (number, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if jsonl {
+            let message: String = serde_json::from_str(&line)
+                .map_err(|err| format!("Invalid JSON message on line {}: {}", number + 1, err))?;
+            messages.push(message);
+        } else {
+            messages.push(line);
+        }
+    }
+    Ok(messages)
+}
+
 /// Validates a threshold signature for a given message.
 ///
 /// This function ensures that a provided signature matches the expected
@@ -301,6 +1091,16 @@ pub fn validate_signature(
     message: &str,
     key_file: &str,
     signature_file: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    validate_signature_with_context(message, policy::DEFAULT_CONTEXT, key_file, signature_file)
+}
+
+/// Validates a signature like [`validate_signature`], for a message signed under the application `context`.
+pub fn validate_signature_with_context(
+    message: &str,
+    context: &str,
+    key_file: &str,
+    signature_file: &str,
 ) -> Result<(), Box<dyn std::error::Error>> {
     // Step 1: Load the signature from file
     let signature_file = File::open(signature_file)?;
@@ -308,38 +1108,76 @@ pub fn validate_signature(
 
     // Parse the signature into a vector of bytes
     let signature_vec: Vec<u8> = serde_json::from_reader(signature_reader)?;
-    if signature_vec.len() != 64 {
-        return Err("Invalid length for threshold signature".into());
+
+    // Step 2: Load the public group key from the key file
+    let key_file = load_key_file(key_file)?;
+
+    // Step 3: Verify the threshold signature with the key file's ciphersuite
+    match &key_file {
+        KeyFile::Frost(frost_keys) => {
+            // Convert signature bytes to a fixed-length array
+            let signature_bytes: [u8; 64] = signature_vec
+                .try_into()
+                .map_err(|_| "Invalid length for threshold signature")?;
+            verify_signature_with_context(
+                &frost_keys.group_key,
+                message.as_bytes(),
+                context,
+                &signature_bytes,
+            )?;
+        }
+        KeyFile::Suite(keys) => {
+            if context != policy::DEFAULT_CONTEXT {
+                return Err(
+                    "RFC 9591 signatures cannot bind a context; verify under the default context"
+                        .into(),
+                );
+            }
+            rfc9591::verify_suite(
+                keys.suite()?,
+                &keys.group_key,
+                message.as_bytes(),
+                &signature_vec,
+            )?;
+        }
     }
 
-    // Convert signature bytes to a fixed-length array
-    let signature_bytes: [u8; 64] = signature_vec
-        .try_into()
-        .map_err(|_| "Failed to convert to [u8; 64]")?;
+    println!("Signature is valid!");
+    Ok(())
+}
 
+/// Verifies a 64-byte threshold signature over `message` against a group public key.
+///
+/// This is the verification core of [`validate_signature`], for callers that
+/// already hold the group key and signature in memory.
+pub fn verify_signature(
+    group_key: &[u8; 32],
+    message: &[u8],
+    signature: &[u8; 64],
+) -> Result<(), Box<dyn std::error::Error>> {
+    verify_signature_with_context(group_key, message, policy::DEFAULT_CONTEXT, signature)
+}
+
+/// Verifies a threshold signature like [`verify_signature`], for a message signed under the application `context`.
+pub fn verify_signature_with_context(
+    group_key: &[u8; 32],
+    message: &[u8],
+    context: &str,
+    signature: &[u8; 64],
+) -> Result<(), Box<dyn std::error::Error>> {
     // Deserialize the threshold signature
-    let threshold_signature = ThresholdSignature::from_bytes(signature_bytes)
+    let threshold_signature = ThresholdSignature::from_bytes(*signature)
         .map_err(|_| "Failed to deserialize ThresholdSignature")?;
 
-    // Step 2: Load the public group key from the key file
-    let key_file = File::open(key_file)?;
-    let key_reader = BufReader::new(key_file);
-    let frost_keys: FrostKeys = serde_json::from_reader(key_reader)?;
-
     // Reconstruct the group public key
-    let group_key =
-        GroupKey::from_bytes(frost_keys.group_key).map_err(|_| "Invalid group public key")?;
+    let group_key = GroupKey::from_bytes(*group_key).map_err(|_| "Invalid group public key")?;
 
-    // Step 3: Compute the message hash
-    let context = b"THRESHOLD SIGNING CONTEXT";
-    let message_bytes = message.as_bytes();
-    let message_hash = compute_message_hash(&context[..], &message_bytes[..]);
+    // Compute the message hash under the signing context
+    let message_hash = compute_message_hash(&rounds::signing_context(context), message);
 
-    // Step 4: Verify the threshold signature
+    // Verify the threshold signature
     threshold_signature
         .verify(&group_key, &message_hash)
         .map_err(|_| "Signature verification failed")?;
-
-    println!("Signature is valid!");
     Ok(())
 }
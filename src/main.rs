@@ -2,20 +2,97 @@
 //!
 //! This tool supports:
 //! - Generating a public key and private key shares.
+//! - Running a guided key ceremony that hands out passphrase-protected share files.
 //! - Signing a message using a threshold of private key shares.
+//! - Signing a batch of messages in a single signing session.
 //! - Verifying a signature using the public key.
+//! - Signing across machines with participant daemons and a coordinator.
+//! - Following a networked signing run in a terminal dashboard.
+//! - Robust networked signing that falls back to other signers when some fail.
+//! - Serving signing and verification over an HTTP API.
+//! - Inspecting key, share, public package and signature files.
+//! - Refreshing shares without changing the group key.
+//! - Resharing the group key to a new threshold and participant set.
+//! - Repairing a lost share with the help of other holders.
+//! - Enrolling a new participant without changing the group key.
+//! - Proposing a message for signing and approving it before it is signed.
+//! - Restricting what may be signed with a signing policy.
+//! - Recording key generations, signing runs and share management in a hash-chained audit log.
+//! - Choosing an RFC 9591 ciphersuite for keys and signatures.
+//! - Exporting Ed25519 group keys for standard Ed25519 verifiers.
+//! - BIP-340 Schnorr signatures and Taproot tweaks for secp256k1 keys.
+//! - Locking secret key material into RAM with `--mlock`.
+//! - Writing output files atomically, with key and share files readable by their owner only.
+//! - Timing each phase of key generation, signing and verification.
 
-use clap::{Parser, Subcommand};
-use frost_cli::{generate_keys, sign_message, validate_signature};
+use clap::{Args, Parser, Subcommand};
+use frost_cli::audit::{append, message_hash, outcome, verify_log, AuditRecord};
+use frost_cli::bench::run_bench;
+use frost_cli::bip340::{export_x_only_key, taproot_tweak_keys};
+use frost_cli::ceremony::{run_ceremony, Prompter};
+use frost_cli::ciphersuite::SuiteId;
+use frost_cli::dashboard::coordinate_with_dashboard;
+use frost_cli::ed25519::{export_public_key, KeyFormat};
+use frost_cli::enroll::{enroll_accept, enroll_deal, enroll_finish, enroll_keys};
+use frost_cli::http::serve;
+use frost_cli::inspect::inspect_file_with_suite;
+use frost_cli::net::{coordinate, serve_participant};
+use frost_cli::policy::{load_policy, DEFAULT_CONTEXT};
+use frost_cli::proposal::{approve_proposal, load_proposal, propose, sign_proposal};
+use frost_cli::refresh::{refresh_deal, refresh_finish, refresh_keys};
+use frost_cli::repair::{repair_combine, repair_deal, repair_finish};
+use frost_cli::reshare::{reshare_deal, reshare_finish, reshare_keys};
+use frost_cli::roast::coordinate_robust;
+use frost_cli::{
+    export_public_package, export_share, fingerprint, generate_keys_with_suite, load_key_file,
+    read_messages, sign_batch, sign_message_with_policy, validate_signature_with_context,
+};
+use serde::de::IgnoredAny;
+use serde::Deserialize;
+use std::fs::{self, File};
+use std::io::{self, BufReader, IsTerminal, Write};
+use std::time::Duration;
+use zeroize::Zeroizing;
 
 /// Defines the structure for the CLI interface.
 #[derive(Parser)]
 #[command(name = "frost-cli")]
 #[command(about = "CLI for FROST threshold signatures", long_about = None)]
 struct Cli {
-    /// Subcommand to execute (generate, sign, or verify).
+    /// Subcommand to execute (generate, sign, sign-batch, verify, ...).
     #[command(subcommand)]
     command: Commands,
+    /// Path to the audit log that key generations, signing runs and share management are appended to.
+    #[arg(long, global = true, default_value = "./results/audit.log")]
+    audit_log: String,
+    /// Lock private shares and nonces into RAM so they are never written to swap.
+    #[arg(long, global = true)]
+    mlock: bool,
+    /// Overwrite existing key and share files.
+    #[arg(long, global = true)]
+    force: bool,
+    /// Load key, share and public package files written without commitments,
+    /// which cannot be checked for tampering.
+    #[arg(long, global = true)]
+    allow_legacy: bool,
+}
+
+/// The `--ciphersuite` flag of commands that only run frost-dalek keys.
+///
+/// RFC 9591 key sets come from a trusted dealer, and only `sign` and `verify`
+/// take them; these commands reject any other ciphersuite while parsing.
+#[derive(Args)]
+struct FrostDalekOnly {
+    /// Ciphersuite of the keys; only frost-dalek is supported by this command.
+    #[arg(long, hide = true, value_parser = parse_frost_dalek_suite)]
+    ciphersuite: Option<SuiteId>,
+}
+
+/// Subcommands of `audit`.
+#[derive(Subcommand)]
+enum AuditCommands {
+    /// Check the hash chain of the audit log for edited, removed or truncated entries.
+    VerifyLog,
 }
 
 /// Enum representing available CLI commands.
@@ -31,17 +108,39 @@ enum Commands {
         n: u32,
         #[arg(short, long, default_value = "./results/frost_keys.json")]
         output_key_file: String,
+        /// Ciphersuite of the keys: frost-dalek, ed25519, ristretto255, secp256k1 or secp256k1-tr.
+        /// Only frost-dalek keys come from a distributed key generation: the RFC 9591
+        /// suites are split by a trusted dealer that holds the whole group secret, and
+        /// their keys can only be used with `sign` and `verify`.
+        #[arg(long, default_value = "frost-dalek")]
+        ciphersuite: SuiteId,
     },
+    /// Generate keys and write a share file per holder, guided step by step.
+    Ceremony,
     /// Sign a message using a threshold of private key shares.
     Sign {
-        /// The message to sign.
+        /// The message to sign. Optional with a proposal that carries the message.
+        #[arg(short, long, required_unless_present = "proposal")]
+        message: Option<String>,
+        /// Sign an approved proposal instead, with the signers and context it carries.
         #[arg(short, long)]
-        message: String,
+        proposal: Option<String>,
+        /// The context the message is signed under.
+        #[arg(short, long, default_value = DEFAULT_CONTEXT, conflicts_with = "proposal")]
+        context: String,
+        /// Path to a signing policy every signer checks before signing.
+        #[arg(long)]
+        policy_file: Option<String>,
         /// Threshold value for signing.
-        #[arg(short = 'i', long, default_value = "1,2,3")]
+        #[arg(
+            short = 'i',
+            long,
+            default_value = "1,2,3",
+            conflicts_with = "proposal"
+        )]
         signers: String,
         /// Total number of participants.
-        #[arg(short, long, default_value = "5")]
+        #[arg(short, long, default_value = "5", conflicts_with = "proposal")]
         n: u32,
         /// Path to the JSON file containing key shares.
         #[arg(short, long, default_value = "./results/frost_keys.json")]
@@ -49,53 +148,1110 @@ enum Commands {
         /// Path to save the resulting signature.
         #[arg(short = 'f', long, default_value = "./results/signature.json")]
         signature_file: String,
+        /// Ciphersuite the key file must use; taken from the key file if omitted.
+        #[arg(long)]
+        ciphersuite: Option<SuiteId>,
+    },
+    /// Sign a batch of messages in a single signing session.
+    SignBatch {
+        /// File with one message per line, or `-` to read from stdin.
+        #[arg(short, long, default_value = "-")]
+        messages_file: String,
+        /// Treat each line as a JSON string (JSONL) instead of raw text.
+        #[arg(short, long)]
+        jsonl: bool,
+        /// Signers taking part in the session.
+        #[arg(short = 'i', long, default_value = "1,2,3")]
+        signers: String,
+        /// Total number of participants.
+        #[arg(short, long, default_value = "5")]
+        n: u32,
+        /// Path to the JSON file containing key shares.
+        #[arg(short, long, default_value = "./results/frost_keys.json")]
+        key_file: String,
+        /// Path to save the resulting signatures.
+        #[arg(short = 'f', long, default_value = "./results/signatures.json")]
+        signature_file: String,
+        #[command(flatten)]
+        frost_dalek_only: FrostDalekOnly,
     },
     /// Verify a signature using the public key.
     Verify {
         /// The signed message to verify.
         #[arg(short, long)]
         message: String,
+        /// The context the message was signed under.
+        #[arg(short, long, default_value = DEFAULT_CONTEXT)]
+        context: String,
         /// Path to the JSON file containing the public key.
         #[arg(short, long, default_value = "./results/frost_keys.json")]
         key_file: String,
         /// Path to the JSON file containing the signature.
         #[arg(short, long, default_value = "./results/signature.json")]
         signature_file: String,
+        /// Ciphersuite the key file must use; taken from the key file if omitted.
+        #[arg(long)]
+        ciphersuite: Option<SuiteId>,
+    },
+    /// Export the share of a single participant from a key file.
+    ExportShare {
+        /// Participant index (1 to n) whose share is exported.
+        #[arg(short, long)]
+        index: u32,
+        /// Path to the JSON file containing key shares.
+        #[arg(short, long, default_value = "./results/frost_keys.json")]
+        key_file: String,
+        /// Path to save the participant's share.
+        #[arg(short, long)]
+        share_file: String,
+    },
+    /// Export the public package (group key and public key shares) of a key file.
+    ExportPublic {
+        /// Path to the JSON file containing key shares.
+        #[arg(short, long, default_value = "./results/frost_keys.json")]
+        key_file: String,
+        /// Path to save the public package.
+        #[arg(short, long, default_value = "./results/frost_public.json")]
+        public_file: String,
+    },
+    /// Print the group key of an ed25519 key set for standard Ed25519 tools.
+    Ed25519PublicKey {
+        /// Path to the JSON file containing ed25519 key shares.
+        #[arg(short, long, default_value = "./results/frost_keys.json")]
+        key_file: String,
+        /// Output format: hex, openssh or base58 (Solana).
+        #[arg(short, long, default_value = "hex")]
+        format: KeyFormat,
+        /// Comment appended to OpenSSH keys.
+        #[arg(short, long, default_value = "")]
+        comment: String,
+    },
+    /// Print the x-only BIP-340 public key of a secp256k1-tr key set.
+    Bip340PublicKey {
+        /// Path to the JSON file containing secp256k1-tr key shares.
+        #[arg(short, long, default_value = "./results/frost_keys.json")]
+        key_file: String,
+    },
+    /// Apply the Taproot tweak to a secp256k1-tr key set, for key-path spends of the output key.
+    TaprootTweak {
+        /// Path to the JSON file containing the key shares of the internal key.
+        #[arg(short, long, default_value = "./results/frost_keys.json")]
+        key_file: String,
+        /// Merkle root of the script tree in hex; omit for an output key without scripts.
+        #[arg(short, long)]
+        merkle_root: Option<String>,
+        /// Path to save the key shares of the output key.
+        #[arg(short, long, default_value = "./results/frost_taproot_keys.json")]
+        output_key_file: String,
+    },
+    /// Hold one share and sign on request of a coordinator.
+    ServeParticipant {
+        /// Path to the participant's share file.
+        #[arg(short, long)]
+        share_file: String,
+        /// Address to listen on.
+        #[arg(short, long, default_value = "127.0.0.1:7001")]
+        address: String,
+        /// Number of coordinator connections to serve before exiting (default: unlimited).
+        #[arg(long)]
+        sessions: Option<usize>,
+        /// Path to a signing policy checked before every partial signature.
+        #[arg(long)]
+        policy_file: Option<String>,
+        #[command(flatten)]
+        frost_dalek_only: FrostDalekOnly,
+    },
+    /// Sign a message by driving the signing rounds with participant daemons.
+    Coordinate {
+        /// The message to sign.
+        #[arg(short, long)]
+        message: String,
+        /// The context the message is signed under.
+        #[arg(short, long, default_value = DEFAULT_CONTEXT)]
+        context: String,
+        /// Comma-separated addresses of the participant daemons.
+        #[arg(short, long)]
+        participants: String,
+        /// Path to the group's public package, which the participants are checked against.
+        #[arg(long, default_value = "./results/frost_public.json")]
+        public_file: String,
+        /// Path to save the resulting signature.
+        #[arg(short = 'f', long, default_value = "./results/signature.json")]
+        signature_file: String,
+        /// Show each participant's progress in a terminal dashboard.
+        #[arg(long)]
+        dashboard: bool,
+        /// Start signing with the first t participants to commit, and retry with
+        /// others when a participant fails or times out.
+        #[arg(long)]
+        robust: bool,
+        /// Seconds to wait for a participant's answer before excluding it, with --robust.
+        #[arg(long, default_value = "30", requires = "robust")]
+        timeout: u64,
+        #[command(flatten)]
+        frost_dalek_only: FrostDalekOnly,
+    },
+    /// Serve signing and verification over an HTTP API.
+    Serve {
+        /// Path to the JSON file containing key shares.
+        #[arg(short, long, default_value = "./results/frost_keys.json")]
+        key_file: String,
+        /// Address to listen on.
+        #[arg(short, long, default_value = "127.0.0.1:8080")]
+        address: String,
+        /// Path to a signing policy every request is checked against before signing.
+        #[arg(long)]
+        policy_file: Option<String>,
+        #[command(flatten)]
+        frost_dalek_only: FrostDalekOnly,
+    },
+    /// Identify and check a key set, share, public package or signature file.
+    Inspect {
+        /// Path to the file to inspect.
+        file: String,
+        /// Ciphersuite to decode signatures with; only their lengths are shown if omitted.
+        #[arg(long)]
+        ciphersuite: Option<SuiteId>,
+    },
+    /// Refresh every share of a key file in-process, keeping the group key.
+    Refresh {
+        /// Path to the JSON file containing key shares.
+        #[arg(short, long, default_value = "./results/frost_keys.json")]
+        key_file: String,
+        /// Path to save the refreshed key shares.
+        #[arg(short, long, default_value = "./results/frost_keys_refreshed.json")]
+        output_key_file: String,
+        #[command(flatten)]
+        frost_dalek_only: FrostDalekOnly,
+    },
+    /// First refresh round: deal this holder's refresh packages, one per participant.
+    RefreshDeal {
+        /// Path to the holder's share file.
+        #[arg(short, long)]
+        share_file: String,
+        /// Directory to write the packages to.
+        #[arg(short, long, default_value = "./results")]
+        output_dir: String,
+        #[command(flatten)]
+        frost_dalek_only: FrostDalekOnly,
+    },
+    /// Second refresh round: apply the packages received from every participant.
+    RefreshFinish {
+        /// Path to the holder's share file.
+        #[arg(short, long)]
+        share_file: String,
+        /// Comma-separated paths to the packages addressed to this holder.
+        #[arg(short, long)]
+        packages: String,
+        /// Commitments digest printed by another holder; refuse to finish if ours differs.
+        #[arg(long)]
+        expected_digest: Option<String>,
+        /// Path to save the refreshed share.
+        #[arg(short, long)]
+        output_share_file: String,
+        #[command(flatten)]
+        frost_dalek_only: FrostDalekOnly,
+    },
+    /// Reshare the keys of a key file in-process to a new threshold and participant set.
+    Reshare {
+        /// Path to the JSON file containing key shares.
+        #[arg(short, long, default_value = "./results/frost_keys.json")]
+        key_file: String,
+        /// Current holders taking part, at least t of them.
+        #[arg(long, default_value = "1,2,3")]
+        holders: String,
+        /// New threshold.
+        #[arg(short, long)]
+        t: u32,
+        /// New number of participants.
+        #[arg(short, long)]
+        n: u32,
+        /// Directory to write the new share files and public package to.
+        #[arg(short, long, default_value = "./results/reshared")]
+        output_dir: String,
+        #[command(flatten)]
+        frost_dalek_only: FrostDalekOnly,
+    },
+    /// First reshare round: deal this holder's share to the new participants.
+    ReshareDeal {
+        /// Path to the holder's share file.
+        #[arg(short, long)]
+        share_file: String,
+        /// Current holders taking part, at least t of them.
+        #[arg(long)]
+        holders: String,
+        /// New threshold.
+        #[arg(short, long)]
+        t: u32,
+        /// New number of participants.
+        #[arg(short, long)]
+        n: u32,
+        /// Directory to write the packages to.
+        #[arg(short, long, default_value = "./results")]
+        output_dir: String,
+        #[command(flatten)]
+        frost_dalek_only: FrostDalekOnly,
+    },
+    /// Second reshare round: combine the packages received from the dealing holders.
+    ReshareFinish {
+        /// Path to the public package of the group before the reshare.
+        #[arg(short, long, default_value = "./results/frost_public.json")]
+        public_file: String,
+        /// The new participant's index (1 to n).
+        #[arg(short, long)]
+        index: u32,
+        /// New threshold.
+        #[arg(short, long)]
+        t: u32,
+        /// New number of participants.
+        #[arg(short, long)]
+        n: u32,
+        /// Comma-separated paths to the packages addressed to this participant.
+        #[arg(long)]
+        packages: String,
+        /// Commitments digest printed by another new participant; refuse to finish if ours differs.
+        #[arg(long)]
+        expected_digest: Option<String>,
+        /// Path to save the new share.
+        #[arg(short, long)]
+        output_share_file: String,
+        /// Path to save the new public package.
+        #[arg(long, default_value = "./results/frost_public_reshared.json")]
+        output_public_file: String,
+        #[command(flatten)]
+        frost_dalek_only: FrostDalekOnly,
+    },
+    /// First repair round: split this helper's contribution to a lost share into parts for the helpers.
+    RepairDeal {
+        /// Path to the helper's share file.
+        #[arg(short, long)]
+        share_file: String,
+        /// Helpers taking part, at least t of them.
+        #[arg(long)]
+        helpers: String,
+        /// Index of the participant whose share was lost.
+        #[arg(short, long)]
+        lost: u32,
+        /// Directory to write the parts to.
+        #[arg(short, long, default_value = "./results")]
+        output_dir: String,
+        #[command(flatten)]
+        frost_dalek_only: FrostDalekOnly,
+    },
+    /// Second repair round: add the parts received from every helper.
+    RepairCombine {
+        /// Path to the helper's share file.
+        #[arg(short, long)]
+        share_file: String,
+        /// Comma-separated paths to the parts addressed to this helper.
+        #[arg(short, long)]
+        parts: String,
+        /// Path to save the sum for the recovering participant.
+        #[arg(short, long)]
+        output_file: String,
+        #[command(flatten)]
+        frost_dalek_only: FrostDalekOnly,
+    },
+    /// Last repair round: rebuild the lost share from the helpers' sums.
+    RepairFinish {
+        /// Path to the group's public package.
+        #[arg(short, long, default_value = "./results/frost_public.json")]
+        public_file: String,
+        /// Index of the recovering participant.
+        #[arg(short, long)]
+        index: u32,
+        /// Comma-separated paths to the sums, one from every helper.
+        #[arg(short = 'u', long)]
+        sums: String,
+        /// Path to save the repaired share.
+        #[arg(short, long)]
+        output_share_file: String,
+        #[command(flatten)]
+        frost_dalek_only: FrostDalekOnly,
+    },
+    /// Enroll a new participant into a key file in-process.
+    Enroll {
+        /// Path to the JSON file containing key shares.
+        #[arg(short, long, default_value = "./results/frost_keys.json")]
+        key_file: String,
+        /// Holders computing the new share, at least t of them.
+        #[arg(long, default_value = "1,2,3")]
+        helpers: String,
+        /// Path to save the keys including the new participant.
+        #[arg(short, long, default_value = "./results/frost_keys_enrolled.json")]
+        output_key_file: String,
+        #[command(flatten)]
+        frost_dalek_only: FrostDalekOnly,
+    },
+    /// First enrollment round: split this helper's contribution to the new participant's share.
+    EnrollDeal {
+        /// Path to the helper's share file.
+        #[arg(short, long)]
+        share_file: String,
+        /// Path to the group's public package.
+        #[arg(short, long, default_value = "./results/frost_public.json")]
+        public_file: String,
+        /// Helpers taking part, at least t of them.
+        #[arg(long)]
+        helpers: String,
+        /// Directory to write the parts to.
+        #[arg(short, long, default_value = "./results")]
+        output_dir: String,
+        #[command(flatten)]
+        frost_dalek_only: FrostDalekOnly,
+    },
+    /// Last enrollment round: build the new participant's share from the helpers' sums.
+    EnrollFinish {
+        /// Path to the group's public package.
+        #[arg(short, long, default_value = "./results/frost_public.json")]
+        public_file: String,
+        /// Comma-separated paths to the sums, one from every helper.
+        #[arg(short = 'u', long)]
+        sums: String,
+        /// Path to save the new participant's share.
+        #[arg(short, long)]
+        output_share_file: String,
+        /// Path to save the public package including the new participant.
+        #[arg(long, default_value = "./results/frost_public_enrolled.json")]
+        output_public_file: String,
+        #[command(flatten)]
+        frost_dalek_only: FrostDalekOnly,
+    },
+    /// After an enrollment: update an existing holder's share to the new participant count.
+    EnrollAccept {
+        /// Path to the holder's share file.
+        #[arg(short, long)]
+        share_file: String,
+        /// Path to the public package including the new participant.
+        #[arg(short, long, default_value = "./results/frost_public_enrolled.json")]
+        public_file: String,
+        /// Path to save the updated share.
+        #[arg(short, long)]
+        output_share_file: String,
+        #[command(flatten)]
+        frost_dalek_only: FrostDalekOnly,
+    },
+    /// Create a signing proposal for the signers to review and approve.
+    Propose {
+        /// The message to sign.
+        #[arg(short, long)]
+        message: String,
+        /// Store only the message hash; the message must be supplied again at signing.
+        #[arg(long)]
+        hash_only: bool,
+        /// The application context the message is signed for.
+        #[arg(short, long, default_value = "default")]
+        context: String,
+        /// Participant indices (1 to n) requested to sign.
+        #[arg(short = 'i', long, default_value = "1,2,3")]
+        signers: String,
+        /// Seconds from now until the proposal expires.
+        #[arg(short, long, default_value = "3600")]
+        expires_in: u64,
+        /// A note from the requester to the signers.
+        #[arg(long, default_value = "")]
+        note: String,
+        /// Path to the group's public package.
+        #[arg(short, long, default_value = "./results/frost_public.json")]
+        public_file: String,
+        /// Path to save the proposal.
+        #[arg(short = 'f', long, default_value = "./results/proposal.json")]
+        proposal_file: String,
+        #[command(flatten)]
+        frost_dalek_only: FrostDalekOnly,
+    },
+    /// Inspect the audit log.
+    Audit {
+        #[command(subcommand)]
+        command: AuditCommands,
+    },
+    /// Review a signing proposal and add this participant's approval.
+    Approve {
+        /// Path to the proposal.
+        #[arg(short = 'f', long, default_value = "./results/proposal.json")]
+        proposal_file: String,
+        /// Path to the participant's share file.
+        #[arg(short, long)]
+        share_file: String,
+        /// Approve without asking for confirmation.
+        #[arg(short, long)]
+        yes: bool,
+        #[command(flatten)]
+        frost_dalek_only: FrostDalekOnly,
+    },
+    /// Time each phase of key generation, signing and verification on this machine.
+    Bench {
+        /// Threshold value for key shares.
+        #[arg(short, long, default_value = "3")]
+        t: u32,
+        /// Total number of key shares to generate.
+        #[arg(short, long, default_value = "5")]
+        n: u32,
+        /// Number of times every operation is run.
+        #[arg(short, long, default_value = "10")]
+        iterations: u32,
     },
 }
 
 fn main() {
     let cli = Cli::parse();
+    frost_cli::secret::set_memory_locking(cli.mlock);
+    frost_cli::integrity::set_allow_legacy(cli.allow_legacy);
 
     match &cli.command {
         Commands::Generate {
             t,
             n,
             output_key_file,
+            ciphersuite,
         } => {
-            generate_keys(*t, *n, output_key_file).expect("Failed to generate keys");
+            let result = generate_keys_with_suite(*t, *n, output_key_file, *ciphersuite, cli.force);
+            let signers: Vec<u32> = (1..=*n).collect();
+            // A failed run may have left an earlier key file in place.
+            let key_fingerprint = match &result {
+                Ok(()) => key_details(output_key_file, &[]).0,
+                Err(_) => None,
+            };
+            record(
+                &cli.audit_log,
+                AuditRecord {
+                    operation: "generate".to_string(),
+                    key_fingerprint,
+                    signers,
+                    outcome: outcome(&result),
+                    ..Default::default()
+                },
+            );
+            result.expect("Failed to generate keys");
+        }
+        Commands::Ceremony => {
+            let stdin = io::stdin();
+            let hide_passphrases = stdin.is_terminal();
+            let mut prompter = Prompter::new(stdin.lock(), io::stdout(), hide_passphrases);
+            let result = run_ceremony(&mut prompter, cli.force);
+            if !matches!(result, Ok(None)) {
+                let summary = result.as_ref().ok().and_then(Option::as_ref);
+                record(
+                    &cli.audit_log,
+                    AuditRecord {
+                        operation: "ceremony".to_string(),
+                        key_fingerprint: summary.map(|summary| summary.fingerprint.clone()),
+                        signers: summary
+                            .map(|summary| {
+                                summary.holders.iter().map(|holder| holder.index).collect()
+                            })
+                            .unwrap_or_default(),
+                        outcome: outcome(&result),
+                        ..Default::default()
+                    },
+                );
+            }
+            if let Err(err) = result {
+                eprintln!("Ceremony failed: {}", err);
+                std::process::exit(1);
+            }
         }
         Commands::Sign {
             message,
+            proposal,
+            context,
+            policy_file,
+            signers,
+            n,
+            key_file,
+            signature_file,
+            ciphersuite,
+        } => {
+            check_ciphersuite(key_file, *ciphersuite);
+            let policy = policy_file
+                .as_deref()
+                .map(load_policy)
+                .transpose()
+                .expect("Failed to load policy");
+            if let Some(proposal) = proposal {
+                let result = sign_proposal(
+                    proposal,
+                    message.as_deref(),
+                    key_file,
+                    signature_file,
+                    policy.as_ref(),
+                );
+                let details = load_proposal(proposal).ok();
+                record(
+                    &cli.audit_log,
+                    AuditRecord {
+                        operation: "sign".to_string(),
+                        key_fingerprint: key_details(key_file, &[]).0,
+                        message_hashes: details
+                            .iter()
+                            .map(|details| details.message_hash.clone())
+                            .collect(),
+                        context: details.as_ref().map(|details| details.context.clone()),
+                        signers: details.map(|details| details.signers).unwrap_or_default(),
+                        outcome: outcome(&result),
+                    },
+                );
+                result.expect("Failed to sign proposal");
+                return;
+            }
+            let message = message.as_deref().expect("A message is required");
+            let signers: Vec<u32> = signers
+                .split(',')
+                .map(|s| s.parse().expect("Invalid signer index"))
+                .collect();
+            let (key_fingerprint, indices) = key_details(key_file, &signers);
+            let result = sign_message_with_policy(
+                message,
+                signers,
+                *n,
+                key_file,
+                signature_file,
+                context,
+                policy.as_ref(),
+            );
+            record(
+                &cli.audit_log,
+                AuditRecord {
+                    operation: "sign".to_string(),
+                    key_fingerprint,
+                    message_hashes: vec![message_hash(message.as_bytes())],
+                    context: Some(context.clone()),
+                    signers: indices,
+                    outcome: outcome(&result),
+                },
+            );
+            result.expect("Failed to sign message");
+        }
+        Commands::SignBatch {
+            messages_file,
+            jsonl,
             signers,
             n,
             key_file,
             signature_file,
+            ..
         } => {
+            let messages = if messages_file == "-" {
+                read_messages(io::stdin().lock(), *jsonl)
+            } else {
+                let file = File::open(messages_file).expect("Failed to open messages file");
+                read_messages(BufReader::new(file), *jsonl)
+            }
+            .expect("Failed to read messages");
             let signers: Vec<u32> = signers
                 .split(',')
                 .map(|s| s.parse().expect("Invalid signer index"))
                 .collect();
-            sign_message(message, signers, *n, key_file, signature_file)
-                .expect("Failed to sign message");
+            let (key_fingerprint, indices) = key_details(key_file, &signers);
+            let result = sign_batch(&messages, signers, *n, key_file, signature_file);
+            record(
+                &cli.audit_log,
+                AuditRecord {
+                    operation: "sign-batch".to_string(),
+                    key_fingerprint,
+                    message_hashes: messages
+                        .iter()
+                        .map(|message| message_hash(message.as_bytes()))
+                        .collect(),
+                    context: Some(DEFAULT_CONTEXT.to_string()),
+                    signers: indices,
+                    outcome: outcome(&result),
+                },
+            );
+            result.expect("Failed to sign messages");
         }
         Commands::Verify {
             message,
+            context,
             key_file,
             signature_file,
+            ciphersuite,
         } => {
-            validate_signature(message, key_file, signature_file)
+            check_ciphersuite(key_file, *ciphersuite);
+            validate_signature_with_context(message, context, key_file, signature_file)
                 .expect("Failed to verify signature");
         }
+        Commands::ExportShare {
+            index,
+            key_file,
+            share_file,
+        } => {
+            export_share(key_file, *index, share_file, cli.force).expect("Failed to export share");
+        }
+        Commands::ExportPublic {
+            key_file,
+            public_file,
+        } => {
+            export_public_package(key_file, public_file).expect("Failed to export public package");
+        }
+        Commands::Ed25519PublicKey {
+            key_file,
+            format,
+            comment,
+        } => {
+            let public_key =
+                export_public_key(key_file, *format, comment).expect("Failed to export public key");
+            println!("{}", public_key);
+        }
+        Commands::Bip340PublicKey { key_file } => {
+            let public_key = export_x_only_key(key_file).expect("Failed to export public key");
+            println!("{}", public_key);
+        }
+        Commands::TaprootTweak {
+            key_file,
+            merkle_root,
+            output_key_file,
+        } => {
+            let merkle_root: Option<[u8; 32]> = merkle_root.as_deref().map(|root| {
+                hex::decode(root)
+                    .ok()
+                    .and_then(|bytes| bytes.try_into().ok())
+                    .expect("Merkle root must be 32 bytes in hex")
+            });
+            taproot_tweak_keys(key_file, merkle_root.as_ref(), output_key_file, cli.force)
+                .expect("Failed to tweak keys");
+        }
+        Commands::ServeParticipant {
+            share_file,
+            address,
+            sessions,
+            policy_file,
+            ..
+        } => {
+            serve_participant(
+                share_file,
+                address,
+                *sessions,
+                policy_file.as_deref(),
+                Some(&cli.audit_log),
+            )
+            .expect("Failed to serve participant");
+        }
+        Commands::Coordinate {
+            message,
+            context,
+            participants,
+            public_file,
+            signature_file,
+            dashboard,
+            robust,
+            timeout,
+            ..
+        } => {
+            let participants: Vec<String> = participants
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .collect();
+            let robust = robust.then(|| Duration::from_secs(*timeout));
+            let mut signers = Vec::new();
+            let result = match (dashboard, robust) {
+                (true, robust) => coordinate_with_dashboard(
+                    message,
+                    context,
+                    public_file,
+                    &participants,
+                    robust,
+                    signature_file,
+                    &mut signers,
+                ),
+                (false, Some(timeout)) => coordinate_robust(
+                    message,
+                    context,
+                    public_file,
+                    &participants,
+                    timeout,
+                    signature_file,
+                    &mut signers,
+                ),
+                (false, None) => coordinate(
+                    message,
+                    context,
+                    public_file,
+                    &participants,
+                    signature_file,
+                    &mut signers,
+                ),
+            };
+            signers.sort_unstable();
+            record(
+                &cli.audit_log,
+                AuditRecord {
+                    operation: "coordinate".to_string(),
+                    key_fingerprint: public_fields(public_file)
+                        .map(|fields| fingerprint(&fields.group_key)),
+                    message_hashes: vec![message_hash(message.as_bytes())],
+                    context: Some(context.clone()),
+                    signers,
+                    outcome: outcome(&result),
+                },
+            );
+            result.expect("Failed to coordinate signing");
+        }
+        Commands::Serve {
+            key_file,
+            address,
+            policy_file,
+            ..
+        } => {
+            let token = std::env::var(frost_cli::http::TOKEN_VAR).ok();
+            serve(
+                key_file,
+                address,
+                policy_file.as_deref(),
+                token,
+                Some(cli.audit_log.clone()),
+            )
+            .expect("Failed to serve signing API");
+        }
+        Commands::Inspect { file, ciphersuite } => {
+            let inspection =
+                inspect_file_with_suite(file, *ciphersuite).expect("Failed to inspect file");
+            println!("{}", inspection);
+            if !inspection.is_valid() {
+                std::process::exit(1);
+            }
+        }
+        Commands::Refresh {
+            key_file,
+            output_key_file,
+            ..
+        } => {
+            let result = refresh_keys(key_file, output_key_file, cli.force);
+            record_operation(&cli.audit_log, "refresh", key_file, &[], result)
+                .expect("Failed to refresh keys");
+        }
+        Commands::RefreshDeal {
+            share_file,
+            output_dir,
+            ..
+        } => {
+            let result = refresh_deal(share_file, output_dir, cli.force);
+            record_operation(&cli.audit_log, "refresh-deal", share_file, &[], result)
+                .expect("Failed to deal refresh packages");
+        }
+        Commands::RefreshFinish {
+            share_file,
+            packages,
+            expected_digest,
+            output_share_file,
+            ..
+        } => {
+            let packages: Vec<String> = packages.split(',').map(|s| s.trim().to_string()).collect();
+            let result = refresh_finish(
+                share_file,
+                &packages,
+                expected_digest.as_deref(),
+                output_share_file,
+                cli.force,
+            );
+            record_operation(&cli.audit_log, "refresh-finish", share_file, &[], result)
+                .expect("Failed to refresh share");
+        }
+        Commands::Reshare {
+            key_file,
+            holders,
+            t,
+            n,
+            output_dir,
+            ..
+        } => {
+            let holders: Vec<u32> = holders
+                .split(',')
+                .map(|s| s.parse().expect("Invalid holder index"))
+                .collect();
+            let result = reshare_keys(key_file, &holders, *t, *n, output_dir, cli.force);
+            record_operation(&cli.audit_log, "reshare", key_file, &holders, result)
+                .expect("Failed to reshare keys");
+        }
+        Commands::ReshareDeal {
+            share_file,
+            holders,
+            t,
+            n,
+            output_dir,
+            ..
+        } => {
+            let holders: Vec<u32> = holders
+                .split(',')
+                .map(|s| s.parse().expect("Invalid holder index"))
+                .collect();
+            let result = reshare_deal(share_file, &holders, *t, *n, output_dir, cli.force);
+            record_operation(&cli.audit_log, "reshare-deal", share_file, &holders, result)
+                .expect("Failed to deal reshare packages");
+        }
+        Commands::ReshareFinish {
+            public_file,
+            index,
+            t,
+            n,
+            packages,
+            expected_digest,
+            output_share_file,
+            output_public_file,
+            ..
+        } => {
+            let packages: Vec<String> = packages.split(',').map(|s| s.trim().to_string()).collect();
+            let result = reshare_finish(
+                public_file,
+                *index,
+                *t,
+                *n,
+                &packages,
+                expected_digest.as_deref(),
+                output_share_file,
+                output_public_file,
+                cli.force,
+            );
+            record_operation(&cli.audit_log, "reshare-finish", public_file, &[], result)
+                .expect("Failed to complete reshare");
+        }
+        Commands::RepairDeal {
+            share_file,
+            helpers,
+            lost,
+            output_dir,
+            ..
+        } => {
+            let helpers: Vec<u32> = helpers
+                .split(',')
+                .map(|s| s.parse().expect("Invalid helper index"))
+                .collect();
+            let result = repair_deal(share_file, &helpers, *lost, output_dir, cli.force);
+            record_operation(&cli.audit_log, "repair-deal", share_file, &helpers, result)
+                .expect("Failed to deal repair parts");
+        }
+        Commands::RepairCombine {
+            share_file,
+            parts,
+            output_file,
+            ..
+        } => {
+            let parts: Vec<String> = parts.split(',').map(|s| s.trim().to_string()).collect();
+            let result = repair_combine(share_file, &parts, output_file, cli.force);
+            record_operation(&cli.audit_log, "repair-combine", share_file, &[], result)
+                .expect("Failed to combine repair parts");
+        }
+        Commands::RepairFinish {
+            public_file,
+            index,
+            sums,
+            output_share_file,
+            ..
+        } => {
+            let sums: Vec<String> = sums.split(',').map(|s| s.trim().to_string()).collect();
+            let result = repair_finish(public_file, *index, &sums, output_share_file, cli.force);
+            record_operation(&cli.audit_log, "repair-finish", public_file, &[], result)
+                .expect("Failed to repair share");
+        }
+        Commands::Enroll {
+            key_file,
+            helpers,
+            output_key_file,
+            ..
+        } => {
+            let helpers: Vec<u32> = helpers
+                .split(',')
+                .map(|s| s.parse().expect("Invalid helper index"))
+                .collect();
+            let result = enroll_keys(key_file, &helpers, output_key_file, cli.force);
+            record_operation(&cli.audit_log, "enroll", key_file, &helpers, result)
+                .expect("Failed to enroll participant");
+        }
+        Commands::EnrollDeal {
+            share_file,
+            public_file,
+            helpers,
+            output_dir,
+            ..
+        } => {
+            let helpers: Vec<u32> = helpers
+                .split(',')
+                .map(|s| s.parse().expect("Invalid helper index"))
+                .collect();
+            let result = enroll_deal(share_file, public_file, &helpers, output_dir, cli.force);
+            record_operation(&cli.audit_log, "enroll-deal", share_file, &helpers, result)
+                .expect("Failed to deal enrollment parts");
+        }
+        Commands::EnrollFinish {
+            public_file,
+            sums,
+            output_share_file,
+            output_public_file,
+            ..
+        } => {
+            let sums: Vec<String> = sums.split(',').map(|s| s.trim().to_string()).collect();
+            let result = enroll_finish(
+                public_file,
+                &sums,
+                output_share_file,
+                output_public_file,
+                cli.force,
+            );
+            record_operation(&cli.audit_log, "enroll-finish", public_file, &[], result)
+                .expect("Failed to enroll participant");
+        }
+        Commands::EnrollAccept {
+            share_file,
+            public_file,
+            output_share_file,
+            ..
+        } => {
+            let result = enroll_accept(share_file, public_file, output_share_file, cli.force);
+            record_operation(&cli.audit_log, "enroll-accept", share_file, &[], result)
+                .expect("Failed to accept enrollment");
+        }
+        Commands::Propose {
+            message,
+            hash_only,
+            context,
+            signers,
+            expires_in,
+            note,
+            public_file,
+            proposal_file,
+            ..
+        } => {
+            let signers: Vec<u32> = signers
+                .split(',')
+                .map(|s| s.parse().expect("Invalid signer index"))
+                .collect();
+            propose(
+                public_file,
+                message,
+                *hash_only,
+                context,
+                signers,
+                *expires_in,
+                note,
+                proposal_file,
+            )
+            .expect("Failed to create proposal");
+        }
+        Commands::Approve {
+            proposal_file,
+            share_file,
+            yes,
+            ..
+        } => {
+            let proposal = load_proposal(proposal_file).expect("Failed to load proposal");
+            println!("{}", proposal);
+            if !yes {
+                print!("Approve this proposal? [y/N] ");
+                io::stdout().flush().expect("Failed to write prompt");
+                let mut answer = String::new();
+                io::stdin()
+                    .read_line(&mut answer)
+                    .expect("Failed to read answer");
+                if !answer.trim().eq_ignore_ascii_case("y") {
+                    println!("Proposal not approved");
+                    return;
+                }
+            }
+            approve_proposal(proposal_file, share_file).expect("Failed to approve proposal");
+        }
+        Commands::Audit {
+            command: AuditCommands::VerifyLog,
+        } => match verify_log(&cli.audit_log) {
+            Ok(entries) => println!("Audit log is intact: {} entries", entries),
+            Err(err) => {
+                eprintln!("Audit log verification failed: {}", err);
+                std::process::exit(1);
+            }
+        },
+        Commands::Bench { t, n, iterations } => {
+            let report = run_bench(*t, *n, *iterations).expect("Failed to run the benchmark");
+            print!("{}", report);
+        }
+    }
+}
+
+/// Appends a record to the audit log.
+fn record(audit_log: &str, record: AuditRecord) {
+    append(audit_log, record).expect("Failed to append to the audit log");
+}
+
+/// Records a key management operation on the group of `file`, a key, share or
+/// public package file, and passes its result through.
+fn record_operation(
+    audit_log: &str,
+    operation: &str,
+    file: &str,
+    participants: &[u32],
+    result: Result<(), Box<dyn std::error::Error>>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let key_fingerprint = public_fields(file).map(|fields| fingerprint(&fields.group_key));
+    record(
+        audit_log,
+        AuditRecord {
+            operation: operation.to_string(),
+            key_fingerprint,
+            signers: participants.to_vec(),
+            outcome: outcome(&result),
+            ..Default::default()
+        },
+    );
+    result
+}
+
+/// Returns the group key fingerprint of a key file and the participant indices
+/// of the signers at the given positions, as far as the key file can be read.
+fn key_details(key_file: &str, signers: &[u32]) -> (Option<String>, Vec<u32>) {
+    match public_fields(key_file) {
+        Some(fields) => (
+            Some(fingerprint(&fields.group_key)),
+            signers
+                .iter()
+                .filter_map(|&signer| fields.private_shares.get(signer as usize))
+                .map(|(_, index)| *index)
+                .collect(),
+        ),
+        None => (None, Vec::new()),
+    }
+}
+
+/// The public fields of a key, share or public package file, encrypted or not.
+///
+/// Audit records only need the group key and the participant indices, so they
+/// are read without parsing, decrypting or locking any private share.
+#[derive(Deserialize)]
+struct PublicFields {
+    group_key: Vec<u8>,
+    #[serde(default)]
+    private_shares: Vec<(IgnoredAny, u32)>,
+}
+
+/// Reads the public fields of `file`, if it can be read.
+fn public_fields(file: &str) -> Option<PublicFields> {
+    let contents = Zeroizing::new(fs::read_to_string(file).ok()?);
+    serde_json::from_str(&contents).ok()
+}
+
+/// Parses the `--ciphersuite` of a [`FrostDalekOnly`] command, refusing every suite but frost-dalek.
+fn parse_frost_dalek_suite(name: &str) -> Result<SuiteId, String> {
+    let suite: SuiteId = name.parse()?;
+    if suite != SuiteId::FrostDalek {
+        return Err(format!(
+            "this command runs frost-dalek's protocol only; {} keys come from a trusted \
+             dealer and can only be used with `sign` and `verify`",
+            suite
+        ));
+    }
+    Ok(suite)
+}
+
+/// Exits with an error if the key file does not use the ciphersuite asked for on the command line.
+fn check_ciphersuite(key_file: &str, expected: Option<SuiteId>) {
+    let Some(expected) = expected else {
+        return;
+    };
+    let suite = load_key_file(key_file)
+        .and_then(|key_file| key_file.suite())
+        .expect("Failed to load keys");
+    if suite != expected {
+        eprintln!(
+            "Key file {} uses the {} ciphersuite, not {}",
+            key_file, suite, expected
+        );
+        std::process::exit(1);
     }
 }
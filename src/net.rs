@@ -0,0 +1,624 @@
+//! Networked threshold signing over TCP.
+//!
+//! A participant daemon ([`serve_participant`]) holds a single share and answers the
+//! requests of a coordinator ([`coordinate`]), which drives the signing rounds and
+//! aggregates the final signature. Shares never leave the participant's machine.
+//!
+//! # Protocol
+//!
+//! Every message is a frame: a 4-byte big-endian length followed by that many bytes
+//! of JSON, at most [`MAX_FRAME_LEN`] bytes long. The coordinator opens one connection
+//! per participant and sends [`Request`]s, each answered by exactly one [`Response`]:
+//!
+//! 1. `"Info"` is answered with `{"Info": {index, threshold, group_key, public_share}}`.
+//! 2. `"Commit"` is answered with `{"Commitment": {index, hiding, binding}}`. The
+//!    participant keeps the matching nonces for the rest of the connection.
+//! 3. `{"Sign": {message, context, commitments}}` is answered with
+//!    `{"PartialSignature": {index, z}}`. The nonces are discarded, so a new
+//!    `"Commit"` is needed before signing again. A participant with a signing
+//!    policy first checks the request against it, and answers a request the
+//!    policy does not allow with an `"Error"` naming the violation.
+//!
+//! A request that cannot be served is answered with `{"Error": "<reason>"}`. Nonces
+//! are never kept beyond the connection they were committed on.
+//!
+//! The coordinator does not take a participant's word for the group: every `"Info"`
+//! answer is checked against the group's public package, partial signatures are
+//! checked against the public key shares it lists, and the aggregated signature is
+//! verified under its group key before it is returned.
+
+use crate::audit::{self, message_hash, outcome, AuditRecord};
+use crate::files;
+use crate::policy::{load_policy, Policy, PolicyViolation, DEFAULT_CONTEXT};
+use crate::rounds::{self, InvalidShare, PartialSignature, SigningCommitment};
+use crate::{
+    fingerprint, load_public_package, load_share, verify_signature_with_context,
+    FrostPublicPackage, FrostShare,
+};
+use frost_dalek::signature::SecretKey as SignatureSecretKey;
+use rand::rngs::OsRng;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::time::Duration;
+use zeroize::Zeroize;
+
+/// Largest frame accepted in either direction, in bytes.
+pub const MAX_FRAME_LEN: u32 = 1 << 20;
+
+/// How long either side waits for the other before giving up on a connection.
+const SESSION_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A request sent by the coordinator to a participant.
+#[derive(Debug, Serialize, Deserialize)]
+pub enum Request {
+    Info,
+    Commit,
+    Sign {
+        message: String,
+        #[serde(default = "default_context")]
+        context: String,
+        commitments: Vec<SigningCommitment>,
+    },
+}
+
+fn default_context() -> String {
+    DEFAULT_CONTEXT.to_string()
+}
+
+/// A participant's answer to a [`Request`].
+#[derive(Debug, Serialize, Deserialize)]
+pub enum Response {
+    Info {
+        index: u32,
+        threshold: u32,
+        group_key: [u8; 32],
+        public_share: [u8; 32],
+    },
+    Commitment(SigningCommitment),
+    PartialSignature(PartialSignature),
+    PolicyViolation(PolicyViolation),
+    Error(String),
+}
+
+/// Writes one length-prefixed JSON frame.
+pub fn write_frame<T: Serialize, W: Write>(
+    writer: &mut W,
+    message: &T,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let body = serde_json::to_vec(message)?;
+    if body.len() > MAX_FRAME_LEN as usize {
+        return Err(format!("Frame of {} bytes exceeds the size limit", body.len()).into());
+    }
+    writer.write_all(&(body.len() as u32).to_be_bytes())?;
+    writer.write_all(&body)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Reads one length-prefixed JSON frame, or `None` if the peer closed the connection.
+pub fn read_frame<T: DeserializeOwned, R: Read>(
+    reader: &mut R,
+) -> Result<Option<T>, Box<dyn std::error::Error>> {
+    let mut len = [0u8; 4];
+    match reader.read_exact(&mut len) {
+        Ok(()) => {}
+        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(err) => return Err(err.into()),
+    }
+
+    let len = u32::from_be_bytes(len);
+    if len > MAX_FRAME_LEN {
+        return Err(format!("Frame of {} bytes exceeds the size limit", len).into());
+    }
+    let mut body = vec![0u8; len as usize];
+    reader.read_exact(&mut body)?;
+    Ok(Some(serde_json::from_slice(&body)?))
+}
+
+/// Runs a participant daemon holding the share in `share_file`.
+///
+/// # Arguments
+/// - `share_file`: Path to the participant's share file.
+/// - `address`: Address to listen on, e.g. `127.0.0.1:7001`.
+/// - `sessions`: Number of coordinator connections to serve before exiting, or `None` to serve forever.
+/// - `policy_file`: Path to the signing policy checked before every partial signature, if any.
+/// - `audit_log`: Path to the audit log every signing request is recorded in, if any.
+pub fn serve_participant(
+    share_file: &str,
+    address: &str,
+    sessions: Option<usize>,
+    policy_file: Option<&str>,
+    audit_log: Option<&str>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let share = load_share(share_file)?;
+    let policy = policy_file.map(load_policy).transpose()?;
+    let listener = TcpListener::bind(address)?;
+    println!(
+        "Participant {} listening on {}",
+        share.private_share.1,
+        listener.local_addr()?
+    );
+    run_participant(&share, listener, sessions, policy.as_ref(), audit_log)
+}
+
+/// Serves coordinator connections on an already bound listener.
+///
+/// A failed session is reported and does not stop the daemon. With an
+/// `audit_log`, every signing request is appended to it with its outcome, and a
+/// partial signature that cannot be recorded is not sent.
+pub fn run_participant(
+    share: &FrostShare,
+    listener: TcpListener,
+    sessions: Option<usize>,
+    policy: Option<&Policy>,
+    audit_log: Option<&str>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (mut key_bytes, index) = share.private_share;
+    let secret_key = SignatureSecretKey::from_bytes(index, key_bytes);
+    key_bytes.zeroize();
+    let secret_key = secret_key.map_err(|_| "Invalid private key bytes")?;
+
+    for (served, stream) in listener.incoming().enumerate() {
+        if let Err(err) = handle_session(share, &secret_key, policy, audit_log, stream?) {
+            eprintln!("Signing session failed: {}", err);
+        }
+        if sessions.is_some_and(|limit| served + 1 >= limit) {
+            break;
+        }
+    }
+    Ok(())
+}
+
+fn handle_session(
+    share: &FrostShare,
+    secret_key: &SignatureSecretKey,
+    policy: Option<&Policy>,
+    audit_log: Option<&str>,
+    mut stream: TcpStream,
+) -> Result<(), Box<dyn std::error::Error>> {
+    stream.set_read_timeout(Some(SESSION_TIMEOUT))?;
+    let index = secret_key.get_index();
+    let mut nonces = None;
+
+    while let Some(request) = read_frame::<Request, _>(&mut stream)? {
+        let response = match request {
+            Request::Info => Response::Info {
+                index,
+                threshold: share.threshold,
+                group_key: share.group_key,
+                public_share: rounds::public_share(secret_key),
+            },
+            Request::Commit => {
+                let (signing_nonces, commitment) = rounds::commit(&mut OsRng, index);
+                nonces = Some(signing_nonces);
+                Response::Commitment(commitment)
+            }
+            Request::Sign {
+                message,
+                context,
+                commitments,
+            } => match nonces.take() {
+                None => Response::Error("Sign requested before Commit".to_string()),
+                Some(signing_nonces) => {
+                    let result = check_policy(
+                        policy,
+                        index,
+                        &share.group_key,
+                        &message,
+                        &context,
+                        &commitments,
+                    )
+                    .and_then(|()| {
+                        rounds::sign_share(
+                            secret_key,
+                            &share.group_key,
+                            signing_nonces,
+                            message.as_bytes(),
+                            &context,
+                            &commitments,
+                        )
+                    });
+                    let recorded = match audit_log {
+                        Some(log_file) => audit::append(
+                            log_file,
+                            AuditRecord {
+                                operation: "partial-sign".to_string(),
+                                key_fingerprint: Some(fingerprint(&share.group_key)),
+                                message_hashes: vec![message_hash(message.as_bytes())],
+                                context: Some(context.clone()),
+                                signers: commitments.iter().map(|c| c.index).collect(),
+                                outcome: outcome(&result),
+                            },
+                        )
+                        .map(|_| ()),
+                        None => Ok(()),
+                    };
+                    match (result, recorded) {
+                        (_, Err(err)) => {
+                            Response::Error(format!("Failed to append to the audit log: {}", err))
+                        }
+                        (Ok(partial_signature), Ok(())) => {
+                            Response::PartialSignature(partial_signature)
+                        }
+                        (Err(err), Ok(())) => match err.downcast::<PolicyViolation>() {
+                            Ok(violation) => Response::PolicyViolation(*violation),
+                            Err(err) => Response::Error(err.to_string()),
+                        },
+                    }
+                }
+            },
+        };
+        write_frame(&mut stream, &response)?;
+    }
+    Ok(())
+}
+
+/// Checks a signing request against the participant's policy, if it has one.
+fn check_policy(
+    policy: Option<&Policy>,
+    index: u32,
+    group_key: &[u8; 32],
+    message: &str,
+    context: &str,
+    commitments: &[SigningCommitment],
+) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(policy) = policy {
+        let signers: Vec<u32> = commitments
+            .iter()
+            .map(|commitment| commitment.index)
+            .collect();
+        policy.evaluate(index, group_key, &signers, message.as_bytes(), context)?;
+    }
+    Ok(())
+}
+
+/// A coordinator's connection to one participant.
+pub(crate) struct Connection {
+    pub(crate) address: String,
+    stream: TcpStream,
+}
+
+impl Connection {
+    fn open(address: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let stream = TcpStream::connect(address)
+            .map_err(|err| format!("Failed to connect to participant at {}: {}", address, err))?;
+        stream.set_read_timeout(Some(SESSION_TIMEOUT))?;
+        Ok(Connection {
+            address: address.to_string(),
+            stream,
+        })
+    }
+
+    /// Connects to a participant, giving up on connecting and on every answer after `timeout`.
+    pub(crate) fn open_with_timeout(
+        address: &str,
+        timeout: Duration,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let connect = || -> io::Result<TcpStream> {
+            let mut last_err = io::Error::new(io::ErrorKind::InvalidInput, "no address");
+            for socket_address in address.to_socket_addrs()? {
+                match TcpStream::connect_timeout(&socket_address, timeout) {
+                    Ok(stream) => return Ok(stream),
+                    Err(err) => last_err = err,
+                }
+            }
+            Err(last_err)
+        };
+        let stream = connect()
+            .map_err(|err| format!("Failed to connect to participant at {}: {}", address, err))?;
+        stream.set_read_timeout(Some(timeout))?;
+        stream.set_write_timeout(Some(timeout))?;
+        Ok(Connection {
+            address: address.to_string(),
+            stream,
+        })
+    }
+
+    /// Sends a request and waits for the answer, turning `Response::Error` and
+    /// `Response::PolicyViolation` into errors.
+    pub(crate) fn exchange(
+        &mut self,
+        request: &Request,
+    ) -> Result<Response, Box<dyn std::error::Error>> {
+        write_frame(&mut self.stream, request)?;
+        match read_frame(&mut self.stream)? {
+            Some(Response::PolicyViolation(violation)) => Err(Box::new(violation)),
+            Some(Response::Error(err)) => {
+                Err(format!("Participant at {} failed: {}", self.address, err).into())
+            }
+            Some(response) => Ok(response),
+            None => Err(format!("Participant at {} closed the connection", self.address).into()),
+        }
+    }
+
+    pub(crate) fn unexpected(&self, response: Response) -> Box<dyn std::error::Error> {
+        format!(
+            "Unexpected response from participant at {}: {:?}",
+            self.address, response
+        )
+        .into()
+    }
+}
+
+/// Progress of a signing run, reported by [`request_signature_with`].
+///
+/// Participants are named by the address the coordinator reaches them at.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SigningEvent {
+    /// The participant answered and holds the share of participant `index`.
+    Joined { address: String, index: u32 },
+    /// The participant sent its commitment.
+    Committed { address: String },
+    /// A signing session started with the participants of these indices.
+    SessionStarted { signers: Vec<u32> },
+    /// The participant sent its partial signature.
+    PartialSigned { address: String },
+    /// The participant failed or misbehaved; the run stops.
+    Faulted { address: String, reason: String },
+    /// Every partial signature checked out and the signature is complete.
+    Signed([u8; 64]),
+}
+
+/// Signs a message by driving the signing rounds with remote participants.
+///
+/// # Arguments
+/// - `message`: The message to be signed.
+/// - `context`: The context the message is signed under, bound into the signature and checked by the participants' policies.
+/// - `public_file`: Path to the group's public package, which the participants are checked against.
+/// - `participants`: Addresses of the participant daemons taking part.
+/// - `signature_file`: Path to save the generated signature.
+/// - `signers`: Receives the index of every participant that joins, for the audit log.
+///
+/// # Errors
+/// Returns an error if a participant is unreachable or misbehaves, if a participant
+/// does not hold a share of the group in the public package, or if there are fewer
+/// than the threshold. A participant refusing under its policy is reported as a
+/// [`PolicyViolation`].
+pub fn coordinate(
+    message: &str,
+    context: &str,
+    public_file: &str,
+    participants: &[String],
+    signature_file: &str,
+    signers: &mut Vec<u32>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let public = load_public_package(public_file)?;
+    let signature = request_signature_with(
+        message,
+        context,
+        &public,
+        participants,
+        &mut |event| match event {
+            SigningEvent::Joined { address, index } => {
+                println!("Participant {} joined from {}", index, address);
+                signers.push(index);
+            }
+            SigningEvent::Faulted { address, reason } => {
+                eprintln!("Participant at {} faulted: {}", address, reason)
+            }
+            SigningEvent::SessionStarted { signers } => {
+                println!("Signing with participants {:?}", signers)
+            }
+            _ => {}
+        },
+    )?;
+
+    files::write_json(signature_file, &signature.to_vec())?;
+
+    println!("Threshold signature saved to: {}", signature_file);
+    Ok(())
+}
+
+/// Runs the signing rounds with remote participants and returns the threshold signature.
+pub fn request_signature(
+    message: &str,
+    context: &str,
+    public: &FrostPublicPackage,
+    participants: &[String],
+) -> Result<[u8; 64], Box<dyn std::error::Error>> {
+    request_signature_with(message, context, public, participants, &mut |_| {})
+}
+
+/// Reports a participant as faulted and passes its error on.
+fn fault(
+    report: &mut dyn FnMut(SigningEvent),
+    address: &str,
+    err: Box<dyn std::error::Error>,
+) -> Box<dyn std::error::Error> {
+    report(SigningEvent::Faulted {
+        address: address.to_string(),
+        reason: err.to_string(),
+    });
+    err
+}
+
+/// Checks a participant's `Info` answer against the group's public package.
+///
+/// # Errors
+/// Returns an error if the participant reports another group key or threshold,
+/// an index missing from the package, or a public key share other than the one
+/// the package lists for its index.
+pub(crate) fn check_info(
+    public: &FrostPublicPackage,
+    address: &str,
+    response: &Response,
+) -> Result<u32, Box<dyn std::error::Error>> {
+    let Response::Info {
+        index,
+        threshold,
+        group_key,
+        public_share,
+    } = response
+    else {
+        return Err(format!(
+            "Unexpected response from participant at {}: {:?}",
+            address, response
+        )
+        .into());
+    };
+    if *group_key != public.group_key {
+        return Err(format!(
+            "Participant at {} holds a share of a different group key",
+            address
+        )
+        .into());
+    }
+    if *threshold != public.threshold {
+        return Err(format!(
+            "Participant at {} reports threshold {} instead of {}",
+            address, threshold, public.threshold
+        )
+        .into());
+    }
+    match public.public_shares.iter().find(|(_, i)| i == index) {
+        None => Err(format!(
+            "Participant at {} claims index {}, which is not in the public package",
+            address, index
+        )
+        .into()),
+        Some((expected, _)) if expected != public_share => Err(format!(
+            "Participant {} at {} reports a public key share that does not match the public package",
+            index, address
+        )
+        .into()),
+        Some(_) => Ok(*index),
+    }
+}
+
+/// Returns the public key share of every participant in a public package, by index.
+pub(crate) fn public_shares_of(public: &FrostPublicPackage) -> BTreeMap<u32, [u8; 32]> {
+    public
+        .public_shares
+        .iter()
+        .map(|&(public_share, index)| (index, public_share))
+        .collect()
+}
+
+/// Runs the signing rounds like [`request_signature`], reporting every step to `report`.
+pub fn request_signature_with(
+    message: &str,
+    context: &str,
+    public: &FrostPublicPackage,
+    participants: &[String],
+    report: &mut dyn FnMut(SigningEvent),
+) -> Result<[u8; 64], Box<dyn std::error::Error>> {
+    // Step 1: Connect and check that each participant holds a share of the group
+    let mut connections = Vec::new();
+    let mut indices = Vec::new();
+    let mut addresses = BTreeMap::new();
+    for address in participants {
+        let mut connection =
+            Connection::open(address).map_err(|err| fault(report, address, err))?;
+        let response = connection
+            .exchange(&Request::Info)
+            .map_err(|err| fault(report, address, err))?;
+        let index =
+            check_info(public, address, &response).map_err(|err| fault(report, address, err))?;
+        if addresses.insert(index, address.clone()).is_some() {
+            return Err(fault(
+                report,
+                address,
+                format!("Participant {} joined more than once", index).into(),
+            ));
+        }
+        report(SigningEvent::Joined {
+            address: address.clone(),
+            index,
+        });
+        connections.push(connection);
+        indices.push(index);
+    }
+    if connections.is_empty() {
+        return Err("No participants given".into());
+    }
+    if connections.len() < public.threshold as usize {
+        return Err("Number of signers is less than the threshold".into());
+    }
+
+    // Step 2: Collect a commitment from every participant, under the index it joined with
+    let mut commitments = Vec::new();
+    for (connection, &index) in connections.iter_mut().zip(&indices) {
+        match connection.exchange(&Request::Commit) {
+            Ok(Response::Commitment(commitment)) if commitment.index != index => {
+                return Err(fault(
+                    report,
+                    &connection.address,
+                    format!(
+                        "Participant {} sent a commitment for participant {}",
+                        index, commitment.index
+                    )
+                    .into(),
+                ))
+            }
+            Ok(Response::Commitment(commitment)) => commitments.push(commitment),
+            Ok(other) => {
+                return Err(fault(
+                    report,
+                    &connection.address,
+                    connection.unexpected(other),
+                ))
+            }
+            Err(err) => return Err(fault(report, &connection.address, err)),
+        }
+        report(SigningEvent::Committed {
+            address: connection.address.clone(),
+        });
+    }
+
+    report(SigningEvent::SessionStarted {
+        signers: commitments
+            .iter()
+            .map(|commitment| commitment.index)
+            .collect(),
+    });
+
+    // Step 3: Collect a partial signature from every participant
+    let request = Request::Sign {
+        message: message.to_string(),
+        context: context.to_string(),
+        commitments: commitments.clone(),
+    };
+    let mut partial_signatures = Vec::new();
+    for connection in &mut connections {
+        match connection.exchange(&request) {
+            Ok(Response::PartialSignature(partial_signature)) => {
+                partial_signatures.push(partial_signature)
+            }
+            Ok(other) => {
+                return Err(fault(
+                    report,
+                    &connection.address,
+                    connection.unexpected(other),
+                ))
+            }
+            Err(err) => return Err(fault(report, &connection.address, err)),
+        }
+        report(SigningEvent::PartialSigned {
+            address: connection.address.clone(),
+        });
+    }
+
+    // Step 4: Check the partial signatures against the public package and aggregate them
+    let signature = rounds::aggregate(
+        &public.group_key,
+        message.as_bytes(),
+        context,
+        &commitments,
+        &partial_signatures,
+        &public_shares_of(public),
+    )
+    .map_err(|err| match err.downcast_ref::<InvalidShare>() {
+        Some(invalid) => match addresses.get(&invalid.participant) {
+            Some(address) => fault(report, address, err),
+            None => err,
+        },
+        None => err,
+    })?;
+    verify_signature_with_context(&public.group_key, message.as_bytes(), context, &signature)
+        .map_err(|_| "Aggregated signature does not verify under the group key")?;
+    report(SigningEvent::Signed(signature));
+    Ok(signature)
+}
@@ -0,0 +1,201 @@
+//! Signing policies: restrictions each participant checks before signing.
+//!
+//! A policy is attached to a key set by its group key and can restrict:
+//!
+//! - the combinations of signers allowed to sign together;
+//! - the messages, by regular expressions one of which must match the whole message;
+//! - the message schema, by fields every message must have as a JSON object;
+//! - the message size;
+//! - the signing contexts.
+//!
+//! The context is bound into the signature, so a signature allowed under one
+//! context does not verify under another.
+//!
+//! Every participant evaluates the policy before producing its partial signature.
+//! A request the policy does not allow is refused with a [`PolicyViolation`], so
+//! that callers can tell it apart from other signing failures.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::BufReader;
+
+/// The context signed under when none is given.
+pub const DEFAULT_CONTEXT: &str = "default";
+
+/// The restrictions of a policy. A restriction that is not set allows everything.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Policy {
+    /// The group key of the key set the policy is attached to.
+    pub group_key: Vec<u8>,
+    /// Signer sets allowed to sign together, by participant index (1 to n).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowed_signer_sets: Option<Vec<Vec<u32>>>,
+    /// Regular expressions, one of which must match the whole message.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message_patterns: Option<Vec<String>>,
+    /// Fields every message must have as a JSON object.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub required_json_fields: Option<Vec<String>>,
+    /// Largest message allowed, in bytes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_message_size: Option<usize>,
+    /// Contexts allowed to sign under.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowed_contexts: Option<Vec<String>>,
+}
+
+/// A signing request refused by a participant's policy.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PolicyViolation {
+    /// The participant that refused to sign.
+    pub participant: u32,
+    /// Why the request is not allowed.
+    pub reason: String,
+}
+
+impl fmt::Display for PolicyViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Policy violation for participant {}: {}",
+            self.participant, self.reason
+        )
+    }
+}
+
+impl Error for PolicyViolation {}
+
+impl Policy {
+    /// Checks that the policy is well-formed: every pattern compiles and every signer set is non-empty.
+    pub fn validate(&self) -> Result<(), Box<dyn Error>> {
+        for pattern in self.message_patterns.iter().flatten() {
+            Regex::new(&anchored(pattern))
+                .map_err(|err| format!("Invalid message pattern {:?}: {}", pattern, err))?;
+        }
+        if let Some(sets) = &self.allowed_signer_sets {
+            if sets.iter().any(Vec::is_empty) {
+                return Err("Allowed signer sets must not be empty".into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Evaluates a signing request on behalf of `participant`.
+    ///
+    /// # Arguments
+    /// - `participant`: The index of the participant about to sign.
+    /// - `group_key`: The group key the request signs for.
+    /// - `signers`: The participant indices (1 to n) signing together.
+    /// - `message`: The message to sign.
+    /// - `context`: The context the message is signed under.
+    pub fn evaluate(
+        &self,
+        participant: u32,
+        group_key: &[u8],
+        signers: &[u32],
+        message: &[u8],
+        context: &str,
+    ) -> Result<(), PolicyViolation> {
+        let violation = |reason: String| PolicyViolation {
+            participant,
+            reason,
+        };
+
+        if group_key != self.group_key.as_slice() {
+            return Err(violation(
+                "the policy is attached to a different group key".to_string(),
+            ));
+        }
+        if let Some(sets) = &self.allowed_signer_sets {
+            let signers: BTreeSet<u32> = signers.iter().copied().collect();
+            if !sets
+                .iter()
+                .any(|set| set.iter().copied().collect::<BTreeSet<u32>>() == signers)
+            {
+                return Err(violation(format!(
+                    "signers {:?} are not an allowed combination",
+                    signers
+                )));
+            }
+        }
+        if let Some(max_message_size) = self.max_message_size {
+            if message.len() > max_message_size {
+                return Err(violation(format!(
+                    "the message has {} bytes, more than the allowed {}",
+                    message.len(),
+                    max_message_size
+                )));
+            }
+        }
+        if let Some(contexts) = &self.allowed_contexts {
+            if !contexts.iter().any(|allowed| allowed == context) {
+                return Err(violation(format!("context {:?} is not allowed", context)));
+            }
+        }
+        if self.message_patterns.is_some() || self.required_json_fields.is_some() {
+            let text = std::str::from_utf8(message)
+                .map_err(|_| violation("the message is not valid UTF-8".to_string()))?;
+            self.evaluate_text(text).map_err(violation)?;
+        }
+        Ok(())
+    }
+
+    /// Checks the text of a message against the patterns and the schema.
+    fn evaluate_text(&self, text: &str) -> Result<(), String> {
+        if let Some(patterns) = &self.message_patterns {
+            let matches = patterns.iter().any(|pattern| {
+                Regex::new(&anchored(pattern)).is_ok_and(|regex| regex.is_match(text))
+            });
+            if !matches {
+                return Err("the message matches none of the allowed patterns".to_string());
+            }
+        }
+        if let Some(fields) = &self.required_json_fields {
+            let value: Value = serde_json::from_str(text)
+                .map_err(|_| "the message is not a JSON document".to_string())?;
+            let object = value
+                .as_object()
+                .ok_or_else(|| "the message is not a JSON object".to_string())?;
+            if let Some(missing) = fields.iter().find(|field| !object.contains_key(*field)) {
+                return Err(format!("the message has no field {:?}", missing));
+            }
+        }
+        Ok(())
+    }
+
+    /// Evaluates a signing request on behalf of every signer in `signers`.
+    ///
+    /// # Errors
+    /// Returns the [`PolicyViolation`] of the first signer whose check fails.
+    pub fn enforce(
+        &self,
+        group_key: &[u8],
+        signers: &[u32],
+        message: &[u8],
+        context: &str,
+    ) -> Result<(), PolicyViolation> {
+        for &participant in signers {
+            self.evaluate(participant, group_key, signers, message, context)?;
+        }
+        Ok(())
+    }
+}
+
+fn anchored(pattern: &str) -> String {
+    format!("^(?:{})$", pattern)
+}
+
+/// Loads a policy from a file and checks that it is well-formed.
+pub fn load_policy(policy_file: &str) -> Result<Policy, Box<dyn Error>> {
+    let file = File::open(policy_file)?;
+    let policy: Policy = serde_json::from_reader(BufReader::new(file))?;
+    policy
+        .validate()
+        .map_err(|err| format!("Policy file {} is invalid: {}", policy_file, err))?;
+    Ok(policy)
+}
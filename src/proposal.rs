@@ -0,0 +1,409 @@
+//! Signing proposals: what is to be signed, reviewed and approved before signing.
+//!
+//! A proposal names the group key, the message (or only its hash), an
+//! application context, the requested signers, an expiry time and a note from
+//! the requester. It is created with [`propose`], passed to each requested
+//! signer, who reviews it and adds an approval with [`approve_proposal`], and
+//! finally signed with [`sign_proposal`], which refuses a proposal that has
+//! expired or that a requested signer has not approved.
+//!
+//! An approval is a Schnorr signature by the signer's share over the digest of
+//! the proposal, so it cannot be forged without the share and does not carry
+//! over to a proposal with different contents.
+
+use crate::files;
+use crate::policy::Policy;
+use crate::{load_keys, load_public_package, load_share, public_package_of, threshold_sign_with};
+use crate::{FrostPublicPackage, FrostShare};
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_TABLE;
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use rand::rngs::OsRng;
+use rand::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256, Sha512};
+use std::fmt;
+use std::fs::File;
+use std::io::BufReader;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A request to sign one message with a given set of signers.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Proposal {
+    pub id: String,
+    pub group_key: [u8; 32],
+    /// The message, unless the proposal only carries its hash.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// SHA-256 of the message, in hex.
+    pub message_hash: String,
+    pub context: String,
+    /// Participant indices (1 to n) requested to sign.
+    pub signers: Vec<u32>,
+    /// Unix time, in seconds, after which the proposal can no longer be approved or signed.
+    pub expires_at: u64,
+    pub note: String,
+    #[serde(default)]
+    pub approvals: Vec<Approval>,
+}
+
+/// A signer's approval of a proposal: a Schnorr signature over its digest.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Approval {
+    pub index: u32,
+    pub signature: Vec<u8>,
+}
+
+impl Proposal {
+    /// Creates a proposal with a random id and no approvals.
+    ///
+    /// If `hash_only` is set, the message itself is left out and must be supplied again at signing.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new<R: RngCore + CryptoRng>(
+        csprng: &mut R,
+        group_key: [u8; 32],
+        message: &str,
+        hash_only: bool,
+        context: &str,
+        signers: Vec<u32>,
+        expires_at: u64,
+        note: &str,
+    ) -> Self {
+        let mut id = [0u8; 16];
+        csprng.fill_bytes(&mut id);
+        Proposal {
+            id: hex::encode(id),
+            group_key,
+            message: (!hash_only).then(|| message.to_string()),
+            message_hash: hex::encode(Sha256::digest(message.as_bytes())),
+            context: context.to_string(),
+            signers,
+            expires_at,
+            note: note.to_string(),
+            approvals: Vec::new(),
+        }
+    }
+
+    /// Returns the digest approvals sign: every field except the approvals themselves.
+    pub fn digest(&self) -> [u8; 32] {
+        let mut h = Sha256::new();
+        h.update(b"frost-cli proposal");
+        for field in [&self.id, &self.message_hash, &self.context, &self.note] {
+            h.update((field.len() as u64).to_be_bytes());
+            h.update(field.as_bytes());
+        }
+        h.update(self.group_key);
+        h.update((self.signers.len() as u32).to_be_bytes());
+        for signer in &self.signers {
+            h.update(signer.to_be_bytes());
+        }
+        h.update(self.expires_at.to_be_bytes());
+        h.finalize().into()
+    }
+
+    /// Returns `true` if the proposal expired at time `now`.
+    pub fn is_expired(&self, now: u64) -> bool {
+        now >= self.expires_at
+    }
+
+    /// Adds the approval of `share`'s holder, replacing any earlier one.
+    ///
+    /// # Errors
+    /// Returns an error if the proposal has expired, is for another group key,
+    /// or the holder is not a requested signer.
+    pub fn approve<R: RngCore + CryptoRng>(
+        &mut self,
+        csprng: &mut R,
+        share: &FrostShare,
+        now: u64,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let (key_bytes, index) = share.private_share;
+        if self.is_expired(now) {
+            return Err(format!("Proposal {} has expired", self.id).into());
+        }
+        if share.group_key != self.group_key {
+            return Err("Share does not belong to the proposal's group key".into());
+        }
+        if !self.signers.contains(&index) {
+            return Err(format!(
+                "Participant {} is not a requested signer of proposal {}",
+                index, self.id
+            )
+            .into());
+        }
+
+        let key = Scalar::from_canonical_bytes(key_bytes).ok_or("Invalid private key bytes")?;
+        let public_share = (&key * &RISTRETTO_BASEPOINT_TABLE).compress().to_bytes();
+        let nonce = Scalar::random(csprng);
+        let commitment = (&nonce * &RISTRETTO_BASEPOINT_TABLE).compress().to_bytes();
+        let challenge = approval_challenge(&commitment, &public_share, &self.digest());
+        let response = nonce + challenge * key;
+
+        let mut signature = commitment.to_vec();
+        signature.extend_from_slice(response.as_bytes());
+        self.approvals.retain(|approval| approval.index != index);
+        self.approvals.push(Approval { index, signature });
+        Ok(())
+    }
+
+    /// Checks that the proposal can be signed at time `now`.
+    ///
+    /// # Errors
+    /// Returns an error if the proposal has expired, is for another group key, or
+    /// a requested signer has not approved it with a valid approval.
+    pub fn check_ready(
+        &self,
+        public_package: &FrostPublicPackage,
+        now: u64,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if self.is_expired(now) {
+            return Err(format!("Proposal {} has expired", self.id).into());
+        }
+        if public_package.group_key != self.group_key {
+            return Err("Proposal is for a different group key".into());
+        }
+
+        let digest = self.digest();
+        for &signer in &self.signers {
+            let approval = self
+                .approvals
+                .iter()
+                .find(|approval| approval.index == signer)
+                .ok_or_else(|| format!("Participant {} has not approved the proposal", signer))?;
+            let public_share = public_package
+                .public_shares
+                .iter()
+                .find(|(_, index)| *index == signer)
+                .map(|(share_bytes, _)| *share_bytes)
+                .ok_or_else(|| format!("No public share for participant {}", signer))?;
+            if !verify_approval(&approval.signature, &public_share, &digest) {
+                return Err(format!("Approval of participant {} is not valid", signer).into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the bytes to sign, taking the message from the proposal or from `message`.
+    ///
+    /// # Errors
+    /// Returns an error if no message is available or it does not match the proposal's hash.
+    pub fn message_bytes(
+        &self,
+        message: Option<&str>,
+    ) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let message = message
+            .or(self.message.as_deref())
+            .ok_or("The proposal only carries the message hash; supply the message")?;
+        if hex::encode(Sha256::digest(message.as_bytes())) != self.message_hash {
+            return Err("Message does not match the proposal's message hash".into());
+        }
+        Ok(message.as_bytes().to_vec())
+    }
+}
+
+impl fmt::Display for Proposal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let signers: Vec<String> = self.signers.iter().map(u32::to_string).collect();
+        let approved: Vec<String> = self
+            .approvals
+            .iter()
+            .map(|approval| approval.index.to_string())
+            .collect();
+        writeln!(f, "Proposal:     {}", self.id)?;
+        writeln!(f, "Group key:    {}", crate::fingerprint(&self.group_key))?;
+        match &self.message {
+            Some(message) => writeln!(f, "Message:      {:?}", message)?,
+            None => writeln!(f, "Message:      (hash only)")?,
+        }
+        writeln!(f, "Message hash: {}", self.message_hash)?;
+        writeln!(f, "Context:      {}", self.context)?;
+        writeln!(f, "Signers:      {}", signers.join(", "))?;
+        writeln!(f, "Expires at:   {} (Unix time)", self.expires_at)?;
+        writeln!(f, "Note:         {}", self.note)?;
+        write!(f, "Approved by:  {}", approved.join(", "))
+    }
+}
+
+fn approval_challenge(commitment: &[u8; 32], public_share: &[u8; 32], digest: &[u8; 32]) -> Scalar {
+    let mut h = Sha512::new();
+    h.update(b"frost-cli proposal approval");
+    h.update(commitment);
+    h.update(public_share);
+    h.update(digest);
+    Scalar::from_hash(h)
+}
+
+fn verify_approval(signature: &[u8], public_share: &[u8; 32], digest: &[u8; 32]) -> bool {
+    if signature.len() != 64 {
+        return false;
+    }
+    let mut commitment = [0u8; 32];
+    let mut response = [0u8; 32];
+    commitment.copy_from_slice(&signature[..32]);
+    response.copy_from_slice(&signature[32..]);
+
+    let points = (
+        CompressedRistretto(commitment).decompress(),
+        CompressedRistretto(*public_share).decompress(),
+        Scalar::from_canonical_bytes(response),
+    );
+    let (Some(r), Some(y), Some(z)) = points else {
+        return false;
+    };
+    let challenge = approval_challenge(&commitment, public_share, digest);
+    let expected: RistrettoPoint = r + challenge * y;
+    &z * &RISTRETTO_BASEPOINT_TABLE == expected
+}
+
+/// Returns the current Unix time in seconds.
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0)
+}
+
+/// Loads a proposal from a file.
+pub fn load_proposal(proposal_file: &str) -> Result<Proposal, Box<dyn std::error::Error>> {
+    let file = File::open(proposal_file)?;
+    Ok(serde_json::from_reader(BufReader::new(file))?)
+}
+
+fn save_proposal(
+    proposal: &Proposal,
+    proposal_file: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    files::write_json(proposal_file, proposal)?;
+    Ok(())
+}
+
+/// Creates a signing proposal for the group of a public package.
+///
+/// # Arguments
+/// - `public_file`: Path to the group's public package.
+/// - `message`: The message to be signed.
+/// - `hash_only`: Store only the message hash in the proposal.
+/// - `context`: The application context the message is signed for.
+/// - `signers`: Participant indices (1 to n) requested to sign.
+/// - `expires_in`: Seconds from now until the proposal expires.
+/// - `note`: A note from the requester to the signers.
+/// - `proposal_file`: Path to save the proposal.
+#[allow(clippy::too_many_arguments)]
+pub fn propose(
+    public_file: &str,
+    message: &str,
+    hash_only: bool,
+    context: &str,
+    signers: Vec<u32>,
+    expires_in: u64,
+    note: &str,
+    proposal_file: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let public_package = load_public_package(public_file)?;
+    if signers.len() < public_package.threshold as usize {
+        return Err("Number of signers is less than the threshold".into());
+    }
+    for signer in &signers {
+        if !public_package
+            .public_shares
+            .iter()
+            .any(|(_, index)| index == signer)
+        {
+            return Err(format!("Invalid signer index: {}", signer).into());
+        }
+    }
+
+    let proposal = Proposal::new(
+        &mut OsRng,
+        public_package.group_key,
+        message,
+        hash_only,
+        context,
+        signers,
+        now() + expires_in,
+        note,
+    );
+    save_proposal(&proposal, proposal_file)?;
+
+    println!("Proposal {} saved to: {}", proposal.id, proposal_file);
+    Ok(())
+}
+
+/// Adds the approval of a share's holder to a proposal file.
+pub fn approve_proposal(
+    proposal_file: &str,
+    share_file: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut proposal = load_proposal(proposal_file)?;
+    let share = load_share(share_file)?;
+    proposal.approve(&mut OsRng, &share, now())?;
+    save_proposal(&proposal, proposal_file)?;
+
+    println!(
+        "Participant {} approved proposal {}",
+        share.private_share.1, proposal.id
+    );
+    Ok(())
+}
+
+/// Signs an approved proposal with the requested signers' shares from a key file.
+///
+/// # Arguments
+/// - `proposal_file`: Path to the proposal.
+/// - `message`: The message, required if the proposal only carries its hash.
+/// - `key_file`: Path to the file containing the generated keys.
+/// - `signature_file`: Path to save the generated signature.
+/// - `policy`: The signing policy every signer checks, or `None` to sign without one.
+///
+/// # Errors
+/// Returns an error if the proposal has expired or is not approved by every
+/// requested signer, or a [`PolicyViolation`](crate::policy::PolicyViolation) if
+/// the policy does not allow it.
+pub fn sign_proposal(
+    proposal_file: &str,
+    message: Option<&str>,
+    key_file: &str,
+    signature_file: &str,
+    policy: Option<&Policy>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let proposal = load_proposal(proposal_file)?;
+    let frost_keys = load_keys(key_file)?;
+    proposal.check_ready(&public_package_of(&frost_keys)?, now())?;
+    let message = proposal.message_bytes(message)?;
+    if let Some(policy) = policy {
+        policy.enforce(
+            &frost_keys.group_key,
+            &proposal.signers,
+            &message,
+            &proposal.context,
+        )?;
+    }
+
+    // `threshold_sign_with` takes signers as positions in the key file.
+    let mut positions = Vec::new();
+    for signer in &proposal.signers {
+        let position = frost_keys
+            .private_shares
+            .iter()
+            .position(|(_, index)| index == signer)
+            .ok_or_else(|| format!("No share for participant {} in the key file", signer))?;
+        positions.push(position as u32);
+    }
+    let n = frost_keys.private_shares.len() as u32;
+    let threshold_signature = threshold_sign_with(
+        &frost_keys,
+        &message,
+        &proposal.context,
+        &positions,
+        n,
+        &mut |_| {},
+    )?;
+
+    files::write_json(signature_file, &threshold_signature.to_vec())?;
+
+    println!(
+        "Threshold signature for proposal {} saved to: {}",
+        proposal.id, signature_file
+    );
+    Ok(())
+}
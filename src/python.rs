@@ -0,0 +1,286 @@
+//! Python bindings for scripting key ceremonies.
+//!
+//! Built with the `python` feature as the extension module `frost_cli`, e.g.
+//! with `maturin develop` (see `pyproject.toml`). Every function takes and
+//! returns `bytes`: key sets, shares, public packages, commitments and partial
+//! signatures in the JSON of the CLI's files and signing messages, and group
+//! keys and signatures as raw bytes, so files written by the CLI can be read
+//! with `open(path, "rb").read()` and passed straight in.
+//!
+//! Errors raise subclasses of `frost_cli.FrostError`:
+//!
+//! - `InvalidArgumentError`: an argument is out of range, e.g. an unknown participant.
+//! - `MalformedInputError`: an input does not decode or fails its integrity checks.
+//! - `SigningError`: signing or aggregation failed, e.g. on an invalid partial signature.
+//! - `InvalidSignatureError`: a signature does not verify.
+//! - `NoncesUsedError`: signing nonces were used a second time.
+
+use crate::policy::DEFAULT_CONTEXT;
+use crate::rounds::{self, PartialSignature, SigningCommitment};
+use crate::{
+    generate_key_set_with, integrity, public_package_of, share_of, threshold_sign,
+    verify_signature, FrostKeys, FrostPublicPackage, FrostShare,
+};
+use frost_dalek::signature::SecretKey as SignatureSecretKey;
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use rand::rngs::OsRng;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use zeroize::Zeroize;
+
+create_exception!(
+    frost_cli,
+    FrostError,
+    PyException,
+    "Base class of all frost_cli errors."
+);
+create_exception!(
+    frost_cli,
+    InvalidArgumentError,
+    FrostError,
+    "An argument is out of range."
+);
+create_exception!(
+    frost_cli,
+    MalformedInputError,
+    FrostError,
+    "An input does not decode or fails its integrity checks."
+);
+create_exception!(
+    frost_cli,
+    SigningError,
+    FrostError,
+    "Signing or aggregation failed."
+);
+create_exception!(
+    frost_cli,
+    InvalidSignatureError,
+    FrostError,
+    "A signature does not verify."
+);
+create_exception!(
+    frost_cli,
+    NoncesUsedError,
+    FrostError,
+    "Signing nonces were used a second time."
+);
+
+fn malformed(err: impl std::fmt::Display) -> PyErr {
+    MalformedInputError::new_err(err.to_string())
+}
+
+fn json<T: DeserializeOwned>(input: &[u8]) -> PyResult<T> {
+    serde_json::from_slice(input).map_err(malformed)
+}
+
+fn json_bytes<T: Serialize>(py: Python<'_>, value: &T) -> PyResult<Py<PyBytes>> {
+    let bytes = serde_json::to_vec_pretty(value).map_err(malformed)?;
+    Ok(PyBytes::new(py, &bytes).unbind())
+}
+
+fn parse_keys(keys: &[u8]) -> PyResult<FrostKeys> {
+    let frost_keys: FrostKeys = json(keys)?;
+    integrity::check_key_set(&frost_keys).map_err(malformed)?;
+    Ok(frost_keys)
+}
+
+fn parse_share(share: &[u8]) -> PyResult<FrostShare> {
+    let share: FrostShare = json(share)?;
+    integrity::check_share(&share).map_err(malformed)?;
+    Ok(share)
+}
+
+fn parse_all<T: DeserializeOwned>(items: Vec<Vec<u8>>) -> PyResult<Vec<T>> {
+    items.iter().map(|item| json(item)).collect()
+}
+
+/// The secret nonces of one signer for one signature, returned by `commit`.
+#[pyclass(module = "frost_cli")]
+pub struct Nonces(Option<rounds::SigningNonces>);
+
+#[pymethods]
+impl Nonces {
+    /// True once the nonces have signed and can no longer be used.
+    #[getter]
+    fn used(&self) -> bool {
+        self.0.is_none()
+    }
+}
+
+/// Generates a key set of `n` shares with threshold `t` and returns it as a key file.
+#[pyfunction]
+fn generate(py: Python<'_>, t: u32, n: u32) -> PyResult<Py<PyBytes>> {
+    if t == 0 {
+        return Err(InvalidArgumentError::new_err(
+            "Threshold must be at least 1",
+        ));
+    }
+    let frost_keys = py
+        .allow_threads(|| generate_key_set_with(t, n, &mut |_| {}).map_err(|err| err.to_string()))
+        .map_err(InvalidArgumentError::new_err)?;
+    json_bytes(py, &frost_keys)
+}
+
+/// Returns the 32-byte group public key of a key file, share or public package.
+#[pyfunction]
+fn group_key(py: Python<'_>, file: &[u8]) -> PyResult<Py<PyBytes>> {
+    #[derive(serde::Deserialize)]
+    struct GroupKeyOnly {
+        group_key: [u8; 32],
+    }
+    let file: GroupKeyOnly = json(file)?;
+    Ok(PyBytes::new(py, &file.group_key).unbind())
+}
+
+/// Returns the share file of participant `index` (1 to n) of a key file.
+#[pyfunction]
+fn share(py: Python<'_>, keys: &[u8], index: u32) -> PyResult<Py<PyBytes>> {
+    let share = share_of(&parse_keys(keys)?, index)
+        .map_err(|err| InvalidArgumentError::new_err(err.to_string()))?;
+    json_bytes(py, &share)
+}
+
+/// Returns the public package of a key file.
+#[pyfunction]
+fn public_package(py: Python<'_>, keys: &[u8]) -> PyResult<Py<PyBytes>> {
+    let public_package = public_package_of(&parse_keys(keys)?).map_err(malformed)?;
+    json_bytes(py, &public_package)
+}
+
+/// Signs `message` with the given signers of a key file and returns the 64-byte signature.
+///
+/// `signers` are positions in the key file (0 to n - 1), as taken by the CLI's `--signers`.
+#[pyfunction]
+fn sign(py: Python<'_>, keys: &[u8], message: &[u8], signers: Vec<u32>) -> PyResult<Py<PyBytes>> {
+    let frost_keys = parse_keys(keys)?;
+    let n = frost_keys.private_shares.len() as u32;
+    let signature = py
+        .allow_threads(|| {
+            threshold_sign(&frost_keys, message, &signers, n).map_err(|err| err.to_string())
+        })
+        .map_err(SigningError::new_err)?;
+    Ok(PyBytes::new(py, &signature).unbind())
+}
+
+/// First signing round: returns fresh `Nonces` for the holder of `share` and the commitment to send.
+#[pyfunction]
+fn commit(py: Python<'_>, share: &[u8]) -> PyResult<(Nonces, Py<PyBytes>)> {
+    let share = parse_share(share)?;
+    let (nonces, commitment) = rounds::commit(&mut OsRng, share.private_share.1);
+    Ok((Nonces(Some(nonces)), json_bytes(py, &commitment)?))
+}
+
+/// Second signing round: returns the partial signature of the holder of `share`.
+///
+/// `commitments` holds the commitment of every signer, including this one. The
+/// nonces are used up, even if signing fails.
+#[pyfunction]
+fn sign_share(
+    py: Python<'_>,
+    share: &[u8],
+    nonces: &mut Nonces,
+    message: &[u8],
+    commitments: Vec<Vec<u8>>,
+) -> PyResult<Py<PyBytes>> {
+    let signing_nonces = nonces
+        .0
+        .take()
+        .ok_or_else(|| NoncesUsedError::new_err("These nonces have already been used to sign"))?;
+    let share = parse_share(share)?;
+    let commitments: Vec<SigningCommitment> = parse_all(commitments)?;
+
+    let (mut key_bytes, index) = share.private_share;
+    let secret_key = SignatureSecretKey::from_bytes(index, key_bytes);
+    key_bytes.zeroize();
+    let secret_key = secret_key.map_err(|_| malformed("Invalid private key bytes"))?;
+
+    let partial = rounds::sign_share(
+        &secret_key,
+        &share.group_key,
+        signing_nonces,
+        message,
+        DEFAULT_CONTEXT,
+        &commitments,
+    )
+    .map_err(|err| SigningError::new_err(err.to_string()))?;
+    json_bytes(py, &partial)
+}
+
+/// Checks every partial signature and combines them into the 64-byte signature.
+#[pyfunction]
+fn aggregate(
+    py: Python<'_>,
+    public_package: &[u8],
+    message: &[u8],
+    commitments: Vec<Vec<u8>>,
+    partial_signatures: Vec<Vec<u8>>,
+) -> PyResult<Py<PyBytes>> {
+    let public_package: FrostPublicPackage = json(public_package)?;
+    integrity::check_public_package(&public_package).map_err(malformed)?;
+    let commitments: Vec<SigningCommitment> = parse_all(commitments)?;
+    let partials: Vec<PartialSignature> = parse_all(partial_signatures)?;
+    let public_shares: BTreeMap<u32, [u8; 32]> = public_package
+        .public_shares
+        .iter()
+        .map(|&(share, index)| (index, share))
+        .collect();
+
+    let signature = rounds::aggregate(
+        &public_package.group_key,
+        message,
+        DEFAULT_CONTEXT,
+        &commitments,
+        &partials,
+        &public_shares,
+    )
+    .map_err(|err| SigningError::new_err(err.to_string()))?;
+    Ok(PyBytes::new(py, &signature).unbind())
+}
+
+/// Verifies a 64-byte signature against a 32-byte group public key.
+///
+/// Raises `InvalidSignatureError` if the signature does not verify.
+#[pyfunction]
+fn verify(group_key: &[u8], message: &[u8], signature: &[u8]) -> PyResult<()> {
+    let group_key: &[u8; 32] = group_key
+        .try_into()
+        .map_err(|_| MalformedInputError::new_err("A group key has 32 bytes"))?;
+    let signature: &[u8; 64] = signature
+        .try_into()
+        .map_err(|_| MalformedInputError::new_err("A signature has 64 bytes"))?;
+    verify_signature(group_key, message, signature)
+        .map_err(|err| InvalidSignatureError::new_err(err.to_string()))
+}
+
+/// FROST threshold signatures.
+#[pymodule]
+fn frost_cli(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = module.py();
+    module.add("FrostError", py.get_type::<FrostError>())?;
+    module.add(
+        "InvalidArgumentError",
+        py.get_type::<InvalidArgumentError>(),
+    )?;
+    module.add("MalformedInputError", py.get_type::<MalformedInputError>())?;
+    module.add("SigningError", py.get_type::<SigningError>())?;
+    module.add(
+        "InvalidSignatureError",
+        py.get_type::<InvalidSignatureError>(),
+    )?;
+    module.add("NoncesUsedError", py.get_type::<NoncesUsedError>())?;
+    module.add_class::<Nonces>()?;
+    module.add_function(wrap_pyfunction!(generate, module)?)?;
+    module.add_function(wrap_pyfunction!(group_key, module)?)?;
+    module.add_function(wrap_pyfunction!(share, module)?)?;
+    module.add_function(wrap_pyfunction!(public_package, module)?)?;
+    module.add_function(wrap_pyfunction!(sign, module)?)?;
+    module.add_function(wrap_pyfunction!(commit, module)?)?;
+    module.add_function(wrap_pyfunction!(sign_share, module)?)?;
+    module.add_function(wrap_pyfunction!(aggregate, module)?)?;
+    module.add_function(wrap_pyfunction!(verify, module)?)?;
+    Ok(())
+}
@@ -0,0 +1,250 @@
+//! Proactive refresh of shares without changing the group key.
+//!
+//! Every holder deals a random polynomial whose constant term is zero. Adding the
+//! sub-shares dealt by all holders to each share moves the shares onto a new
+//! polynomial with the same constant term: the group key is unchanged, but shares
+//! from before the refresh no longer combine with shares from after it, so a share
+//! that leaked earlier becomes useless once every holder has refreshed.
+//!
+//! The refresh runs in-process over a key file with [`refresh_keys`], or as two
+//! distributed rounds over share files:
+//!
+//! 1. Every holder runs [`refresh_deal`] and privately delivers each package to its recipient.
+//! 2. Every holder runs [`refresh_finish`] with the packages received from all n holders.
+//!
+//! A dealer could send packages for different polynomials to different holders,
+//! leaving shares that no longer combine. [`refresh_finish`] therefore prints the
+//! [`commitments_digest`] of the packages it received; the holders compare their
+//! digests, and a holder given the digest of another refuses to finish if its own
+//! differs.
+
+use crate::files;
+use crate::vss::{
+    decode_points, encode_points, load_packages, open_package, Polynomial, SharePackage,
+};
+use crate::{integrity, keys_from_shares, load_keys, load_share, share_of, FrostShare};
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::Identity;
+use rand::rngs::OsRng;
+use rand::{CryptoRng, RngCore};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+/// Deals a zero-secret polynomial from `share`'s holder to every participant.
+pub fn deal_refresh<R: RngCore + CryptoRng>(
+    csprng: &mut R,
+    share: &FrostShare,
+) -> Vec<SharePackage> {
+    let polynomial = Polynomial::random(csprng, Scalar::zero(), share.threshold);
+    polynomial.deal(share.private_share.1, 1..=share.n)
+}
+
+/// Returns the hash of every dealer's commitments in `packages`, in hex.
+///
+/// Every holder receives the same commitments from each dealer, so all holders
+/// of an honest refresh, or new participants of an honest reshare, compute the
+/// same digest.
+pub fn commitments_digest(packages: &[SharePackage]) -> String {
+    let mut sorted: Vec<&SharePackage> = packages.iter().collect();
+    sorted.sort_by_key(|package| package.from);
+    let mut h = Sha256::new();
+    h.update(b"frost-cli refresh commitments");
+    for package in sorted {
+        h.update(package.from.to_be_bytes());
+        h.update((package.commitments.len() as u32).to_be_bytes());
+        for commitment in &package.commitments {
+            h.update(commitment);
+        }
+    }
+    hex::encode(h.finalize())
+}
+
+/// Applies the refresh packages received from all n holders to `share`.
+///
+/// # Errors
+/// Returns an error if a holder's package is missing, duplicated, does not deal
+/// a zero secret, or does not match its commitments.
+pub fn apply_refresh(
+    share: &FrostShare,
+    packages: &[SharePackage],
+) -> Result<FrostShare, Box<dyn std::error::Error>> {
+    let (key_bytes, index) = share.private_share;
+    let mut key = Scalar::from_canonical_bytes(key_bytes).ok_or("Invalid private key bytes")?;
+    let mut commitments =
+        decode_points(&share.commitments).ok_or("Share has a malformed commitment")?;
+
+    let mut dealers = BTreeSet::new();
+    for package in packages {
+        if !dealers.insert(package.from) {
+            return Err(format!("Two packages from participant {}", package.from).into());
+        }
+        let (sub_share, package_commitments) = open_package(package, index, share.threshold)?;
+        if package_commitments[0] != RistrettoPoint::identity() {
+            return Err(format!(
+                "Package from participant {} does not deal a zero secret",
+                package.from
+            )
+            .into());
+        }
+
+        key += sub_share;
+        for (commitment, package_commitment) in commitments.iter_mut().zip(&package_commitments) {
+            *commitment += package_commitment;
+        }
+    }
+    if dealers != (1..=share.n).collect() {
+        return Err(format!(
+            "Refresh needs a package from each of the {} participants, got {}",
+            share.n,
+            dealers.len()
+        )
+        .into());
+    }
+
+    let mut refreshed = FrostShare {
+        group_key: share.group_key,
+        private_share: (key.to_bytes(), index),
+        threshold: share.threshold,
+        n: share.n,
+        commitments: encode_points(&commitments),
+        checksum: None,
+    };
+    refreshed.checksum = Some(integrity::share_checksum(&refreshed));
+    integrity::check_share(&refreshed)?;
+    Ok(refreshed)
+}
+
+/// Refreshes every share of a key file in-process, keeping the group key.
+///
+/// # Arguments
+/// - `key_file`: Path to the file containing the current keys.
+/// - `output_key_file`: Path to save the refreshed keys.
+/// - `force`: Replace `output_key_file` if it exists.
+pub fn refresh_keys(
+    key_file: &str,
+    output_key_file: &str,
+    force: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    files::check_overwrite(output_key_file, force)?;
+    let frost_keys = load_keys(key_file)?;
+    let shares = frost_keys
+        .private_shares
+        .iter()
+        .map(|(_, index)| share_of(&frost_keys, *index))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Round 1: every holder deals a zero-secret polynomial.
+    let packages: Vec<SharePackage> = shares
+        .iter()
+        .flat_map(|share| deal_refresh(&mut OsRng, share))
+        .collect();
+
+    // Round 2: every holder adds the sub-shares addressed to it, and all holders
+    // must have received the same commitments.
+    let mut refreshed = Vec::new();
+    let mut digests = BTreeSet::new();
+    for share in &shares {
+        let index = share.private_share.1;
+        let received: Vec<SharePackage> = packages
+            .iter()
+            .filter(|package| package.to == index)
+            .cloned()
+            .collect();
+        digests.insert(commitments_digest(&received));
+        refreshed.push(apply_refresh(share, &received)?);
+    }
+    if digests.len() > 1 {
+        return Err("Holders received different commitments from the same dealer".into());
+    }
+
+    let refreshed_keys = keys_from_shares(&refreshed)?;
+    files::write_secret_json(output_key_file, &refreshed_keys, force)?;
+
+    println!(
+        "Refreshed {} shares for the same group key. Keys saved to: {}",
+        refreshed.len(),
+        output_key_file
+    );
+    Ok(())
+}
+
+/// First distributed round: deals this holder's refresh packages into `output_dir`.
+///
+/// One file `refresh_<from>_to_<to>.json` is written per participant, and must be
+/// delivered privately to participant `<to>`. Existing package files are only
+/// replaced if `force` is set.
+pub fn refresh_deal(
+    share_file: &str,
+    output_dir: &str,
+    force: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let share = load_share(share_file)?;
+    let packages = deal_refresh(&mut OsRng, &share);
+    let paths: Vec<PathBuf> = packages
+        .iter()
+        .map(|package| {
+            Path::new(output_dir).join(format!("refresh_{}_to_{}.json", package.from, package.to))
+        })
+        .collect();
+    for path in &paths {
+        files::check_overwrite(path, force)?;
+    }
+    for (package, path) in packages.iter().zip(&paths) {
+        files::write_secret_json(path, package, force)?;
+    }
+
+    println!(
+        "Refresh packages of participant {} saved to: {}",
+        share.private_share.1, output_dir
+    );
+    Ok(())
+}
+
+/// Second distributed round: applies the received packages and saves the refreshed share.
+///
+/// # Arguments
+/// - `share_file`: Path to the holder's current share.
+/// - `package_files`: Paths to the packages addressed to this holder, one from every participant.
+/// - `expected_digest`: The [`commitments_digest`] announced by another holder, if any.
+/// - `output_share_file`: Path to save the refreshed share.
+/// - `force`: Replace `output_share_file` if it exists.
+///
+/// # Errors
+/// Returns an error if the packages are invalid or their digest differs from `expected_digest`.
+pub fn refresh_finish(
+    share_file: &str,
+    package_files: &[String],
+    expected_digest: Option<&str>,
+    output_share_file: &str,
+    force: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    files::check_overwrite(output_share_file, force)?;
+    let share = load_share(share_file)?;
+    let packages = load_packages(package_files)?;
+    let digest = commitments_digest(&packages);
+    if let Some(expected) = expected_digest {
+        if !expected.eq_ignore_ascii_case(&digest) {
+            return Err(format!(
+                "Commitments digest {} differs from the expected {}: a dealer sent \
+                 different commitments to different holders",
+                digest, expected
+            )
+            .into());
+        }
+    }
+    let refreshed = apply_refresh(&share, &packages)?;
+
+    files::write_secret_json(output_share_file, &refreshed, force)?;
+
+    println!(
+        "Refreshed share of participant {} saved to: {}",
+        refreshed.private_share.1, output_share_file
+    );
+    println!(
+        "Commitments digest: {}\nCompare it with every other holder before using the refreshed share.",
+        digest
+    );
+    Ok(())
+}
@@ -0,0 +1,363 @@
+//! Repair of a lost share with the help of t other holders.
+//!
+//! The helpers recompute the share of the lost participant `r` for the same
+//! index, without any of them learning it:
+//!
+//! 1. Every helper `i` weights its share by its Lagrange coefficient at `r` over
+//!    the helpers, splits the result into one random part per helper with
+//!    [`deal_repair`], and privately delivers each part to its helper.
+//! 2. Every helper adds the parts it received with [`combine_repair`] and sends
+//!    the sum to the recovering participant. Each sum is uniformly random on its
+//!    own, so a helper learns nothing about the lost share.
+//! 3. The recovering participant adds the sums with [`complete_repair`] and checks
+//!    the result against its public share in the public package.
+
+use crate::files;
+use crate::rounds::lagrange_coefficient_at;
+use crate::{integrity, load_public_package, load_share, FrostPublicPackage, FrostShare};
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_TABLE;
+use curve25519_dalek::scalar::Scalar;
+use rand::rngs::OsRng;
+use rand::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use zeroize::Zeroize;
+
+/// A random part of helper `from`'s weighted share, sent to helper `to`.
+///
+/// The `part` is secret and must only be delivered to helper `to`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RepairPart {
+    pub from: u32,
+    pub to: u32,
+    pub lost: u32,
+    pub helpers: Vec<u32>,
+    pub part: [u8; 32],
+}
+
+impl Drop for RepairPart {
+    fn drop(&mut self) {
+        self.part.zeroize();
+    }
+}
+
+/// The sum of the parts received by helper `from`, sent to the recovering participant.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RepairSum {
+    pub from: u32,
+    pub lost: u32,
+    pub helpers: Vec<u32>,
+    pub sum: [u8; 32],
+}
+
+impl Drop for RepairSum {
+    fn drop(&mut self) {
+        self.sum.zeroize();
+    }
+}
+
+/// Splits `share`'s contribution to the share of participant `lost` into one random part per helper.
+///
+/// # Errors
+/// Returns an error if `helpers` does not include this share's holder or `lost`
+/// is among the helpers, or there are fewer than t helpers.
+pub fn deal_repair<R: RngCore + CryptoRng>(
+    csprng: &mut R,
+    share: &FrostShare,
+    helpers: &[u32],
+    lost: u32,
+) -> Result<Vec<RepairPart>, Box<dyn std::error::Error>> {
+    check_helpers(helpers, lost, share.threshold, share.n)?;
+    split_share(csprng, share, helpers, lost)
+}
+
+/// Splits `share`'s Lagrange-weighted value at `target` into one random part per helper.
+pub(crate) fn split_share<R: RngCore + CryptoRng>(
+    csprng: &mut R,
+    share: &FrostShare,
+    helpers: &[u32],
+    target: u32,
+) -> Result<Vec<RepairPart>, Box<dyn std::error::Error>> {
+    let (key_bytes, index) = share.private_share;
+    if !helpers.contains(&index) {
+        return Err(format!("Participant {} is not among the helpers", index).into());
+    }
+
+    let key = Scalar::from_canonical_bytes(key_bytes).ok_or("Invalid private key bytes")?;
+    let weighted = lagrange_coefficient_at(target, index, helpers) * key;
+
+    // Random parts for every helper but the last, which gets the remainder.
+    let mut parts: Vec<Scalar> = (1..helpers.len()).map(|_| Scalar::random(csprng)).collect();
+    let remainder = weighted - parts.iter().sum::<Scalar>();
+    parts.push(remainder);
+
+    Ok(helpers
+        .iter()
+        .zip(parts)
+        .map(|(&to, part)| RepairPart {
+            from: index,
+            to,
+            lost: target,
+            helpers: helpers.to_vec(),
+            part: part.to_bytes(),
+        })
+        .collect())
+}
+
+/// Adds the parts received by helper `index`, one from every helper.
+///
+/// # Errors
+/// Returns an error if a part is addressed to someone else, the parts disagree
+/// on the lost participant or the helpers, or a helper's part is missing.
+pub fn combine_repair(
+    index: u32,
+    parts: &[RepairPart],
+) -> Result<RepairSum, Box<dyn std::error::Error>> {
+    let first = parts.first().ok_or("No repair parts to combine")?;
+    let mut senders = BTreeSet::new();
+    let mut sum = Scalar::zero();
+    for part in parts {
+        if part.to != index {
+            return Err(format!(
+                "Part from participant {} is addressed to participant {}, not {}",
+                part.from, part.to, index
+            )
+            .into());
+        }
+        if part.lost != first.lost || part.helpers != first.helpers {
+            return Err(format!(
+                "Part from participant {} belongs to a different repair",
+                part.from
+            )
+            .into());
+        }
+        if !senders.insert(part.from) {
+            return Err(format!("Two parts from participant {}", part.from).into());
+        }
+        sum += Scalar::from_canonical_bytes(part.part)
+            .ok_or_else(|| format!("Part from participant {} is malformed", part.from))?;
+    }
+    if senders != first.helpers.iter().copied().collect() {
+        return Err(format!(
+            "Repair needs a part from each of the {} helpers, got {}",
+            first.helpers.len(),
+            senders.len()
+        )
+        .into());
+    }
+
+    Ok(RepairSum {
+        from: index,
+        lost: first.lost,
+        helpers: first.helpers.clone(),
+        sum: sum.to_bytes(),
+    })
+}
+
+/// Adds the helpers' sums into the share of participant `index` and checks it against the public package.
+///
+/// # Errors
+/// Returns an error if a helper's sum is missing or belongs to another repair,
+/// or the recovered share does not match the participant's public share.
+pub fn complete_repair(
+    public_package: &FrostPublicPackage,
+    index: u32,
+    sums: &[RepairSum],
+) -> Result<FrostShare, Box<dyn std::error::Error>> {
+    let n = public_package.public_shares.len() as u32;
+    let key = add_sums(sums, index, public_package.threshold, n)?;
+
+    let public_share = public_package
+        .public_shares
+        .iter()
+        .find(|(_, share_index)| *share_index == index)
+        .map(|(share_bytes, _)| *share_bytes)
+        .ok_or_else(|| format!("No public share for participant {}", index))?;
+    if (&key * &RISTRETTO_BASEPOINT_TABLE).compress().to_bytes() != public_share {
+        return Err(format!(
+            "Recovered share does not match the public share of participant {}",
+            index
+        )
+        .into());
+    }
+
+    let mut share = FrostShare {
+        group_key: public_package.group_key,
+        private_share: (key.to_bytes(), index),
+        threshold: public_package.threshold,
+        n,
+        commitments: public_package.commitments.clone(),
+        checksum: None,
+    };
+    share.checksum = Some(integrity::share_checksum(&share));
+    integrity::check_share(&share)?;
+    Ok(share)
+}
+
+/// Adds the helpers' sums for participant `index`, checking that every helper sent exactly one.
+pub(crate) fn add_sums(
+    sums: &[RepairSum],
+    index: u32,
+    threshold: u32,
+    n: u32,
+) -> Result<Scalar, Box<dyn std::error::Error>> {
+    let first = sums.first().ok_or("No repair sums to add")?;
+    check_helpers(&first.helpers, index, threshold, n)?;
+
+    let mut senders = BTreeSet::new();
+    let mut key = Scalar::zero();
+    for sum in sums {
+        if sum.lost != index || sum.helpers != first.helpers {
+            return Err(format!(
+                "Sum from participant {} belongs to a different repair",
+                sum.from
+            )
+            .into());
+        }
+        if !senders.insert(sum.from) {
+            return Err(format!("Two sums from participant {}", sum.from).into());
+        }
+        key += Scalar::from_canonical_bytes(sum.sum)
+            .ok_or_else(|| format!("Sum from participant {} is malformed", sum.from))?;
+    }
+    if senders != first.helpers.iter().copied().collect() {
+        return Err(format!(
+            "Repair needs a sum from each of the {} helpers, got {}",
+            first.helpers.len(),
+            senders.len()
+        )
+        .into());
+    }
+
+    Ok(key)
+}
+
+/// First repair round, on every helper: writes `repair_<from>_to_<to>.json` for every helper into `output_dir`.
+///
+/// Existing part files are only replaced if `force` is set.
+pub fn repair_deal(
+    share_file: &str,
+    helpers: &[u32],
+    lost: u32,
+    output_dir: &str,
+    force: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let share = load_share(share_file)?;
+    let parts = deal_repair(&mut OsRng, &share, helpers, lost)?;
+    let paths: Vec<PathBuf> = parts
+        .iter()
+        .map(|part| Path::new(output_dir).join(format!("repair_{}_to_{}.json", part.from, part.to)))
+        .collect();
+    for path in &paths {
+        files::check_overwrite(path, force)?;
+    }
+    for (part, path) in parts.iter().zip(&paths) {
+        files::write_secret_json(path, part, force)?;
+    }
+
+    println!(
+        "Repair parts of participant {} saved to: {}",
+        share.private_share.1, output_dir
+    );
+    Ok(())
+}
+
+/// Second repair round, on every helper: adds the received parts into the sum for the recovering participant.
+///
+/// An existing `output_file` is only replaced if `force` is set.
+pub fn repair_combine(
+    share_file: &str,
+    part_files: &[String],
+    output_file: &str,
+    force: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    files::check_overwrite(output_file, force)?;
+    let share = load_share(share_file)?;
+    let parts: Vec<RepairPart> = load_files(part_files)?;
+    let sum = combine_repair(share.private_share.1, &parts)?;
+
+    files::write_secret_json(output_file, &sum, force)?;
+
+    println!(
+        "Repair sum for participant {} saved to: {}",
+        sum.lost, output_file
+    );
+    Ok(())
+}
+
+/// Last repair round, on the recovering participant: adds the helpers' sums into the repaired share.
+///
+/// # Arguments
+/// - `public_file`: Path to the group's public package.
+/// - `index`: The recovering participant's index.
+/// - `sum_files`: Paths to the sums, one from every helper.
+/// - `output_share_file`: Path to save the repaired share.
+/// - `force`: Replace `output_share_file` if it exists.
+pub fn repair_finish(
+    public_file: &str,
+    index: u32,
+    sum_files: &[String],
+    output_share_file: &str,
+    force: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    files::check_overwrite(output_share_file, force)?;
+    let public_package = load_public_package(public_file)?;
+    let sums: Vec<RepairSum> = load_files(sum_files)?;
+    let share = complete_repair(&public_package, index, &sums)?;
+
+    files::write_secret_json(output_share_file, &share, force)?;
+
+    println!(
+        "Repaired share of participant {} saved to: {}",
+        index, output_share_file
+    );
+    Ok(())
+}
+
+pub(crate) fn load_files<T: serde::de::DeserializeOwned>(
+    paths: &[String],
+) -> Result<Vec<T>, Box<dyn std::error::Error>> {
+    paths
+        .iter()
+        .map(|path| {
+            let file = File::open(path)?;
+            Ok(serde_json::from_reader(BufReader::new(file))?)
+        })
+        .collect()
+}
+
+/// Checks that `helpers` are at least `threshold` distinct indices between 1 and `n`, other than `lost`.
+pub(crate) fn check_helpers(
+    helpers: &[u32],
+    lost: u32,
+    threshold: u32,
+    n: u32,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if lost == 0 || lost > n {
+        return Err(format!("Lost participant index {} is not between 1 and {}", lost, n).into());
+    }
+    let mut seen = BTreeSet::new();
+    for &helper in helpers {
+        if helper == 0 || helper > n {
+            return Err(format!("Helper index {} is not between 1 and {}", helper, n).into());
+        }
+        if helper == lost {
+            return Err(format!("Participant {} cannot help repair its own share", lost).into());
+        }
+        if !seen.insert(helper) {
+            return Err(format!("Helper {} appears twice", helper).into());
+        }
+    }
+    if seen.len() < threshold as usize {
+        return Err(format!(
+            "Repair needs at least {} helpers, got {}",
+            threshold,
+            seen.len()
+        )
+        .into());
+    }
+    Ok(())
+}
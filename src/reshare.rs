@@ -0,0 +1,369 @@
+//! Resharing the group secret to a new threshold and participant set.
+//!
+//! At least t current holders each deal their share, weighted by its Lagrange
+//! coefficient over the dealing holders, through a fresh polynomial of degree
+//! t' - 1 to the n' new participants. The weighted shares sum to the group
+//! secret, so every new participant's sum of sub-shares is a share of the same
+//! secret on a new t'-of-n' polynomial: the group key does not change, and the
+//! old shares no longer combine with the new ones.
+//!
+//! New participants are numbered `1..=n'`. Each dealer's commitments are checked
+//! against its public share in the old public package, so a holder cannot deal
+//! anything other than its own weighted share.
+//!
+//! The reshare runs in-process over a key file with [`reshare_keys`], or as two
+//! distributed rounds:
+//!
+//! 1. Every dealing holder runs [`reshare_deal`] and privately delivers each package to its recipient.
+//! 2. Every new participant runs [`reshare_finish`] with the old public package and the packages
+//!    received from all dealing holders.
+//!
+//! A dealer could still send commitments for different polynomials to different
+//! new participants, each passing the public share check, and leave them with
+//! public packages that do not combine. As with a refresh, [`reshare_finish`]
+//! prints the [`commitments_digest`] of the packages it received; the new
+//! participants compare their digests, and one given the digest of another
+//! refuses to finish if its own differs.
+
+use crate::files;
+use crate::refresh::commitments_digest;
+use crate::rounds::lagrange_coefficient;
+use crate::vss::{load_packages, open_package, Polynomial, SharePackage};
+use crate::{
+    integrity, load_keys, load_public_package, load_share, public_package_from_commitments,
+    public_package_of, share_of, FrostPublicPackage, FrostShare,
+};
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::Identity;
+use rand::rngs::OsRng;
+use rand::{CryptoRng, RngCore};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Deals `share`'s Lagrange-weighted secret to the `new_n` participants of a `new_threshold`-of-`new_n` group.
+///
+/// # Errors
+/// Returns an error if `holders` does not include this share's holder, has fewer
+/// than t holders, or the new parameters are invalid.
+pub fn deal_reshare<R: RngCore + CryptoRng>(
+    csprng: &mut R,
+    share: &FrostShare,
+    holders: &[u32],
+    new_threshold: u32,
+    new_n: u32,
+) -> Result<Vec<SharePackage>, Box<dyn std::error::Error>> {
+    let (key_bytes, index) = share.private_share;
+    check_holders(holders, share.threshold, share.n)?;
+    check_parameters(new_threshold, new_n)?;
+    if !holders.contains(&index) {
+        return Err(format!("Participant {} is not among the dealing holders", index).into());
+    }
+
+    let key = Scalar::from_canonical_bytes(key_bytes).ok_or("Invalid private key bytes")?;
+    let weighted = lagrange_coefficient(index, holders) * key;
+    let polynomial = Polynomial::random(csprng, weighted, new_threshold);
+    Ok(polynomial.deal(index, 1..=new_n))
+}
+
+/// Combines the packages dealt to new participant `index` into its share, and
+/// returns it with the new group's public package.
+///
+/// # Arguments
+/// - `old_package`: The public package of the group before the reshare.
+/// - `index`: The new participant's index (1 to `new_n`).
+/// - `new_threshold`, `new_n`: The parameters of the new group.
+/// - `packages`: The packages addressed to `index`, one from every dealing holder.
+///
+/// # Errors
+/// Returns an error if fewer than t holders dealt, a holder dealt twice, or a
+/// package does not match its commitments or its dealer's public share.
+pub fn complete_reshare(
+    old_package: &FrostPublicPackage,
+    index: u32,
+    new_threshold: u32,
+    new_n: u32,
+    packages: &[SharePackage],
+) -> Result<(FrostShare, FrostPublicPackage), Box<dyn std::error::Error>> {
+    check_parameters(new_threshold, new_n)?;
+    if index == 0 || index > new_n {
+        return Err(format!("Participant index {} is not between 1 and {}", index, new_n).into());
+    }
+
+    let holders: Vec<u32> = packages.iter().map(|package| package.from).collect();
+    check_holders(
+        &holders,
+        old_package.threshold,
+        old_package.public_shares.len() as u32,
+    )?;
+
+    let mut key = Scalar::zero();
+    let mut commitments = vec![RistrettoPoint::identity(); new_threshold as usize];
+    for package in packages {
+        let (sub_share, package_commitments) = open_package(package, index, new_threshold)?;
+
+        // The dealer must have dealt its own share, weighted over the dealing holders.
+        let public_share = old_package
+            .public_shares
+            .iter()
+            .find(|(_, share_index)| *share_index == package.from)
+            .and_then(|(share_bytes, _)| CompressedRistretto(*share_bytes).decompress())
+            .ok_or_else(|| {
+                format!(
+                    "Participant {} is not a holder in the old public package",
+                    package.from
+                )
+            })?;
+        if package_commitments[0] != lagrange_coefficient(package.from, &holders) * public_share {
+            return Err(format!(
+                "Package from participant {} does not deal that participant's share",
+                package.from
+            )
+            .into());
+        }
+
+        key += sub_share;
+        for (commitment, package_commitment) in commitments.iter_mut().zip(&package_commitments) {
+            *commitment += package_commitment;
+        }
+    }
+    if commitments[0].compress().to_bytes() != old_package.group_key {
+        return Err("Reshared shares do not reproduce the group key".into());
+    }
+
+    let public_package =
+        public_package_from_commitments(old_package.group_key, &commitments, new_n);
+    let mut share = FrostShare {
+        group_key: old_package.group_key,
+        private_share: (key.to_bytes(), index),
+        threshold: new_threshold,
+        n: new_n,
+        commitments: public_package.commitments.clone(),
+        checksum: None,
+    };
+    share.checksum = Some(integrity::share_checksum(&share));
+    integrity::check_share(&share)?;
+    Ok((share, public_package))
+}
+
+/// Reshares the keys of a key file in-process to a new threshold and participant set.
+///
+/// Writes `share_<index>.json` for every new participant and `frost_public.json`
+/// with the new public package into `output_dir`.
+///
+/// # Arguments
+/// - `key_file`: Path to the file containing the current keys.
+/// - `holders`: Indices of the current holders taking part, at least t of them.
+/// - `new_threshold`, `new_n`: The parameters of the new group.
+/// - `output_dir`: Directory to write the new share files and public package to.
+/// - `force`: Replace existing files in the output directory.
+pub fn reshare_keys(
+    key_file: &str,
+    holders: &[u32],
+    new_threshold: u32,
+    new_n: u32,
+    output_dir: &str,
+    force: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let path = |name: String| {
+        Path::new(output_dir)
+            .join(name)
+            .to_string_lossy()
+            .into_owned()
+    };
+    let share_files: Vec<String> = (1..=new_n)
+        .map(|index| path(format!("share_{}.json", index)))
+        .collect();
+    let public_file = path("frost_public.json".to_string());
+    for file in share_files.iter().chain([&public_file]) {
+        files::check_overwrite(file, force)?;
+    }
+
+    let frost_keys = load_keys(key_file)?;
+    let old_package = public_package_of(&frost_keys)?;
+
+    // Round 1: every dealing holder deals its weighted share.
+    let mut packages = Vec::new();
+    for &holder in holders {
+        let share = share_of(&frost_keys, holder)?;
+        packages.extend(deal_reshare(
+            &mut OsRng,
+            &share,
+            holders,
+            new_threshold,
+            new_n,
+        )?);
+    }
+
+    // Round 2: every new participant combines the sub-shares addressed to it, and
+    // all new participants must have received the same commitments.
+    fs::create_dir_all(output_dir)?;
+    let mut results = Vec::with_capacity(new_n as usize);
+    let mut digests = BTreeSet::new();
+    for (index, share_file) in (1..=new_n).zip(&share_files) {
+        let received: Vec<SharePackage> = packages
+            .iter()
+            .filter(|package| package.to == index)
+            .cloned()
+            .collect();
+        digests.insert(commitments_digest(&received));
+        results.push(complete_reshare(
+            &old_package,
+            index,
+            new_threshold,
+            new_n,
+            &received,
+        )?);
+    }
+    if digests.len() > 1 {
+        return Err("New participants received different commitments from the same dealer".into());
+    }
+    let (_, public_package) = results.first().ok_or("No new participants")?;
+    if results.iter().any(|(_, package)| package != public_package) {
+        return Err("New participants computed different public packages".into());
+    }
+
+    for ((share, _), share_file) in results.iter().zip(&share_files) {
+        files::write_secret_json(share_file, share, force)?;
+    }
+    files::write_json(&public_file, public_package)?;
+
+    println!(
+        "Reshared to {}-of-{} for the same group key. Shares and public package saved to: {}",
+        new_threshold, new_n, output_dir
+    );
+    Ok(())
+}
+
+/// First distributed round: deals this holder's reshare packages into `output_dir`.
+///
+/// One file `reshare_<from>_to_<to>.json` is written per new participant, and
+/// must be delivered privately to new participant `<to>`. Existing package files
+/// are only replaced if `force` is set.
+pub fn reshare_deal(
+    share_file: &str,
+    holders: &[u32],
+    new_threshold: u32,
+    new_n: u32,
+    output_dir: &str,
+    force: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let share = load_share(share_file)?;
+    let packages = deal_reshare(&mut OsRng, &share, holders, new_threshold, new_n)?;
+    let paths: Vec<PathBuf> = packages
+        .iter()
+        .map(|package| {
+            Path::new(output_dir).join(format!("reshare_{}_to_{}.json", package.from, package.to))
+        })
+        .collect();
+    for path in &paths {
+        files::check_overwrite(path, force)?;
+    }
+    for (package, path) in packages.iter().zip(&paths) {
+        files::write_secret_json(path, package, force)?;
+    }
+
+    println!(
+        "Reshare packages of participant {} saved to: {}",
+        share.private_share.1, output_dir
+    );
+    Ok(())
+}
+
+/// Second distributed round: combines the received packages into the new participant's share.
+///
+/// # Arguments
+/// - `public_file`: Path to the public package of the group before the reshare.
+/// - `index`: The new participant's index (1 to `new_n`).
+/// - `new_threshold`, `new_n`: The parameters of the new group.
+/// - `package_files`: Paths to the packages addressed to `index`, one from every dealing holder.
+/// - `expected_digest`: The [`commitments_digest`] announced by another new participant, if any.
+/// - `output_share_file`: Path to save the new share.
+/// - `output_public_file`: Path to save the new public package.
+/// - `force`: Replace the output files if they exist.
+///
+/// # Errors
+/// Returns an error if the packages are invalid or their digest differs from `expected_digest`.
+#[allow(clippy::too_many_arguments)]
+pub fn reshare_finish(
+    public_file: &str,
+    index: u32,
+    new_threshold: u32,
+    new_n: u32,
+    package_files: &[String],
+    expected_digest: Option<&str>,
+    output_share_file: &str,
+    output_public_file: &str,
+    force: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    for file in [output_share_file, output_public_file] {
+        files::check_overwrite(file, force)?;
+    }
+    let old_package = load_public_package(public_file)?;
+    let packages = load_packages(package_files)?;
+    let digest = commitments_digest(&packages);
+    if let Some(expected) = expected_digest {
+        if !expected.eq_ignore_ascii_case(&digest) {
+            return Err(format!(
+                "Commitments digest {} differs from the expected {}: a dealer sent \
+                 different commitments to different new participants",
+                digest, expected
+            )
+            .into());
+        }
+    }
+    let (share, public_package) =
+        complete_reshare(&old_package, index, new_threshold, new_n, &packages)?;
+
+    files::write_secret_json(output_share_file, &share, force)?;
+    files::write_json(output_public_file, &public_package)?;
+
+    println!(
+        "Share of new participant {} saved to: {}",
+        index, output_share_file
+    );
+    println!("New public package saved to: {}", output_public_file);
+    println!(
+        "Commitments digest: {}\nCompare it with every other new participant before using the new share.",
+        digest
+    );
+    Ok(())
+}
+
+/// Checks that `holders` are at least `threshold` distinct indices between 1 and `n`.
+fn check_holders(
+    holders: &[u32],
+    threshold: u32,
+    n: u32,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut seen = BTreeSet::new();
+    for &holder in holders {
+        if holder == 0 || holder > n {
+            return Err(format!("Holder index {} is not between 1 and {}", holder, n).into());
+        }
+        if !seen.insert(holder) {
+            return Err(format!("Holder {} appears twice", holder).into());
+        }
+    }
+    if seen.len() < threshold as usize {
+        return Err(format!(
+            "Resharing needs at least {} holders, got {}",
+            threshold,
+            seen.len()
+        )
+        .into());
+    }
+    Ok(())
+}
+
+fn check_parameters(threshold: u32, n: u32) -> Result<(), Box<dyn std::error::Error>> {
+    if threshold == 0 || threshold > n {
+        return Err(format!(
+            "New threshold {} is not between 1 and the {} new participants",
+            threshold, n
+        )
+        .into());
+    }
+    Ok(())
+}
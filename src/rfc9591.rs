@@ -0,0 +1,607 @@
+//! FROST as standardized in RFC 9591, generic over a [`Ciphersuite`].
+//!
+//! The protocol follows the two rounds of the RFC:
+//!
+//! 1. Every signer samples a hiding and a binding nonce with [`commit`] and
+//!    publishes the [`SigningCommitment`] to them.
+//! 2. Given the message and the commitments of every signer, each signer
+//!    produces a [`SignatureShare`] with [`sign_share`]. The coordinator checks
+//!    every share and adds them into the signature with [`aggregate`].
+//!
+//! Keys come from a trusted dealer ([`deal`]), as in Appendix C of the RFC, and
+//! are stored in a [`SuiteKeys`] file that names its ciphersuite. Signatures are
+//! `SerializeElement(R) || SerializeScalar(z)`, except where the ciphersuite
+//! encodes them otherwise (BIP-340).
+
+use crate::ciphersuite::{Ciphersuite, Ed25519, Ristretto255, Secp256k1, Secp256k1Tr, SuiteId};
+use crate::integrity::{check_checksum, corrupted};
+use crate::secret::{self, Secret};
+use rand::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeSet;
+use zeroize::Zeroize;
+
+/// A key set for an RFC 9591 ciphersuite, with every element and scalar in the suite's encoding.
+#[derive(Serialize, Deserialize)]
+pub struct SuiteKeys {
+    /// The ciphersuite's `contextString`, e.g. `FROST-ED25519-SHA512-v1`.
+    pub ciphersuite: String,
+    pub group_key: Vec<u8>,
+    pub private_shares: Vec<(Vec<u8>, u32)>,
+    pub threshold: u32,
+    /// Commitments to the coefficients of the group's secret polynomial.
+    pub commitments: Vec<Vec<u8>>,
+    /// SHA-256 checksum over all other fields.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<String>,
+}
+
+impl Drop for SuiteKeys {
+    fn drop(&mut self) {
+        for (key_bytes, _) in &mut self.private_shares {
+            key_bytes.zeroize();
+            secret::unlock(key_bytes.as_slice());
+        }
+    }
+}
+
+impl SuiteKeys {
+    /// Returns the ciphersuite the keys belong to.
+    pub fn suite(&self) -> Result<SuiteId, Box<dyn std::error::Error>> {
+        SuiteId::from_context(&self.ciphersuite)
+            .ok_or_else(|| format!("Unknown ciphersuite {:?}", self.ciphersuite).into())
+    }
+
+    /// Returns the checksum of the key set, over every field except the checksum itself.
+    pub fn compute_checksum(&self) -> String {
+        let mut h = Sha256::new();
+        h.update(b"frost-cli suite key set");
+        h.update(self.ciphersuite.as_bytes());
+        h.update(&self.group_key);
+        h.update(self.threshold.to_be_bytes());
+        for (key_bytes, index) in &self.private_shares {
+            h.update(index.to_be_bytes());
+            h.update(key_bytes);
+        }
+        for commitment in &self.commitments {
+            h.update(commitment);
+        }
+        hex::encode(h.finalize())
+    }
+}
+
+/// Runs `$f::<C>($args)` with the [`Ciphersuite`] named by a [`SuiteId`].
+macro_rules! with_suite {
+    ($suite:expr, $f:ident($($args:expr),*)) => {
+        match $suite {
+            SuiteId::Ed25519 => $f::<Ed25519>($($args),*),
+            SuiteId::Ristretto255 => $f::<Ristretto255>($($args),*),
+            SuiteId::Secp256k1 => $f::<Secp256k1>($($args),*),
+            SuiteId::Secp256k1Tr => $f::<Secp256k1Tr>($($args),*),
+            SuiteId::FrostDalek => {
+                Err("frost-dalek keys are not RFC 9591 keys".into())
+            }
+        }
+    };
+}
+
+/// The secret nonces of one signer for one signing session. Use them only once.
+pub struct SigningNonces<C: Ciphersuite> {
+    pub hiding: C::Scalar,
+    pub binding: C::Scalar,
+}
+
+impl<C: Ciphersuite> Drop for SigningNonces<C> {
+    fn drop(&mut self) {
+        self.hiding.zeroize();
+        self.binding.zeroize();
+    }
+}
+
+/// The public commitment of signer `identifier` to its nonces.
+pub struct SigningCommitment<C: Ciphersuite> {
+    pub identifier: u32,
+    pub hiding: C::Element,
+    pub binding: C::Element,
+}
+
+impl<C: Ciphersuite> Clone for SigningCommitment<C> {
+    fn clone(&self) -> Self {
+        SigningCommitment {
+            identifier: self.identifier,
+            hiding: self.hiding,
+            binding: self.binding,
+        }
+    }
+}
+
+/// The signature share of signer `identifier`.
+pub struct SignatureShare<C: Ciphersuite> {
+    pub identifier: u32,
+    pub share: C::Scalar,
+}
+
+fn random_scalar<C: Ciphersuite, R: RngCore + CryptoRng>(csprng: &mut R) -> C::Scalar {
+    let mut bytes = [0u8; 64];
+    csprng.fill_bytes(&mut bytes);
+    let scalar = C::scalar_from_wide(&bytes);
+    bytes.zeroize();
+    scalar
+}
+
+/// `nonce_generate` of the RFC: hashes fresh randomness with the secret, so a weak RNG alone does not leak it.
+fn nonce_generate<C: Ciphersuite, R: RngCore + CryptoRng>(
+    csprng: &mut R,
+    secret: &C::Scalar,
+) -> C::Scalar {
+    let mut input = [0u8; 32].to_vec();
+    csprng.fill_bytes(&mut input);
+    input.extend(C::serialize_scalar(secret));
+    let nonce = C::h3(&input);
+    input.zeroize();
+    nonce
+}
+
+/// First round: samples the nonces of signer `identifier` and commits to them.
+pub fn commit<C: Ciphersuite, R: RngCore + CryptoRng>(
+    csprng: &mut R,
+    identifier: u32,
+    secret: &C::Scalar,
+) -> (SigningNonces<C>, SigningCommitment<C>) {
+    let hiding = nonce_generate::<C, R>(csprng, secret);
+    let binding = nonce_generate::<C, R>(csprng, secret);
+    let commitment = SigningCommitment {
+        identifier,
+        hiding: C::generator() * hiding,
+        binding: C::generator() * binding,
+    };
+    (SigningNonces { hiding, binding }, commitment)
+}
+
+/// Checks that the commitments come from distinct, non-zero identifiers in ascending order.
+fn check_commitment_list<C: Ciphersuite>(
+    commitments: &[SigningCommitment<C>],
+) -> Result<(), Box<dyn std::error::Error>> {
+    if commitments.is_empty() {
+        return Err("No signing commitments".into());
+    }
+    for pair in commitments.windows(2) {
+        if pair[0].identifier >= pair[1].identifier {
+            return Err("Signing commitments must be sorted by distinct identifiers".into());
+        }
+    }
+    if commitments[0].identifier == 0 {
+        return Err("Identifier 0 is not a valid signer".into());
+    }
+    Ok(())
+}
+
+fn encode_group_commitment_list<C: Ciphersuite>(commitments: &[SigningCommitment<C>]) -> Vec<u8> {
+    let mut encoded = Vec::new();
+    for commitment in commitments {
+        encoded.extend(C::serialize_scalar(&C::scalar_from_u32(
+            commitment.identifier,
+        )));
+        encoded.extend(C::serialize_element(&commitment.hiding));
+        encoded.extend(C::serialize_element(&commitment.binding));
+    }
+    encoded
+}
+
+/// Returns the binding factor of every signer, in the order of `commitments`.
+fn compute_binding_factors<C: Ciphersuite>(
+    group_key: &C::Element,
+    commitments: &[SigningCommitment<C>],
+    message: &[u8],
+) -> Vec<C::Scalar> {
+    let mut prefix = C::serialize_element(group_key);
+    prefix.extend(C::h4(message));
+    prefix.extend(C::h5(&encode_group_commitment_list(commitments)));
+
+    commitments
+        .iter()
+        .map(|commitment| {
+            let mut input = prefix.clone();
+            input.extend(C::serialize_scalar(&C::scalar_from_u32(
+                commitment.identifier,
+            )));
+            C::h1(&input)
+        })
+        .collect()
+}
+
+/// Returns the group commitment in the suite's normal form, and whether the
+/// signers' nonces must be negated to reach it.
+fn compute_group_commitment<C: Ciphersuite>(
+    commitments: &[SigningCommitment<C>],
+    binding_factors: &[C::Scalar],
+) -> (C::Element, bool) {
+    let group_commitment = commitments.iter().zip(binding_factors).fold(
+        C::identity(),
+        |acc, (commitment, binding_factor)| {
+            acc + commitment.hiding + commitment.binding * *binding_factor
+        },
+    );
+    if C::needs_negation(&group_commitment) {
+        (-group_commitment, true)
+    } else {
+        (group_commitment, false)
+    }
+}
+
+/// The Lagrange coefficient at 0 of `identifier` over the signers in `commitments`.
+fn derive_interpolating_value<C: Ciphersuite>(
+    identifier: u32,
+    commitments: &[SigningCommitment<C>],
+) -> C::Scalar {
+    let x_i = C::scalar_from_u32(identifier);
+    let mut numerator = C::scalar_from_u32(1);
+    let mut denominator = C::scalar_from_u32(1);
+    for commitment in commitments {
+        if commitment.identifier == identifier {
+            continue;
+        }
+        let x_j = C::scalar_from_u32(commitment.identifier);
+        numerator = numerator * x_j;
+        denominator = denominator * (x_j - x_i);
+    }
+    numerator * C::invert(&denominator)
+}
+
+/// Second round: signer `identifier` signs `message` with its share, given every signer's commitment.
+///
+/// # Errors
+/// Returns an error if the commitment list is malformed or does not include this signer.
+pub fn sign_share<C: Ciphersuite>(
+    identifier: u32,
+    secret: &C::Scalar,
+    nonces: SigningNonces<C>,
+    group_key: &C::Element,
+    commitments: &[SigningCommitment<C>],
+    message: &[u8],
+) -> Result<SignatureShare<C>, Box<dyn std::error::Error>> {
+    check_commitment_list(commitments)?;
+    let position = commitments
+        .iter()
+        .position(|commitment| commitment.identifier == identifier)
+        .ok_or_else(|| format!("Signer {} has no commitment in the list", identifier))?;
+
+    let binding_factors = compute_binding_factors(group_key, commitments, message);
+    let (group_commitment, negate) = compute_group_commitment(commitments, &binding_factors);
+    let lambda = derive_interpolating_value(identifier, commitments);
+    let challenge = C::challenge(&group_commitment, group_key, message);
+
+    let mut nonce = nonces.hiding + nonces.binding * binding_factors[position];
+    if negate {
+        nonce = -nonce;
+    }
+    let share = nonce + lambda * *secret * challenge;
+    Ok(SignatureShare { identifier, share })
+}
+
+/// Checks every signature share and adds them into the serialized signature.
+///
+/// `public_shares` holds the public key share of every signer, by identifier.
+///
+/// # Errors
+/// Returns an error naming the first signer whose share is missing or invalid.
+pub fn aggregate<C: Ciphersuite>(
+    group_key: &C::Element,
+    commitments: &[SigningCommitment<C>],
+    message: &[u8],
+    shares: &[SignatureShare<C>],
+    public_shares: &[(u32, C::Element)],
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    check_commitment_list(commitments)?;
+    let binding_factors = compute_binding_factors(group_key, commitments, message);
+    let (group_commitment, negate) = compute_group_commitment(commitments, &binding_factors);
+    let challenge = C::challenge(&group_commitment, group_key, message);
+
+    let mut z = C::scalar_from_u32(0);
+    for (commitment, binding_factor) in commitments.iter().zip(&binding_factors) {
+        let identifier = commitment.identifier;
+        let share = shares
+            .iter()
+            .find(|share| share.identifier == identifier)
+            .ok_or_else(|| format!("Missing signature share of signer {}", identifier))?;
+        let public_share = public_shares
+            .iter()
+            .find(|(index, _)| *index == identifier)
+            .map(|(_, element)| *element)
+            .ok_or_else(|| format!("No public share for signer {}", identifier))?;
+
+        let lambda = derive_interpolating_value(identifier, commitments);
+        let mut commitment_share = commitment.hiding + commitment.binding * *binding_factor;
+        if negate {
+            commitment_share = -commitment_share;
+        }
+        if C::generator() * share.share != commitment_share + public_share * (challenge * lambda) {
+            return Err(format!("Invalid signature share from signer {}", identifier).into());
+        }
+        z = z + share.share;
+    }
+
+    Ok(C::serialize_signature(&group_commitment, &z))
+}
+
+/// Verifies a serialized signature over `message` against a group key.
+pub fn verify<C: Ciphersuite>(
+    group_key: &C::Element,
+    message: &[u8],
+    signature: &[u8],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (commitment, z) =
+        C::deserialize_signature(signature).ok_or("Invalid threshold signature encoding")?;
+    let challenge = C::challenge(&commitment, group_key, message);
+    if !C::verify_equation(group_key, &commitment, &z, &challenge) {
+        return Err("Signature verification failed".into());
+    }
+    Ok(())
+}
+
+/// The group key, the share of every participant and the commitments to the secret polynomial.
+pub type DealtKeys<C> = (
+    <C as Ciphersuite>::Element,
+    Vec<(u32, <C as Ciphersuite>::Scalar)>,
+    Vec<<C as Ciphersuite>::Element>,
+);
+
+/// Splits a fresh random secret into `n` shares with threshold `t`, as a trusted dealer.
+///
+/// Returns the group key, the share of every participant `1..=n` and the
+/// commitments to the coefficients of the secret polynomial.
+pub fn deal<C: Ciphersuite, R: RngCore + CryptoRng>(
+    csprng: &mut R,
+    t: u32,
+    n: u32,
+) -> DealtKeys<C> {
+    let mut coefficients: Vec<C::Scalar> = (0..t).map(|_| random_scalar::<C, R>(csprng)).collect();
+    let shares = (1..=n)
+        .map(|index| {
+            let x = C::scalar_from_u32(index);
+            let value = coefficients
+                .iter()
+                .rev()
+                .fold(C::scalar_from_u32(0), |acc, coefficient| {
+                    acc * x + *coefficient
+                });
+            (index, value)
+        })
+        .collect();
+    let commitments: Vec<C::Element> = coefficients
+        .iter()
+        .map(|coefficient| C::generator() * *coefficient)
+        .collect();
+    coefficients.zeroize();
+    (commitments[0], shares, commitments)
+}
+
+fn evaluate_commitments<C: Ciphersuite>(commitments: &[C::Element], index: u32) -> C::Element {
+    let x = C::scalar_from_u32(index);
+    commitments
+        .iter()
+        .rev()
+        .fold(C::identity(), |acc, commitment| acc * x + *commitment)
+}
+
+/// Generates a key set for `suite` with threshold `t` out of `n` participants.
+///
+/// # Errors
+/// Returns an error if `suite` is not an RFC 9591 suite or `t` is not between 1 and `n`.
+pub fn generate<R: RngCore + CryptoRng>(
+    csprng: &mut R,
+    suite: SuiteId,
+    t: u32,
+    n: u32,
+) -> Result<SuiteKeys, Box<dyn std::error::Error>> {
+    if t == 0 || t > n {
+        return Err(
+            "Threshold value must be between 1 and the total number of participants".into(),
+        );
+    }
+    with_suite!(suite, generate_with(csprng, t, n))
+}
+
+fn generate_with<C: Ciphersuite>(
+    csprng: &mut (impl RngCore + CryptoRng),
+    t: u32,
+    n: u32,
+) -> Result<SuiteKeys, Box<dyn std::error::Error>> {
+    let (mut group_key, mut shares, mut commitments) = deal::<C, _>(csprng, t, n);
+    // Negating the whole polynomial negates the group key, keeping every share consistent.
+    if C::needs_negation(&group_key) {
+        group_key = -group_key;
+        shares.iter_mut().for_each(|(_, share)| *share = -*share);
+        commitments
+            .iter_mut()
+            .for_each(|commitment| *commitment = -*commitment);
+    }
+    let mut keys = SuiteKeys {
+        ciphersuite: C::CONTEXT.to_string(),
+        group_key: C::serialize_element(&group_key),
+        private_shares: shares
+            .iter()
+            .map(|(index, share)| (C::serialize_scalar(share), *index))
+            .collect(),
+        threshold: t,
+        commitments: commitments.iter().map(C::serialize_element).collect(),
+        checksum: None,
+    };
+    shares.iter_mut().for_each(|(_, share)| share.zeroize());
+    keys.checksum = Some(keys.compute_checksum());
+    Ok(keys)
+}
+
+/// Checks that a key set is consistent: every value decodes, the first commitment
+/// is the group key, every share lies on the committed polynomial and the checksum matches.
+///
+/// The checks and their errors are those of [`check_key_set`](crate::integrity::check_key_set).
+/// Suite key files always carry commitments, so the checksum is always required.
+pub fn check_keys(keys: &SuiteKeys) -> Result<(), Box<dyn std::error::Error>> {
+    with_suite!(keys.suite()?, check_keys_with(keys))
+}
+
+fn check_keys_with<C: Ciphersuite>(keys: &SuiteKeys) -> Result<(), Box<dyn std::error::Error>> {
+    let group_key = C::deserialize_element(&keys.group_key)
+        .ok_or_else(|| corrupted("group_key", "it does not decode to a group element"))?;
+    let commitments = keys
+        .commitments
+        .iter()
+        .enumerate()
+        .map(|(k, bytes)| {
+            C::deserialize_element(bytes).ok_or_else(|| {
+                corrupted(
+                    &format!("commitments[{}]", k),
+                    "it does not decode to a group element",
+                )
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if commitments.len() != keys.threshold as usize {
+        return Err(corrupted(
+            "threshold",
+            &format!(
+                "it is {} but the polynomial has {} commitments",
+                keys.threshold,
+                commitments.len()
+            ),
+        ));
+    }
+    if commitments.first() != Some(&group_key) {
+        return Err(corrupted(
+            "group_key",
+            "it does not match the first commitment",
+        ));
+    }
+
+    let mut seen = BTreeSet::new();
+    for (position, (key_bytes, index)) in keys.private_shares.iter().enumerate() {
+        let field = format!("private_shares[{}]", position);
+        if *index == 0 || !seen.insert(*index) {
+            return Err(corrupted(
+                &field,
+                &format!("its participant index {} is 0 or repeated", index),
+            ));
+        }
+        let secret = C::deserialize_scalar(key_bytes)
+            .ok_or_else(|| corrupted(&field, "it does not decode to a scalar"))?;
+        if C::generator() * secret != evaluate_commitments::<C>(&commitments, *index) {
+            return Err(corrupted(
+                &field,
+                &format!(
+                    "the share of participant {} does not lie on the committed polynomial",
+                    index
+                ),
+            ));
+        }
+    }
+
+    check_checksum(
+        keys.checksum.as_deref(),
+        &keys.compute_checksum(),
+        !keys.commitments.is_empty(),
+    )
+}
+
+/// Signs `message` in memory with the shares of the given participant indices.
+///
+/// Runs both rounds for every signer and aggregates the shares, checking each one.
+///
+/// # Errors
+/// Returns an error if there are fewer signers than the threshold or a signer has no share.
+pub fn sign<R: RngCore + CryptoRng>(
+    csprng: &mut R,
+    keys: &SuiteKeys,
+    message: &[u8],
+    signers: &[u32],
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    if signers.len() < keys.threshold as usize {
+        return Err("Number of signers is less than the threshold".into());
+    }
+    with_suite!(keys.suite()?, sign_with(csprng, keys, message, signers))
+}
+
+fn sign_with<C: Ciphersuite>(
+    csprng: &mut (impl RngCore + CryptoRng),
+    keys: &SuiteKeys,
+    message: &[u8],
+    signers: &[u32],
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let group_key = C::deserialize_element(&keys.group_key).ok_or("Invalid group public key")?;
+
+    let mut signers = signers.to_vec();
+    signers.sort_unstable();
+    let mut secrets = Vec::new();
+    for &index in &signers {
+        let (key_bytes, _) = keys
+            .private_shares
+            .iter()
+            .find(|(_, share_index)| *share_index == index)
+            .ok_or_else(|| format!("No share for participant {} in the key file", index))?;
+        let secret = C::deserialize_scalar(key_bytes).ok_or("Invalid private key bytes")?;
+        secrets.push((index, Secret::new(secret)));
+    }
+
+    // Round one: every signer commits to fresh nonces.
+    let mut nonces = Vec::new();
+    let mut commitments = Vec::new();
+    for (index, secret) in &secrets {
+        let (signer_nonces, commitment) = commit::<C, _>(csprng, *index, &**secret);
+        nonces.push(signer_nonces);
+        commitments.push(commitment);
+    }
+
+    // Round two: every signer signs, and the shares are checked and aggregated.
+    let mut shares = Vec::new();
+    for ((index, secret), signer_nonces) in secrets.iter().zip(nonces) {
+        shares.push(sign_share(
+            *index,
+            &**secret,
+            signer_nonces,
+            &group_key,
+            &commitments,
+            message,
+        )?);
+    }
+    let public_shares: Vec<(u32, C::Element)> = secrets
+        .iter()
+        .map(|(index, secret)| (*index, C::generator() * **secret))
+        .collect();
+    aggregate(&group_key, &commitments, message, &shares, &public_shares)
+}
+
+/// Verifies a serialized signature over `message` against an encoded group key of `suite`.
+pub fn verify_suite(
+    suite: SuiteId,
+    group_key: &[u8],
+    message: &[u8],
+    signature: &[u8],
+) -> Result<(), Box<dyn std::error::Error>> {
+    with_suite!(suite, verify_with(group_key, message, signature))
+}
+
+/// Checks that `signature` decodes as a signature of `suite`, without verifying it.
+pub fn check_signature_encoding(
+    suite: SuiteId,
+    signature: &[u8],
+) -> Result<(), Box<dyn std::error::Error>> {
+    with_suite!(suite, check_signature_encoding_with(signature))
+}
+
+fn check_signature_encoding_with<C: Ciphersuite>(
+    signature: &[u8],
+) -> Result<(), Box<dyn std::error::Error>> {
+    C::deserialize_signature(signature)
+        .map(|_| ())
+        .ok_or_else(|| "Invalid threshold signature encoding".into())
+}
+
+fn verify_with<C: Ciphersuite>(
+    group_key: &[u8],
+    message: &[u8],
+    signature: &[u8],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let group_key = C::deserialize_element(group_key).ok_or("Invalid group public key")?;
+    verify::<C>(&group_key, message, signature)
+}
@@ -0,0 +1,435 @@
+//! Robust networked signing with automatic fallback to other signers.
+//!
+//! [`request_signature_robust`] invites more participants than the threshold
+//! and keeps signing until one session succeeds, in the spirit of ROAST
+//! (Ruffing et al., "ROAST: Robust Asynchronous Schnorr Threshold Signatures"):
+//!
+//! 1. Every participant is checked against the group's public package, like in
+//!    [`request_signature`](crate::net::request_signature), and asked for a
+//!    commitment. Participants that have sent a fresh commitment are *ready*.
+//! 2. As soon as t participants are ready, a signing session starts with them,
+//!    using the commitments they sent.
+//! 3. A participant's partial signature is checked as soon as it arrives. A
+//!    participant with a valid one is asked for a new commitment and becomes
+//!    ready again, so it can join the next session without waiting for the
+//!    others of its session.
+//! 4. A participant that is unreachable, does not answer within the timeout,
+//!    does not match the public package, refuses to sign or sends an invalid
+//!    partial signature is excluded for the rest of the run, and the sessions it
+//!    was part of are abandoned.
+//!
+//! The run ends with the first session whose t partial signatures are all valid
+//! and whose aggregated signature verifies under the group key, or with an error
+//! once fewer than t participants are left. Every participant
+//! is in at most one session at a time, so at most n - t + 1 sessions start.
+//!
+//! Participants are the daemons of [`net`](crate::net), reached over one
+//! connection each; the protocol is unchanged.
+
+use crate::net::{check_info, public_shares_of, Connection, Request, Response, SigningEvent};
+use crate::rounds::{self, InvalidShare, PartialSignature, SigningCommitment};
+use crate::{files, load_public_package, verify_signature_with_context, FrostPublicPackage};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::Duration;
+
+/// What a participant's connection reports to the coordinator.
+enum Update {
+    /// The participant's answer to [`Request::Info`].
+    Joined {
+        worker: usize,
+        info: Response,
+    },
+    Ready {
+        worker: usize,
+        commitment: SigningCommitment,
+    },
+    Signed {
+        worker: usize,
+        partial: PartialSignature,
+    },
+    Failed {
+        worker: usize,
+        reason: String,
+    },
+}
+
+/// A signing session: the commitments it was started with and the partial signatures received so far.
+struct Session {
+    commitments: Vec<SigningCommitment>,
+    partials: Vec<PartialSignature>,
+}
+
+/// Drives the connection to one participant: commit, wait to be picked for a
+/// session, sign, and commit again, until the coordinator stops listening.
+fn run_worker(
+    worker: usize,
+    address: String,
+    message: String,
+    context: String,
+    timeout: Duration,
+    updates: Sender<Update>,
+    sessions: Receiver<Vec<SigningCommitment>>,
+) {
+    let run = || -> Result<(), Box<dyn std::error::Error>> {
+        let mut connection = Connection::open_with_timeout(&address, timeout)?;
+        let info = connection.exchange(&Request::Info)?;
+        if updates.send(Update::Joined { worker, info }).is_err() {
+            return Ok(());
+        }
+
+        loop {
+            let commitment = match connection.exchange(&Request::Commit)? {
+                Response::Commitment(commitment) => commitment,
+                other => return Err(connection.unexpected(other)),
+            };
+            if updates.send(Update::Ready { worker, commitment }).is_err() {
+                return Ok(());
+            }
+            let Ok(commitments) = sessions.recv() else {
+                return Ok(());
+            };
+            let request = Request::Sign {
+                message: message.clone(),
+                context: context.clone(),
+                commitments,
+            };
+            let partial = match connection.exchange(&request)? {
+                Response::PartialSignature(partial) => partial,
+                other => return Err(connection.unexpected(other)),
+            };
+            if updates.send(Update::Signed { worker, partial }).is_err() {
+                return Ok(());
+            }
+        }
+    };
+
+    if let Err(err) = run() {
+        let reason = match err.downcast_ref::<io::Error>() {
+            Some(io_err)
+                if matches!(
+                    io_err.kind(),
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                ) =>
+            {
+                format!("No answer within {:?}", timeout)
+            }
+            _ => err.to_string(),
+        };
+        let _ = updates.send(Update::Failed { worker, reason });
+    }
+}
+
+/// The coordinator's view of the run.
+struct Coordinator<'a> {
+    message: &'a str,
+    context: &'a str,
+    public: &'a FrostPublicPackage,
+    participants: &'a [String],
+    report: &'a mut dyn FnMut(SigningEvent),
+    sessions_of: Vec<Option<Sender<Vec<SigningCommitment>>>>,
+    indices: BTreeMap<usize, u32>,
+    public_shares: BTreeMap<u32, [u8; 32]>,
+    ready: Vec<(usize, SigningCommitment)>,
+    sessions: Vec<Session>,
+    session_of: BTreeMap<usize, usize>,
+    faulty: BTreeSet<usize>,
+}
+
+impl Coordinator<'_> {
+    /// Excludes a participant for the rest of the run.
+    fn exclude(&mut self, worker: usize, reason: String) {
+        if !self.faulty.insert(worker) {
+            return;
+        }
+        // Dropping its sender stops the participant's connection once it waits for a session.
+        self.sessions_of[worker] = None;
+        self.ready.retain(|(ready, _)| *ready != worker);
+        self.session_of.remove(&worker);
+        (self.report)(SigningEvent::Faulted {
+            address: self.participants[worker].clone(),
+            reason,
+        });
+    }
+
+    fn join(&mut self, worker: usize, info: Response) {
+        let index = match check_info(self.public, &self.participants[worker], &info) {
+            Ok(index) => index,
+            Err(err) => return self.exclude(worker, err.to_string()),
+        };
+        if self.indices.values().any(|joined| *joined == index) {
+            return self.exclude(
+                worker,
+                format!("Participant {} joined more than once", index),
+            );
+        }
+        self.indices.insert(worker, index);
+        (self.report)(SigningEvent::Joined {
+            address: self.participants[worker].clone(),
+            index,
+        });
+    }
+
+    fn commit(&mut self, worker: usize, commitment: SigningCommitment) {
+        if self.indices.get(&worker) != Some(&commitment.index) {
+            return self.exclude(
+                worker,
+                format!("Sent a commitment for participant {}", commitment.index),
+            );
+        }
+        self.ready.push((worker, commitment));
+        (self.report)(SigningEvent::Committed {
+            address: self.participants[worker].clone(),
+        });
+
+        let threshold = self.public.threshold;
+        if self.ready.len() >= threshold as usize {
+            let signers: Vec<_> = self.ready.drain(..threshold as usize).collect();
+            let commitments: Vec<SigningCommitment> = signers
+                .iter()
+                .map(|(_, commitment)| commitment.clone())
+                .collect();
+            let session = self.sessions.len();
+            self.sessions.push(Session {
+                commitments: commitments.clone(),
+                partials: Vec::new(),
+            });
+            (self.report)(SigningEvent::SessionStarted {
+                signers: commitments
+                    .iter()
+                    .map(|commitment| commitment.index)
+                    .collect(),
+            });
+            for (signer, _) in signers {
+                self.session_of.insert(signer, session);
+                if let Some(sessions) = &self.sessions_of[signer] {
+                    let _ = sessions.send(commitments.clone());
+                }
+            }
+        }
+    }
+
+    /// Checks a partial signature and returns the signature once its session is complete.
+    ///
+    /// # Errors
+    /// Returns an error if a complete session does not yield a valid signature
+    /// and no participant can be blamed for it.
+    fn sign(
+        &mut self,
+        worker: usize,
+        partial: PartialSignature,
+    ) -> Result<Option<[u8; 64]>, Box<dyn std::error::Error>> {
+        let Some(session) = self.session_of.remove(&worker) else {
+            return Ok(None);
+        };
+        let group_key = self.public.group_key;
+        let index = self.indices[&worker];
+        let commitments = &self.sessions[session].commitments;
+        let checked = if partial.index != index {
+            Err(format!("Sent a partial signature for participant {}", partial.index).into())
+        } else {
+            rounds::verify_partial(
+                &group_key,
+                self.message.as_bytes(),
+                self.context,
+                commitments,
+                &partial,
+                &self.public_shares[&index],
+            )
+        };
+        if let Err(err) = checked {
+            self.exclude(worker, err.to_string());
+            return Ok(None);
+        }
+
+        (self.report)(SigningEvent::PartialSigned {
+            address: self.participants[worker].clone(),
+        });
+        let session = &mut self.sessions[session];
+        session.partials.push(partial);
+        if session.partials.len() < session.commitments.len() {
+            return Ok(None);
+        }
+        let signature = rounds::aggregate(
+            &group_key,
+            self.message.as_bytes(),
+            self.context,
+            &session.commitments,
+            &session.partials,
+            &self.public_shares,
+        )
+        .and_then(|signature| {
+            verify_signature_with_context(
+                &group_key,
+                self.message.as_bytes(),
+                self.context,
+                &signature,
+            )
+            .map_err(|_| "Aggregated signature does not verify under the group key")?;
+            Ok(signature)
+        });
+        match signature {
+            Ok(signature) => Ok(Some(signature)),
+            Err(err) => {
+                // The session is abandoned, and the signer at fault is excluded so that
+                // the run goes on without it.
+                let blamed = err.downcast_ref::<InvalidShare>().and_then(|invalid| {
+                    self.indices
+                        .iter()
+                        .find(|(_, index)| **index == invalid.participant)
+                        .map(|(worker, _)| *worker)
+                });
+                match blamed {
+                    Some(blamed) => {
+                        self.exclude(blamed, err.to_string());
+                        Ok(None)
+                    }
+                    None => Err(err),
+                }
+            }
+        }
+    }
+
+    /// Returns true while enough participants are left to form a session.
+    fn can_continue(&self) -> bool {
+        self.participants.len() - self.faulty.len() >= self.public.threshold as usize
+    }
+}
+
+/// Runs robust signing with remote participants and returns the threshold signature.
+///
+/// # Arguments
+/// - `message`: The message to be signed.
+/// - `context`: The context the message is signed under, bound into the signature and checked by the participants' policies.
+/// - `public`: The group's public package, which every participant is checked against.
+/// - `participants`: Addresses of the participant daemons invited; more than the threshold may be given.
+/// - `timeout`: How long to wait for a participant to connect or answer before excluding it.
+/// - `report`: Called with every step of the run.
+///
+/// # Errors
+/// Returns an error once fewer participants than the threshold are left, or if
+/// a complete session yields a signature that does not verify.
+pub fn request_signature_robust(
+    message: &str,
+    context: &str,
+    public: &FrostPublicPackage,
+    participants: &[String],
+    timeout: Duration,
+    report: &mut dyn FnMut(SigningEvent),
+) -> Result<[u8; 64], Box<dyn std::error::Error>> {
+    if participants.is_empty() {
+        return Err("No participants given".into());
+    }
+    let (updates, received) = mpsc::channel();
+    let mut sessions_of = Vec::new();
+    for (worker, address) in participants.iter().enumerate() {
+        let (sessions, worker_sessions) = mpsc::channel();
+        sessions_of.push(Some(sessions));
+        let (address, message, context, updates) = (
+            address.clone(),
+            message.to_string(),
+            context.to_string(),
+            updates.clone(),
+        );
+        thread::spawn(move || {
+            run_worker(
+                worker,
+                address,
+                message,
+                context,
+                timeout,
+                updates,
+                worker_sessions,
+            )
+        });
+    }
+    drop(updates);
+
+    let mut coordinator = Coordinator {
+        message,
+        context,
+        public,
+        participants,
+        report,
+        sessions_of,
+        indices: BTreeMap::new(),
+        public_shares: public_shares_of(public),
+        ready: Vec::new(),
+        sessions: Vec::new(),
+        session_of: BTreeMap::new(),
+        faulty: BTreeSet::new(),
+    };
+    while coordinator.can_continue() {
+        let Ok(update) = received.recv() else {
+            break;
+        };
+        match update {
+            Update::Joined { worker, info } => coordinator.join(worker, info),
+            Update::Ready { worker, commitment } => {
+                if !coordinator.faulty.contains(&worker) {
+                    coordinator.commit(worker, commitment)
+                }
+            }
+            Update::Signed { worker, partial } => {
+                if let Some(signature) = coordinator.sign(worker, partial)? {
+                    (coordinator.report)(SigningEvent::Signed(signature));
+                    return Ok(signature);
+                }
+            }
+            Update::Failed { worker, reason } => coordinator.exclude(worker, reason),
+        }
+    }
+
+    let left = participants.len() - coordinator.faulty.len();
+    Err(format!(
+        "No qualified set of signers remains: {} of {} participants are left, fewer than the threshold of {}",
+        left,
+        participants.len(),
+        public.threshold
+    )
+    .into())
+}
+
+/// Signs a message like [`coordinate`](crate::net::coordinate), falling back to
+/// other participants when some fail, and saves the signature.
+///
+/// The participants are checked against the public package in `public_file`.
+/// The index of every participant that joins is added to `signers`.
+///
+/// # Errors
+/// Returns an error once fewer participants than the threshold are left.
+pub fn coordinate_robust(
+    message: &str,
+    context: &str,
+    public_file: &str,
+    participants: &[String],
+    timeout: Duration,
+    signature_file: &str,
+    signers: &mut Vec<u32>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let public = load_public_package(public_file)?;
+    let signature = request_signature_robust(
+        message,
+        context,
+        &public,
+        participants,
+        timeout,
+        &mut |event| match event {
+            SigningEvent::Joined { index, .. } => signers.push(index),
+            SigningEvent::Faulted { address, reason } => {
+                eprintln!("Participant at {} excluded: {}", address, reason)
+            }
+            SigningEvent::SessionStarted { signers } => {
+                println!("Signing with participants {:?}", signers)
+            }
+            _ => {}
+        },
+    )?;
+
+    files::write_json(signature_file, &signature.to_vec())?;
+
+    println!("Threshold signature saved to: {}", signature_file);
+    Ok(())
+}
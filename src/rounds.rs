@@ -0,0 +1,414 @@
+//! Round-by-round threshold signing with serializable messages.
+//!
+//! [`sign_message`](crate::sign_message) runs every signer in one process through
+//! frost-dalek's `SignatureAggregator`, whose partial signatures cannot be taken out
+//! of the process. The functions here split signing into its rounds so that every
+//! message exchanged between signers and the aggregator is plain bytes:
+//!
+//! 1. Each signer calls [`commit`] and publishes its [`SigningCommitment`].
+//! 2. Each signer calls [`sign_share`] with the full list of commitments and sends
+//!    back its [`PartialSignature`].
+//! 3. The aggregator calls [`aggregate`], which checks every partial signature and
+//!    returns the 64-byte threshold signature, verifiable with
+//!    [`validate_signature_with_context`](crate::validate_signature_with_context).
+//!
+//! Every round takes the application context the message is signed under, which
+//! is bound into the message hash with [`signing_context`].
+
+use crate::policy::DEFAULT_CONTEXT;
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_TABLE;
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::Identity;
+use frost_dalek::compute_message_hash;
+use frost_dalek::signature::SecretKey as SignatureSecretKey;
+use rand::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha512};
+use std::collections::BTreeMap;
+use std::fmt;
+use zeroize::Zeroize;
+
+/// Context string mixed into every message hash, shared with `sign_message`.
+pub const SIGNING_CONTEXT: &[u8] = b"THRESHOLD SIGNING CONTEXT";
+
+/// Domain tag that starts the hash input of a message signed under a non-default context.
+///
+/// It is not a prefix of [`SIGNING_CONTEXT`], nor the other way round, so no
+/// default-context message can be chosen to hash like a bound one.
+pub const BOUND_SIGNING_CONTEXT: &[u8] = b"THRESHOLD SIGNING BOUND CONTEXT";
+
+/// Returns the bytes a message signed under the application `context` is hashed with.
+///
+/// The default context hashes with [`SIGNING_CONTEXT`] alone, so signatures made
+/// before contexts were bound still verify. Any other context is encoded as
+/// [`BOUND_SIGNING_CONTEXT`] `|| 0x00 || u32_be(len) || context`; the message is
+/// appended to these bytes without a length, so the length prefix is what keeps
+/// `("a", ":b" || m)` and `("a:b", m)` apart, and a signature made under one
+/// context does not verify under another.
+pub fn signing_context(context: &str) -> Vec<u8> {
+    if context == DEFAULT_CONTEXT {
+        return SIGNING_CONTEXT.to_vec();
+    }
+    let mut bytes = Vec::with_capacity(BOUND_SIGNING_CONTEXT.len() + 5 + context.len());
+    bytes.extend_from_slice(BOUND_SIGNING_CONTEXT);
+    bytes.push(0);
+    bytes.extend_from_slice(&(context.len() as u32).to_be_bytes());
+    bytes.extend_from_slice(context.as_bytes());
+    bytes
+}
+
+/// The public commitment a signer publishes in the first round.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SigningCommitment {
+    pub index: u32,
+    pub hiding: [u8; 32],
+    pub binding: [u8; 32],
+}
+
+/// The secret nonces behind a [`SigningCommitment`]. They must be used for a single signature.
+pub struct SigningNonces {
+    index: u32,
+    hiding: Scalar,
+    binding: Scalar,
+}
+
+impl SigningNonces {
+    /// Returns the index of the participant these nonces belong to.
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// Returns the commitment to these nonces.
+    pub fn commitment(&self) -> SigningCommitment {
+        SigningCommitment {
+            index: self.index,
+            hiding: (&self.hiding * &RISTRETTO_BASEPOINT_TABLE)
+                .compress()
+                .to_bytes(),
+            binding: (&self.binding * &RISTRETTO_BASEPOINT_TABLE)
+                .compress()
+                .to_bytes(),
+        }
+    }
+}
+
+impl Drop for SigningNonces {
+    fn drop(&mut self) {
+        self.hiding.zeroize();
+        self.binding.zeroize();
+    }
+}
+
+/// A signer's share of the threshold signature, produced in the second round.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PartialSignature {
+    pub index: u32,
+    pub z: [u8; 32],
+}
+
+/// A signer whose partial signature is missing, malformed or invalid, as reported by [`aggregate`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InvalidShare {
+    /// The participant at fault.
+    pub participant: u32,
+    /// What is wrong with its partial signature.
+    pub reason: String,
+}
+
+impl fmt::Display for InvalidShare {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} from participant {}", self.reason, self.participant)
+    }
+}
+
+impl std::error::Error for InvalidShare {}
+
+fn invalid_share(participant: u32, reason: &str) -> Box<dyn std::error::Error> {
+    Box::new(InvalidShare {
+        participant,
+        reason: reason.to_string(),
+    })
+}
+
+/// Generates fresh signing nonces and their public commitment for participant `index`.
+pub fn commit<R: RngCore + CryptoRng>(
+    csprng: &mut R,
+    index: u32,
+) -> (SigningNonces, SigningCommitment) {
+    let nonces = SigningNonces {
+        index,
+        hiding: Scalar::random(csprng),
+        binding: Scalar::random(csprng),
+    };
+    let commitment = nonces.commitment();
+    (nonces, commitment)
+}
+
+/// Computes a signer's partial signature over `message`, signed under the application `context`.
+///
+/// The nonces are consumed so they can never sign twice. `commitments` must hold
+/// the commitment of every signer taking part, including this one.
+///
+/// # Errors
+/// Returns an error if the nonces do not belong to `secret_key`, if this signer's
+/// commitment is missing or does not match its nonces, or if any commitment is malformed.
+pub fn sign_share(
+    secret_key: &SignatureSecretKey,
+    group_key: &[u8; 32],
+    nonces: SigningNonces,
+    message: &[u8],
+    context: &str,
+    commitments: &[SigningCommitment],
+) -> Result<PartialSignature, Box<dyn std::error::Error>> {
+    let index = secret_key.get_index();
+    if nonces.index != index {
+        return Err(format!(
+            "Nonces belong to participant {}, not participant {}",
+            nonces.index, index
+        )
+        .into());
+    }
+
+    let commitments = sorted_commitments(commitments)?;
+    let own_commitment = (
+        &nonces.hiding * &RISTRETTO_BASEPOINT_TABLE,
+        &nonces.binding * &RISTRETTO_BASEPOINT_TABLE,
+    );
+    if commitments.get(&index) != Some(&own_commitment) {
+        return Err(format!("Commitment of participant {} is missing or altered", index).into());
+    }
+
+    let group_key = decode_point(group_key).ok_or("Invalid group public key")?;
+    let message_hash = compute_message_hash(&signing_context(context), message);
+    let (binding_factors, group_commitment) = group_commitment(&message_hash, &commitments)?;
+    let challenge = compute_challenge(&message_hash, &group_key, &group_commitment);
+    let lambda = lagrange_coefficient(index, commitments.keys());
+
+    let (key_bytes, _) = secret_key.to_bytes();
+    let key = Scalar::from_canonical_bytes(key_bytes).ok_or("Invalid private key bytes")?;
+
+    let z = nonces.hiding + nonces.binding * binding_factors[&index] + lambda * key * challenge;
+    Ok(PartialSignature {
+        index,
+        z: z.to_bytes(),
+    })
+}
+
+/// Checks every partial signature and combines them into a threshold signature.
+///
+/// `public_shares` maps each signer's index to its public key share, used to
+/// check that signer's partial signature before it is included.
+///
+/// # Errors
+/// Returns an [`InvalidShare`] naming the first signer whose partial signature is
+/// missing or invalid.
+pub fn aggregate(
+    group_key: &[u8; 32],
+    message: &[u8],
+    context: &str,
+    commitments: &[SigningCommitment],
+    partial_signatures: &[PartialSignature],
+    public_shares: &BTreeMap<u32, [u8; 32]>,
+) -> Result<[u8; 64], Box<dyn std::error::Error>> {
+    let commitments = sorted_commitments(commitments)?;
+    let group_key = decode_point(group_key).ok_or("Invalid group public key")?;
+    let message_hash = compute_message_hash(&signing_context(context), message);
+    let (binding_factors, group_commitment) = group_commitment(&message_hash, &commitments)?;
+    let challenge = compute_challenge(&message_hash, &group_key, &group_commitment);
+
+    let mut partials = BTreeMap::new();
+    for partial in partial_signatures {
+        if partials.insert(partial.index, partial).is_some() {
+            return Err(format!(
+                "Duplicate partial signature from participant {}",
+                partial.index
+            )
+            .into());
+        }
+    }
+
+    let mut z = Scalar::zero();
+    for (index, commitment) in &commitments {
+        let partial = partials
+            .get(index)
+            .ok_or_else(|| invalid_share(*index, "Missing partial signature"))?;
+        let lambda = lagrange_coefficient(*index, commitments.keys());
+        z += check_partial(
+            partial,
+            commitment,
+            &binding_factors[index],
+            public_shares.get(index),
+            &(lambda * challenge),
+        )?;
+    }
+    if partials.len() != commitments.len() {
+        return Err("Partial signature from a participant without a commitment".into());
+    }
+
+    let mut signature = [0u8; 64];
+    signature[..32].copy_from_slice(group_commitment.compress().as_bytes());
+    signature[32..].copy_from_slice(z.as_bytes());
+    Ok(signature)
+}
+
+/// Checks a single partial signature against the commitments of its signing session.
+///
+/// Lets an aggregator find a faulty signer as soon as its partial signature
+/// arrives, rather than when the last one does.
+///
+/// # Errors
+/// Returns an [`InvalidShare`] if the partial signature is malformed or invalid,
+/// or an error if its signer has no commitment in `commitments`.
+pub fn verify_partial(
+    group_key: &[u8; 32],
+    message: &[u8],
+    context: &str,
+    commitments: &[SigningCommitment],
+    partial: &PartialSignature,
+    public_share: &[u8; 32],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let commitments = sorted_commitments(commitments)?;
+    let group_key = decode_point(group_key).ok_or("Invalid group public key")?;
+    let message_hash = compute_message_hash(&signing_context(context), message);
+    let (binding_factors, group_commitment) = group_commitment(&message_hash, &commitments)?;
+    let challenge = compute_challenge(&message_hash, &group_key, &group_commitment);
+
+    let commitment = commitments.get(&partial.index).ok_or_else(|| {
+        format!(
+            "Participant {} has no commitment in this session",
+            partial.index
+        )
+    })?;
+    let lambda = lagrange_coefficient(partial.index, commitments.keys());
+    check_partial(
+        partial,
+        commitment,
+        &binding_factors[&partial.index],
+        Some(public_share),
+        &(lambda * challenge),
+    )?;
+    Ok(())
+}
+
+/// Checks one signer's partial signature and returns it as a scalar.
+fn check_partial(
+    partial: &PartialSignature,
+    commitment: &(RistrettoPoint, RistrettoPoint),
+    binding_factor: &Scalar,
+    public_share: Option<&[u8; 32]>,
+    lambda_challenge: &Scalar,
+) -> Result<Scalar, Box<dyn std::error::Error>> {
+    let index = partial.index;
+    let z_i = Scalar::from_canonical_bytes(partial.z)
+        .ok_or_else(|| invalid_share(index, "Malformed partial signature"))?;
+    let public_share = public_share
+        .and_then(decode_point)
+        .ok_or_else(|| format!("Missing public key share for participant {}", index))?;
+
+    // z_i * G must equal D_i + rho_i * E_i + lambda_i * c * Y_i.
+    let expected = commitment.0 + commitment.1 * binding_factor + public_share * lambda_challenge;
+    if &z_i * &RISTRETTO_BASEPOINT_TABLE != expected {
+        return Err(invalid_share(index, "Invalid partial signature"));
+    }
+    Ok(z_i)
+}
+
+/// Returns the public key share of `secret_key` in compressed form.
+pub fn public_share(secret_key: &SignatureSecretKey) -> [u8; 32] {
+    secret_key.to_public().share.compress().to_bytes()
+}
+
+fn decode_point(bytes: &[u8; 32]) -> Option<RistrettoPoint> {
+    CompressedRistretto(*bytes).decompress()
+}
+
+/// Decodes the commitments and orders them by participant index.
+fn sorted_commitments(
+    commitments: &[SigningCommitment],
+) -> Result<BTreeMap<u32, (RistrettoPoint, RistrettoPoint)>, Box<dyn std::error::Error>> {
+    let mut sorted = BTreeMap::new();
+    for commitment in commitments {
+        let hiding = decode_point(&commitment.hiding);
+        let binding = decode_point(&commitment.binding);
+        let (hiding, binding) = hiding
+            .zip(binding)
+            .ok_or_else(|| format!("Malformed commitment from participant {}", commitment.index))?;
+        if sorted.insert(commitment.index, (hiding, binding)).is_some() {
+            return Err(
+                format!("Duplicate commitment from participant {}", commitment.index).into(),
+            );
+        }
+    }
+    Ok(sorted)
+}
+
+/// Computes every signer's binding factor and the group commitment `R`.
+fn group_commitment(
+    message_hash: &[u8; 64],
+    commitments: &BTreeMap<u32, (RistrettoPoint, RistrettoPoint)>,
+) -> Result<(BTreeMap<u32, Scalar>, RistrettoPoint), Box<dyn std::error::Error>> {
+    if commitments.is_empty() {
+        return Err("No signing commitments".into());
+    }
+
+    let mut transcript = Sha512::new();
+    transcript.update(b"FROST-CLI-BINDING");
+    transcript.update(&message_hash[..]);
+    for (index, (hiding, binding)) in commitments {
+        transcript.update(index.to_be_bytes());
+        transcript.update(hiding.compress().as_bytes());
+        transcript.update(binding.compress().as_bytes());
+    }
+
+    let mut binding_factors = BTreeMap::new();
+    let mut group_commitment = RistrettoPoint::identity();
+    for (index, (hiding, binding)) in commitments {
+        let mut h = transcript.clone();
+        h.update(index.to_be_bytes());
+        let binding_factor = Scalar::from_hash(h);
+        group_commitment += hiding + binding * binding_factor;
+        binding_factors.insert(*index, binding_factor);
+    }
+    Ok((binding_factors, group_commitment))
+}
+
+/// Computes the Schnorr challenge exactly as frost-dalek's `ThresholdSignature::verify` does.
+fn compute_challenge(
+    message_hash: &[u8; 64],
+    group_key: &RistrettoPoint,
+    group_commitment: &RistrettoPoint,
+) -> Scalar {
+    let mut h = Sha512::new();
+    h.update(b"FROST-SHA512");
+    h.update(group_commitment.compress().as_bytes());
+    h.update(group_key.compress().as_bytes());
+    h.update(&message_hash[..]);
+    Scalar::from_hash(h)
+}
+
+/// Computes the Lagrange coefficient of `index` at zero over the given signer indices.
+pub(crate) fn lagrange_coefficient<'a>(
+    index: u32,
+    indices: impl IntoIterator<Item = &'a u32>,
+) -> Scalar {
+    lagrange_coefficient_at(0, index, indices)
+}
+
+/// Computes the Lagrange coefficient of `index` at `x` over the given indices.
+pub(crate) fn lagrange_coefficient_at<'a>(
+    x: u32,
+    index: u32,
+    indices: impl IntoIterator<Item = &'a u32>,
+) -> Scalar {
+    let mut numerator = Scalar::one();
+    let mut denominator = Scalar::one();
+    for &j in indices {
+        if j == index {
+            continue;
+        }
+        numerator *= Scalar::from(x) - Scalar::from(j);
+        denominator *= Scalar::from(index) - Scalar::from(j);
+    }
+    numerator * denominator.invert()
+}
@@ -0,0 +1,234 @@
+//! Handling of secret material in memory: zeroizing on drop and optional locking.
+//!
+//! Every type holding a private share, nonce or polynomial coefficient overwrites
+//! it with zeros when dropped, so no copy is left behind in freed memory. Values
+//! held in a [`Secret`] are additionally locked into RAM with `mlock` once
+//! [`set_memory_locking`] is enabled (the CLI's `--mlock`), so that they are never
+//! written to swap. Lists of secrets live in a [`SecretVec`], which is allocated
+//! once and never grows, so no reallocation leaves a copy behind. Locking is
+//! best effort: if the process may not lock more memory, a warning is printed
+//! once and the value stays unlocked.
+//!
+//! `mlock` works on whole pages, and several values may share a page. Locks are
+//! therefore counted per page, and a page is only unlocked once every value on
+//! it has been unlocked. Unlocking a value that was never locked does nothing,
+//! so types that are only sometimes locked may unlock themselves unconditionally.
+
+#[cfg(unix)]
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(unix)]
+use std::sync::{Mutex, PoisonError};
+use zeroize::Zeroize;
+
+static LOCK_MEMORY: AtomicBool = AtomicBool::new(false);
+static LOCK_WARNED: AtomicBool = AtomicBool::new(false);
+
+/// Enables or disables locking of secret values into RAM.
+pub fn set_memory_locking(enabled: bool) {
+    LOCK_MEMORY.store(enabled, Ordering::Relaxed);
+}
+
+/// Returns `true` if secret values are locked into RAM.
+pub fn memory_locking() -> bool {
+    LOCK_MEMORY.load(Ordering::Relaxed)
+}
+
+/// Locks the memory of `value` into RAM if locking is enabled.
+pub(crate) fn lock<T: ?Sized>(value: &T) {
+    if !memory_locking() || std::mem::size_of_val(value) == 0 {
+        return;
+    }
+    if !mlock(value) && !LOCK_WARNED.swap(true, Ordering::Relaxed) {
+        eprintln!("Warning: could not lock secret memory; raise the memlock limit to lock secrets");
+    }
+}
+
+/// Unlocks memory locked by [`lock`]. Does nothing if `value` was not locked.
+pub(crate) fn unlock<T: ?Sized>(value: &T) {
+    if memory_locking() && std::mem::size_of_val(value) != 0 {
+        munlock(value);
+    }
+}
+
+/// The locked pages and values of the process.
+#[cfg(unix)]
+struct Locks {
+    /// How many locked values lie on each page, by page address.
+    pages: BTreeMap<usize, usize>,
+    /// How many times each value is locked, by address and size.
+    values: BTreeMap<(usize, usize), usize>,
+}
+
+#[cfg(unix)]
+static LOCKS: Mutex<Locks> = Mutex::new(Locks {
+    pages: BTreeMap::new(),
+    values: BTreeMap::new(),
+});
+
+/// Returns the address and size of `value`.
+#[cfg(unix)]
+fn range<T: ?Sized>(value: &T) -> (usize, usize) {
+    (
+        value as *const T as *const u8 as usize,
+        std::mem::size_of_val(value),
+    )
+}
+
+/// Returns the page size and the address of every page `value` lies on.
+#[cfg(unix)]
+fn pages<T: ?Sized>(value: &T) -> (usize, impl Iterator<Item = usize>) {
+    // SAFETY: sysconf has no preconditions.
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+    let (start, size) = range(value);
+    let end = start + size;
+    let first = start - start % page_size;
+    (page_size, (first..end).step_by(page_size))
+}
+
+#[cfg(unix)]
+fn mlock<T: ?Sized>(value: &T) -> bool {
+    let mut locks = LOCKS.lock().unwrap_or_else(PoisonError::into_inner);
+    *locks.values.entry(range(value)).or_insert(0) += 1;
+    let (page_size, pages) = pages(value);
+    let mut locked = true;
+    for page in pages {
+        let count = locks.pages.entry(page).or_insert(0);
+        if *count == 0 {
+            // SAFETY: the page holds part of a live value, so it is mapped.
+            locked &= unsafe { libc::mlock(page as *const libc::c_void, page_size) == 0 };
+        }
+        *count += 1;
+    }
+    locked
+}
+
+#[cfg(unix)]
+fn munlock<T: ?Sized>(value: &T) {
+    let mut locks = LOCKS.lock().unwrap_or_else(PoisonError::into_inner);
+    let Some(count) = locks.values.get_mut(&range(value)) else {
+        return;
+    };
+    *count -= 1;
+    if *count == 0 {
+        locks.values.remove(&range(value));
+    }
+    let (page_size, pages) = pages(value);
+    for page in pages {
+        let Some(count) = locks.pages.get_mut(&page) else {
+            continue;
+        };
+        *count -= 1;
+        if *count == 0 {
+            locks.pages.remove(&page);
+            // SAFETY: the page holds part of a live value, so it is mapped.
+            unsafe {
+                libc::munlock(page as *const libc::c_void, page_size);
+            }
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn mlock<T: ?Sized>(_value: &T) -> bool {
+    false
+}
+
+#[cfg(not(unix))]
+fn munlock<T: ?Sized>(_value: &T) {}
+
+/// A secret value on the heap, locked into RAM if enabled and zeroized on drop.
+///
+/// The value is boxed so that it keeps one address for its whole life and is
+/// never copied around by moves of the `Secret`.
+pub struct Secret<T: Zeroize>(Box<T>);
+
+impl<T: Zeroize> Secret<T> {
+    pub fn new(value: T) -> Self {
+        let secret = Secret(Box::new(value));
+        lock(&*secret.0);
+        secret
+    }
+}
+
+impl<T: Zeroize> Deref for Secret<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Zeroize> DerefMut for Secret<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: Zeroize> Drop for Secret<T> {
+    fn drop(&mut self) {
+        self.0.zeroize();
+        unlock(&*self.0);
+    }
+}
+
+impl<T: Zeroize> fmt::Debug for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Secret([REDACTED])")
+    }
+}
+
+/// A buffer of secret values, allocated once and locked into RAM if enabled.
+///
+/// The buffer never grows, so no reallocation leaves a freed copy of its values
+/// behind. It holds values that zeroize themselves when dropped, such as
+/// frost-dalek's signing keys and nonces: zeroizing the buffer drops them in
+/// place. Hold it in a [`Secret`] so that it is zeroized when dropped.
+pub struct SecretVec<T>(Vec<T>);
+
+impl<T> SecretVec<T> {
+    /// Allocates room for exactly `capacity` values.
+    pub fn with_capacity(capacity: usize) -> Self {
+        let mut values = Vec::with_capacity(capacity);
+        lock(&*values.spare_capacity_mut());
+        SecretVec(values)
+    }
+
+    /// Appends `value` to the buffer.
+    ///
+    /// # Panics
+    /// Panics if the buffer is full, rather than reallocating it.
+    pub fn push(&mut self, value: T) {
+        assert!(self.0.len() < self.0.capacity(), "Secret buffer is full");
+        self.0.push(value);
+    }
+}
+
+impl<T> Deref for SecretVec<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for SecretVec<T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        &mut self.0
+    }
+}
+
+impl<T> Zeroize for SecretVec<T> {
+    fn zeroize(&mut self) {
+        self.0.clear();
+    }
+}
+
+impl<T> Drop for SecretVec<T> {
+    fn drop(&mut self) {
+        self.0.clear();
+        unlock(&*self.0.spare_capacity_mut());
+    }
+}
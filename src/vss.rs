@@ -0,0 +1,166 @@
+//! Feldman verifiable secret sharing, used to move shares around after key generation.
+//!
+//! A holder deals a secret by sampling a polynomial with that secret as constant
+//! term, publishing commitments to its coefficients and sending every recipient
+//! the polynomial evaluated at the recipient's index in a [`SharePackage`]. The
+//! recipient checks its sub-share against the commitments before using it.
+
+use crate::integrity::evaluate_commitments;
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_TABLE;
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use rand::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::BufReader;
+use zeroize::Zeroize;
+
+/// A sub-share dealt by one holder to one recipient, with the dealer's commitments.
+///
+/// The `sub_share` is secret and must only be delivered to participant `to`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SharePackage {
+    pub from: u32,
+    pub to: u32,
+    pub commitments: Vec<[u8; 32]>,
+    pub sub_share: [u8; 32],
+}
+
+impl Drop for SharePackage {
+    fn drop(&mut self) {
+        self.sub_share.zeroize();
+    }
+}
+
+/// A polynomial over the scalar field, lowest degree coefficient first.
+pub(crate) struct Polynomial(Vec<Scalar>);
+
+impl Drop for Polynomial {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl Polynomial {
+    /// Samples a polynomial with `t` coefficients and the given constant term.
+    pub(crate) fn random<R: RngCore + CryptoRng>(csprng: &mut R, constant: Scalar, t: u32) -> Self {
+        let mut coefficients = vec![constant];
+        for _ in 1..t {
+            coefficients.push(Scalar::random(csprng));
+        }
+        Polynomial(coefficients)
+    }
+
+    pub(crate) fn evaluate(&self, index: u32) -> Scalar {
+        let x = Scalar::from(index);
+        self.0
+            .iter()
+            .rev()
+            .fold(Scalar::zero(), |acc, coefficient| acc * x + coefficient)
+    }
+
+    pub(crate) fn commitments(&self) -> Vec<[u8; 32]> {
+        self.0
+            .iter()
+            .map(|coefficient| {
+                (coefficient * &RISTRETTO_BASEPOINT_TABLE)
+                    .compress()
+                    .to_bytes()
+            })
+            .collect()
+    }
+
+    /// Deals the polynomial to every recipient in `recipients`.
+    pub(crate) fn deal(
+        &self,
+        from: u32,
+        recipients: impl IntoIterator<Item = u32>,
+    ) -> Vec<SharePackage> {
+        let commitments = self.commitments();
+        recipients
+            .into_iter()
+            .map(|to| SharePackage {
+                from,
+                to,
+                commitments: commitments.clone(),
+                sub_share: self.evaluate(to).to_bytes(),
+            })
+            .collect()
+    }
+}
+
+/// Checks a package addressed to `recipient` and returns its sub-share and decoded commitments.
+///
+/// # Errors
+/// Returns an error if the package is for someone else, does not have `t`
+/// commitments, or its sub-share does not match the dealer's commitments.
+pub(crate) fn open_package(
+    package: &SharePackage,
+    recipient: u32,
+    t: u32,
+) -> Result<(Scalar, Vec<RistrettoPoint>), Box<dyn std::error::Error>> {
+    if package.to != recipient {
+        return Err(format!(
+            "Package from participant {} is addressed to participant {}, not {}",
+            package.from, package.to, recipient
+        )
+        .into());
+    }
+    if package.commitments.len() != t as usize {
+        return Err(format!(
+            "Package from participant {} has {} commitments instead of {}",
+            package.from,
+            package.commitments.len(),
+            t
+        )
+        .into());
+    }
+
+    let commitments = decode_points(&package.commitments).ok_or_else(|| {
+        format!(
+            "Package from participant {} has a malformed commitment",
+            package.from
+        )
+    })?;
+    let sub_share = Scalar::from_canonical_bytes(package.sub_share).ok_or_else(|| {
+        format!(
+            "Package from participant {} has a malformed sub-share",
+            package.from
+        )
+    })?;
+    if &sub_share * &RISTRETTO_BASEPOINT_TABLE != evaluate_commitments(&commitments, recipient) {
+        return Err(format!(
+            "Sub-share from participant {} does not match its commitments",
+            package.from
+        )
+        .into());
+    }
+    Ok((sub_share, commitments))
+}
+
+pub(crate) fn decode_points(points: &[[u8; 32]]) -> Option<Vec<RistrettoPoint>> {
+    points
+        .iter()
+        .map(|bytes| CompressedRistretto(*bytes).decompress())
+        .collect()
+}
+
+pub(crate) fn encode_points(points: &[RistrettoPoint]) -> Vec<[u8; 32]> {
+    points
+        .iter()
+        .map(|point| point.compress().to_bytes())
+        .collect()
+}
+
+/// Loads share packages from files.
+pub fn load_packages(
+    package_files: &[String],
+) -> Result<Vec<SharePackage>, Box<dyn std::error::Error>> {
+    package_files
+        .iter()
+        .map(|path| {
+            let file = File::open(path)?;
+            Ok(serde_json::from_reader(BufReader::new(file))?)
+        })
+        .collect()
+}
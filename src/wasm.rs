@@ -0,0 +1,142 @@
+//! WebAssembly bindings for verifying signatures and signing with a browser-held share.
+//!
+//! Built with the `wasm` feature, e.g. `wasm-pack build --target web -- --features wasm`.
+//! All inputs and outputs use the JSON formats of the CLI's files and of the
+//! messages in [`rounds`], so a browser can take part in a signing round run by
+//! `coordinate` or any other aggregator:
+//!
+//! 1. [`commit`] takes the participant's share file and returns a [`Nonces`]
+//!    handle whose [`Nonces::commitment`] is sent to the aggregator.
+//! 2. [`sign_share`] takes the share, the handle, the message and the list of all
+//!    commitments and returns the partial signature. The nonces never leave the
+//!    WebAssembly memory and are zeroized once used.
+//! 3. [`verify`] checks the aggregated signature against a public package or key file.
+
+use crate::policy::DEFAULT_CONTEXT;
+use crate::rfc9591::{self, SuiteKeys};
+use crate::rounds::{self, SigningCommitment};
+use crate::{integrity, verify_signature, FrostShare};
+use frost_dalek::signature::SecretKey as SignatureSecretKey;
+use rand::rngs::OsRng;
+use serde::Deserialize;
+use wasm_bindgen::prelude::*;
+use zeroize::Zeroize;
+
+/// The group key of a public package, share or frost-dalek key file.
+#[derive(Deserialize)]
+struct GroupKeyOnly {
+    group_key: [u8; 32],
+}
+
+fn js_error(err: impl std::fmt::Display) -> JsError {
+    JsError::new(&err.to_string())
+}
+
+fn parse_share(share_json: &str) -> Result<FrostShare, JsError> {
+    let share: FrostShare = serde_json::from_str(share_json).map_err(js_error)?;
+    integrity::check_share(&share)
+        .map_err(|err| js_error(format!("Share is corrupted: {}", err)))?;
+    Ok(share)
+}
+
+fn secret_key(share: &FrostShare) -> Result<SignatureSecretKey, JsError> {
+    let (mut key_bytes, index) = share.private_share;
+    let secret_key = SignatureSecretKey::from_bytes(index, key_bytes);
+    key_bytes.zeroize();
+    secret_key.map_err(|_| js_error("Invalid private key bytes"))
+}
+
+/// Verifies a threshold signature.
+///
+/// # Arguments
+/// - `message`: The signed message.
+/// - `public_json`: A public package, share or key file of any ciphersuite.
+/// - `signature_json`: A signature file.
+///
+/// # Errors
+/// Throws if the signature is invalid or an input does not parse.
+#[wasm_bindgen]
+pub fn verify(message: &str, public_json: &str, signature_json: &str) -> Result<(), JsError> {
+    let signature: Vec<u8> = serde_json::from_str(signature_json).map_err(js_error)?;
+    let value: serde_json::Value = serde_json::from_str(public_json).map_err(js_error)?;
+    if value.get("ciphersuite").is_some() {
+        let keys: SuiteKeys = serde_json::from_value(value).map_err(js_error)?;
+        let suite = keys.suite().map_err(js_error)?;
+        return rfc9591::verify_suite(suite, &keys.group_key, message.as_bytes(), &signature)
+            .map_err(js_error);
+    }
+
+    let public: GroupKeyOnly = serde_json::from_value(value).map_err(js_error)?;
+    let signature: [u8; 64] = signature
+        .try_into()
+        .map_err(|_| js_error("Invalid length for threshold signature"))?;
+    verify_signature(&public.group_key, message.as_bytes(), &signature).map_err(js_error)
+}
+
+/// The secret nonces of one signing round, kept inside WebAssembly memory.
+#[wasm_bindgen]
+pub struct Nonces {
+    nonces: Option<rounds::SigningNonces>,
+    commitment: SigningCommitment,
+}
+
+#[wasm_bindgen]
+impl Nonces {
+    /// Returns the commitment to send to the aggregator, as JSON.
+    pub fn commitment(&self) -> Result<String, JsError> {
+        serde_json::to_string(&self.commitment).map_err(js_error)
+    }
+
+    /// Returns `true` once the nonces have signed and can no longer be used.
+    pub fn used(&self) -> bool {
+        self.nonces.is_none()
+    }
+}
+
+/// Generates fresh nonces for the participant holding `share_json`, for a single signature.
+#[wasm_bindgen]
+pub fn commit(share_json: &str) -> Result<Nonces, JsError> {
+    let share = parse_share(share_json)?;
+    let (nonces, commitment) = rounds::commit(&mut OsRng, share.private_share.1);
+    Ok(Nonces {
+        nonces: Some(nonces),
+        commitment,
+    })
+}
+
+/// Computes the partial signature of the participant holding `share_json`.
+///
+/// # Arguments
+/// - `share_json`: The participant's share file.
+/// - `nonces`: The handle returned by [`commit`]; it is used up by this call.
+/// - `message`: The message to sign.
+/// - `commitments_json`: A JSON array with the commitments of all signers, including this one.
+///
+/// # Errors
+/// Throws if the nonces were already used or the commitments do not include them.
+#[wasm_bindgen]
+pub fn sign_share(
+    share_json: &str,
+    nonces: &mut Nonces,
+    message: &str,
+    commitments_json: &str,
+) -> Result<String, JsError> {
+    let share = parse_share(share_json)?;
+    let commitments: Vec<SigningCommitment> =
+        serde_json::from_str(commitments_json).map_err(js_error)?;
+    let signing_nonces = nonces
+        .nonces
+        .take()
+        .ok_or_else(|| js_error("These nonces have already been used to sign"))?;
+
+    let partial = rounds::sign_share(
+        &secret_key(&share)?,
+        &share.group_key,
+        signing_nonces,
+        message.as_bytes(),
+        DEFAULT_CONTEXT,
+        &commitments,
+    )
+    .map_err(js_error)?;
+    serde_json::to_string(&partial).map_err(js_error)
+}
@@ -0,0 +1,69 @@
+// Tests for frost_cli::ffi, run with `cargo test --features ffi`. They check
+// the committed C header against the one build.rs generates, then compile and
+// run tests/ffi/roundtrip.c against the shared library with the system's `cc`.
+#![cfg(all(unix, feature = "ffi"))]
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// The header generated by build.rs for this build.
+const GENERATED_HEADER: &str = include_str!(concat!(env!("OUT_DIR"), "/frost_cli.h"));
+
+/// Returns the directories cargo puts the library's shared object in.
+fn library_dirs() -> Vec<PathBuf> {
+    let deps = env::current_exe().unwrap().parent().unwrap().to_path_buf();
+    let profile = deps.parent().unwrap().to_path_buf();
+    vec![deps, profile]
+}
+
+#[test]
+fn test_ffi_header_is_current() {
+    let committed =
+        fs::read_to_string(Path::new(env!("CARGO_MANIFEST_DIR")).join("include/frost_cli.h"))
+            .unwrap();
+    assert!(
+        committed == GENERATED_HEADER,
+        "include/frost_cli.h is out of date; run `cargo run --example write_header --features ffi`"
+    );
+}
+
+#[test]
+fn test_ffi_roundtrip() {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let program = Path::new(env!("CARGO_TARGET_TMPDIR")).join("roundtrip");
+    let library_dirs = library_dirs();
+
+    let mut compile = Command::new(env::var("CC").unwrap_or_else(|_| "cc".to_string()));
+    compile
+        .arg(manifest_dir.join("tests/ffi/roundtrip.c"))
+        .arg("-I")
+        .arg(manifest_dir.join("include"));
+    for dir in &library_dirs {
+        compile.arg("-L").arg(dir);
+    }
+    let output = compile
+        .args(["-lfrost_cli", "-o"])
+        .arg(&program)
+        .output()
+        .expect("Failed to run the C compiler");
+    assert!(
+        output.status.success(),
+        "Failed to compile roundtrip.c: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let library_path = env::join_paths(&library_dirs).unwrap();
+    let output = Command::new(&program)
+        .env("LD_LIBRARY_PATH", &library_path)
+        .env("DYLD_LIBRARY_PATH", &library_path)
+        .output()
+        .expect("Failed to run the C round trip");
+    assert!(
+        output.status.success(),
+        "C round trip failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(String::from_utf8_lossy(&output.stdout).contains("C API round trip passed"));
+}
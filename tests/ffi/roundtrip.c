@@ -0,0 +1,113 @@
+/*
+ * Round trip through the C API: key generation, signing in one call and round
+ * by round, and verification.
+ *
+ *   cargo build --features ffi
+ *   cc tests/ffi/roundtrip.c -Iinclude -Ltarget/debug -lfrost_cli -o target/roundtrip
+ *   LD_LIBRARY_PATH=target/debug ./target/roundtrip
+ */
+#include <stdio.h>
+#include <string.h>
+
+#include "frost_cli.h"
+
+#define CHECK(call)                                                              \
+  do {                                                                           \
+    FrostStatus status = (call);                                                 \
+    if (status != FROST_STATUS_OK) {                                             \
+      fprintf(stderr, "%s:%d: %s failed with status %d: %s\n", __FILE__,       \
+              __LINE__, #call, status, frost_last_error());                      \
+      return 1;                                                                  \
+    }                                                                            \
+  } while (0)
+
+#define EXPECT(condition)                                                        \
+  do {                                                                           \
+    if (!(condition)) {                                                          \
+      fprintf(stderr, "%s:%d: expected %s\n", __FILE__, __LINE__, #condition);  \
+      return 1;                                                                  \
+    }                                                                            \
+  } while (0)
+
+/* Joins two JSON values into a JSON array. */
+static void json_pair(char *out, size_t size, const FrostBuffer *first, const FrostBuffer *second) {
+  snprintf(out, size, "[%.*s,%.*s]", (int)first->len, (const char *)first->data,
+           (int)second->len, (const char *)second->data);
+}
+
+int main(void) {
+  const char *message = "hi, this is a test";
+  size_t message_len = strlen(message);
+  uint8_t group_key[32];
+  uint8_t signature[64];
+
+  FrostKeySet *keys = NULL;
+  EXPECT(frost_generate(4, 3, &keys) == FROST_STATUS_INVALID_ARGUMENT);
+  EXPECT(strlen(frost_last_error()) > 0);
+  CHECK(frost_generate(2, 3, &keys));
+  CHECK(frost_key_set_group_key(keys, group_key));
+
+  /* A key set survives a trip through the key file format. */
+  FrostBuffer key_file;
+  FrostKeySet *loaded = NULL;
+  CHECK(frost_key_set_to_json(keys, &key_file));
+  CHECK(frost_key_set_from_json(key_file.data, key_file.len, &loaded));
+  frost_buffer_free(key_file);
+
+  /* Signing in one call. */
+  uint32_t signers[] = {0, 2};
+  CHECK(frost_sign(loaded, signers, 2, (const uint8_t *)message, message_len, signature));
+  CHECK(frost_verify(group_key, (const uint8_t *)message, message_len, signature));
+  EXPECT(frost_verify(group_key, (const uint8_t *)"other", 5, signature) ==
+         FROST_STATUS_INVALID_SIGNATURE);
+  frost_key_set_free(loaded);
+
+  /* Signing round by round, with the shares of participants 1 and 3. */
+  FrostBuffer share_1, share_3, public_package;
+  CHECK(frost_key_set_share(keys, 1, &share_1));
+  CHECK(frost_key_set_share(keys, 3, &share_3));
+  CHECK(frost_key_set_public_package(keys, &public_package));
+
+  FrostNonces *nonces_1 = NULL, *nonces_3 = NULL;
+  FrostBuffer commitment_1, commitment_3;
+  CHECK(frost_commit(share_1.data, share_1.len, &nonces_1, &commitment_1));
+  CHECK(frost_commit(share_3.data, share_3.len, &nonces_3, &commitment_3));
+  char commitments[1024];
+  json_pair(commitments, sizeof commitments, &commitment_1, &commitment_3);
+
+  FrostBuffer partial_1, partial_3;
+  CHECK(frost_sign_share(share_1.data, share_1.len, nonces_1, (const uint8_t *)message,
+                         message_len, (const uint8_t *)commitments, strlen(commitments),
+                         &partial_1));
+  CHECK(frost_sign_share(share_3.data, share_3.len, nonces_3, (const uint8_t *)message,
+                         message_len, (const uint8_t *)commitments, strlen(commitments),
+                         &partial_3));
+  char partials[1024];
+  json_pair(partials, sizeof partials, &partial_1, &partial_3);
+
+  memset(signature, 0, sizeof signature);
+  CHECK(frost_aggregate(public_package.data, public_package.len, (const uint8_t *)message,
+                        message_len, (const uint8_t *)commitments, strlen(commitments),
+                        (const uint8_t *)partials, strlen(partials), signature));
+  CHECK(frost_verify(group_key, (const uint8_t *)message, message_len, signature));
+
+  /* A tampered partial signature is caught by the aggregator. */
+  partials[strlen(partials) - 4] ^= 1;
+  EXPECT(frost_aggregate(public_package.data, public_package.len, (const uint8_t *)message,
+                         message_len, (const uint8_t *)commitments, strlen(commitments),
+                         (const uint8_t *)partials, strlen(partials),
+                         signature) != FROST_STATUS_OK);
+
+  frost_buffer_free(share_1);
+  frost_buffer_free(share_3);
+  frost_buffer_free(public_package);
+  frost_buffer_free(commitment_1);
+  frost_buffer_free(commitment_3);
+  frost_buffer_free(partial_1);
+  frost_buffer_free(partial_3);
+  frost_key_set_free(keys);
+  EXPECT(frost_key_set_to_json(NULL, &key_file) == FROST_STATUS_NULL_POINTER);
+
+  printf("C API round trip passed\n");
+  return 0;
+}
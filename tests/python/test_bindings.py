@@ -0,0 +1,66 @@
+"""Tests of the Python bindings, run after `maturin develop` with
+`python -m unittest discover tests/python`."""
+
+import json
+import unittest
+
+import frost_cli
+
+MESSAGE = b"hi, this is a test"
+
+
+class BindingsTest(unittest.TestCase):
+    @classmethod
+    def setUpClass(cls):
+        cls.keys = frost_cli.generate(2, 3)
+        cls.group_key = frost_cli.group_key(cls.keys)
+
+    def test_sign_and_verify(self):
+        signature = frost_cli.sign(self.keys, MESSAGE, [0, 2])
+        self.assertEqual(len(signature), 64)
+        frost_cli.verify(self.group_key, MESSAGE, signature)
+        with self.assertRaises(frost_cli.InvalidSignatureError):
+            frost_cli.verify(self.group_key, b"different message", signature)
+
+    def test_signing_rounds(self):
+        shares = [frost_cli.share(self.keys, index) for index in (1, 3)]
+        public_package = frost_cli.public_package(self.keys)
+        self.assertEqual(frost_cli.group_key(public_package), self.group_key)
+
+        rounds = [frost_cli.commit(share) for share in shares]
+        commitments = [commitment for _, commitment in rounds]
+        partials = [
+            frost_cli.sign_share(share, nonces, MESSAGE, commitments)
+            for share, (nonces, _) in zip(shares, rounds)
+        ]
+        signature = frost_cli.aggregate(public_package, MESSAGE, commitments, partials)
+        frost_cli.verify(self.group_key, MESSAGE, signature)
+
+        # Nonces sign only once.
+        nonces = rounds[0][0]
+        self.assertTrue(nonces.used)
+        with self.assertRaises(frost_cli.NoncesUsedError):
+            frost_cli.sign_share(shares[0], nonces, MESSAGE, commitments)
+
+        # A tampered partial signature is rejected by the aggregator.
+        tampered = json.loads(partials[1])
+        tampered["z"][0] ^= 1
+        with self.assertRaises(frost_cli.FrostError):
+            frost_cli.aggregate(
+                public_package, MESSAGE, commitments, [partials[0], json.dumps(tampered).encode()]
+            )
+
+    def test_errors(self):
+        with self.assertRaises(frost_cli.InvalidArgumentError):
+            frost_cli.generate(4, 3)
+        with self.assertRaises(frost_cli.InvalidArgumentError):
+            frost_cli.share(self.keys, 9)
+        with self.assertRaises(frost_cli.MalformedInputError):
+            frost_cli.sign(b"not a key file", MESSAGE, [0, 1])
+        with self.assertRaises(frost_cli.SigningError):
+            frost_cli.sign(self.keys, MESSAGE, [0])
+        self.assertTrue(issubclass(frost_cli.SigningError, frost_cli.FrostError))
+
+
+if __name__ == "__main__":
+    unittest.main()
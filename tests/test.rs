@@ -1,13 +1,54 @@
 // Test module for frost_cli
 #[cfg(test)]
 mod tests {
-    use frost_cli::{generate_keys, sign_message, validate_signature};
+    use frost_cli::audit::{self, verify_log, AuditRecord};
+    use frost_cli::bench::run_bench;
+    use frost_cli::bip340;
+    use frost_cli::ceremony::{decrypt_share, run_ceremony, EncryptedShare, Prompter};
+    use frost_cli::ciphersuite::{Ciphersuite, Ed25519, Ristretto255, Secp256k1, SuiteId};
+    use frost_cli::dashboard::{Dashboard, Status};
+    use frost_cli::ed25519::{self, KeyFormat};
+    use frost_cli::enroll::{accept_enrollment, complete_enrollment, deal_enrollment, enroll_keys};
+    use frost_cli::files;
+    use frost_cli::http::{SigningService, MAX_JOBS};
+    use frost_cli::inspect::{inspect_file, inspect_file_with_suite, FileKind};
+    use frost_cli::net::{
+        coordinate, read_frame, request_signature_with, run_participant, write_frame, Request,
+        Response, SigningEvent,
+    };
+    use frost_cli::policy::{Policy, PolicyViolation, DEFAULT_CONTEXT};
+    use frost_cli::proposal::{approve_proposal, load_proposal, propose, sign_proposal};
+    use frost_cli::refresh::{apply_refresh, commitments_digest, deal_refresh, refresh_keys};
+    use frost_cli::repair::{combine_repair, complete_repair, deal_repair};
+    use frost_cli::reshare::{deal_reshare, reshare_keys};
+    use frost_cli::rfc9591::{
+        self, check_keys, SignatureShare, SigningCommitment, SigningNonces, SuiteKeys,
+    };
+    use frost_cli::roast::request_signature_robust;
+    use frost_cli::rounds::signing_context;
+    use frost_cli::{
+        export_public_package, export_share, generate_key_set, generate_keys,
+        generate_keys_with_suite, keys_from_shares, load_key_file, load_keys, load_public_package,
+        load_share, load_share_with_passphrase, public_package_of, read_messages, share_of,
+        sign_batch, sign_message, sign_message_with_policy, threshold_sign, threshold_sign_with,
+        validate_signature, validate_signature_with_context, verify_signature,
+        verify_signature_with_context, BatchSignature, FrostKeys, FrostPublicPackage,
+    };
+    use frost_dalek::compute_message_hash;
+    use rand::rngs::OsRng;
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+    use serde_json::json;
     use std::fs::{self, remove_file};
+    use std::net::{TcpListener, TcpStream};
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::Duration;
 
     #[test]
     fn test_generate_keys() {
         let keys_file = "./results/test_generate_keys_frost_keys.json";
-        let result = generate_keys(3, 5, keys_file);
+        let result = generate_keys(3, 5, keys_file, true);
         assert!(
             result.is_ok(),
             "Failed to generate keys with t=3, n=5: {:?}",
@@ -21,11 +62,30 @@ mod tests {
         remove_file(keys_file).unwrap();
     }
 
+    #[test]
+    fn test_generate_keys_large_group() {
+        let keys_file = "./results/test_generate_keys_large_group_frost_keys.json";
+        let signature_file = "./results/test_generate_keys_large_group_signature.json";
+        generate_keys(9, 25, keys_file, true).unwrap();
+
+        // The last signers only get valid shares if every share reached its recipient.
+        let message = "hi, this is a test";
+        sign_message(message, (16..25).collect(), 25, keys_file, signature_file).unwrap();
+        let result = validate_signature(message, keys_file, signature_file);
+        assert!(
+            result.is_ok(),
+            "Signature by the last nine participants is invalid: {:?}",
+            result.err()
+        );
+        remove_file(keys_file).unwrap();
+        remove_file(signature_file).unwrap();
+    }
+
     #[test]
     fn test_sign_message() {
         let keys_file = "./results/test_sign_message_frost_keys.json";
         let signature_file = "./results/test_sign_message_signature.json";
-        let result = generate_keys(3, 5, keys_file);
+        let result = generate_keys(3, 5, keys_file, true);
         assert!(
             result.is_ok(),
             "Failed to generate keys with t=3, n=5: {:?}",
@@ -51,7 +111,7 @@ mod tests {
     fn test_sign_message_greater_t() {
         let keys_file = "./results/test_sign_message_greater_t_frost_keys.json";
         let signature_file = "./results/test_sign_message_greater_t_signature.json";
-        let result = generate_keys(3, 5, keys_file);
+        let result = generate_keys(3, 5, keys_file, true);
         assert!(
             result.is_ok(),
             "Failed to generate keys with t=3, n=5: {:?}",
@@ -76,7 +136,7 @@ mod tests {
     fn test_verify_signature() {
         let keys_file = "./results/test_verify_signature_frost_keys.json";
         let signature_file = "./results/test_verify_signature_signature.json";
-        let result = generate_keys(3, 5, keys_file);
+        let result = generate_keys(3, 5, keys_file, true);
         assert!(
             result.is_ok(),
             "Failed to generate keys with t=3, n=5: {:?}",
@@ -99,12 +159,1900 @@ mod tests {
         remove_file(signature_file).unwrap();
     }
 
+    #[test]
+    fn test_sign_batch() {
+        let keys_file = "./results/test_sign_batch_frost_keys.json";
+        let signature_file = "./results/test_sign_batch_signatures.json";
+        let result = generate_keys(3, 5, keys_file, true);
+        assert!(
+            result.is_ok(),
+            "Failed to generate keys with t=3, n=5: {:?}",
+            result.err()
+        );
+        let messages = vec![
+            "first message".to_string(),
+            "second message".to_string(),
+            "third message".to_string(),
+        ];
+        let result = sign_batch(&messages, vec![1, 2, 3], 5, keys_file, signature_file);
+        assert!(
+            result.is_ok(),
+            "Failed to sign batch with t=3, n=5: {:?}",
+            result.err()
+        );
+        let batch: Vec<BatchSignature> =
+            serde_json::from_str(&fs::read_to_string(signature_file).unwrap()).unwrap();
+        assert_eq!(batch.len(), messages.len());
+        let group_key = load_keys(keys_file).unwrap().group_key;
+        for (entry, message) in batch.iter().zip(messages.iter()) {
+            assert_eq!(&entry.message, message);
+            let signature: [u8; 64] = entry.signature.as_slice().try_into().unwrap();
+            verify_signature(&group_key, message.as_bytes(), &signature).unwrap();
+            assert!(verify_signature(&group_key, b"different message", &signature).is_err());
+        }
+        // Every signature belongs to its own message only.
+        let first: [u8; 64] = batch[0].signature.as_slice().try_into().unwrap();
+        assert!(verify_signature(&group_key, messages[1].as_bytes(), &first).is_err());
+        remove_file(keys_file).unwrap();
+        remove_file(signature_file).unwrap();
+    }
+
+    #[test]
+    fn test_read_messages() {
+        let plain = read_messages("one\n\ntwo\n".as_bytes(), false).unwrap();
+        assert_eq!(plain, vec!["one", "two"]);
+        let jsonl = read_messages("\"line\\nbreak\"\n\"two\"\n".as_bytes(), true).unwrap();
+        assert_eq!(jsonl, vec!["line\nbreak", "two"]);
+        assert!(read_messages("not json\n".as_bytes(), true).is_err());
+    }
+
+    #[test]
+    fn test_coordinate_over_tcp() {
+        let keys_file = "./results/test_coordinate_over_tcp_frost_keys.json";
+        let signature_file = "./results/test_coordinate_over_tcp_signature.json";
+        let result = generate_keys(2, 3, keys_file, true);
+        assert!(
+            result.is_ok(),
+            "Failed to generate keys with t=2, n=3: {:?}",
+            result.err()
+        );
+
+        // Participant 1 records its partial signature in an audit log.
+        let audit_log = "./results/test_coordinate_over_tcp_audit.log";
+        let _ = remove_file(audit_log);
+        let _ = remove_file(format!("{}.head", audit_log));
+
+        let mut addresses = Vec::new();
+        let mut daemons = Vec::new();
+        for index in [1, 3] {
+            let share_file = format!("./results/test_coordinate_over_tcp_share_{}.json", index);
+            export_share(keys_file, index, &share_file, true).unwrap();
+            let share = load_share(&share_file).unwrap();
+            remove_file(&share_file).unwrap();
+
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            addresses.push(listener.local_addr().unwrap().to_string());
+            let log = (index == 1).then_some(audit_log);
+            daemons.push(thread::spawn(move || {
+                run_participant(&share, listener, Some(1), None, log).unwrap()
+            }));
+        }
+
+        let public_file = "./results/test_coordinate_over_tcp_public.json";
+        export_public_package(keys_file, public_file).unwrap();
+        let message = "hi, this is a test";
+        let mut signers = Vec::new();
+        let result = coordinate(
+            message,
+            DEFAULT_CONTEXT,
+            public_file,
+            &addresses,
+            signature_file,
+            &mut signers,
+        );
+        remove_file(public_file).unwrap();
+        assert!(
+            result.is_ok(),
+            "Failed to coordinate signing over TCP: {:?}",
+            result.err()
+        );
+        assert_eq!(signers, [1, 3]);
+        for daemon in daemons {
+            daemon.join().unwrap();
+        }
+        let result = validate_signature(message, keys_file, signature_file);
+        assert!(
+            result.is_ok(),
+            "Failed to verify networked signature for message: {}",
+            message
+        );
+        assert_eq!(verify_log(audit_log).unwrap(), 1);
+        let entry = fs::read_to_string(audit_log).unwrap();
+        assert!(entry.contains("\"partial-sign\""), "{}", entry);
+        assert!(entry.contains("\"ok\""), "{}", entry);
+        remove_file(keys_file).unwrap();
+        remove_file(signature_file).unwrap();
+        remove_file(audit_log).unwrap();
+        remove_file(format!("{}.head", audit_log)).unwrap();
+    }
+
+    #[test]
+    fn test_signing_dashboard() {
+        let keys_file = "./results/test_signing_dashboard_frost_keys.json";
+        generate_keys(2, 3, keys_file, true).unwrap();
+        let frost_keys = load_keys(keys_file).unwrap();
+        remove_file(keys_file).unwrap();
+
+        let mut addresses = Vec::new();
+        let mut daemons = Vec::new();
+        for index in [1, 3] {
+            let share = share_of(&frost_keys, index).unwrap();
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            addresses.push(listener.local_addr().unwrap().to_string());
+            daemons.push(thread::spawn(move || {
+                run_participant(&share, listener, Some(1), None, None).unwrap()
+            }));
+        }
+
+        let message = "hi, this is a test";
+        let mut dashboard = Dashboard::new(message, &addresses);
+        let mut events = Vec::new();
+        let mut report = |event: SigningEvent| {
+            events.push(event.clone());
+            dashboard.apply(event);
+        };
+        let public = public_package_of(&frost_keys).unwrap();
+        let signature =
+            request_signature_with(message, DEFAULT_CONTEXT, &public, &addresses, &mut report)
+                .unwrap();
+        for daemon in daemons {
+            daemon.join().unwrap();
+        }
+        assert_eq!(events.len(), 8);
+        assert_eq!(
+            events[4],
+            SigningEvent::SessionStarted {
+                signers: vec![1, 3]
+            }
+        );
+        assert_eq!(events[7], SigningEvent::Signed(signature));
+        let indices: Vec<_> = dashboard.participants.iter().map(|p| p.index).collect();
+        assert_eq!(indices, [Some(1), Some(3)]);
+        assert!(dashboard
+            .participants
+            .iter()
+            .all(|p| p.sign == Status::PartialSigned));
+
+        let mut terminal = Terminal::new(TestBackend::new(100, 14)).unwrap();
+        terminal.draw(|frame| dashboard.render(frame)).unwrap();
+        let screen = |terminal: &Terminal<TestBackend>| -> String {
+            let buffer = terminal.backend().buffer();
+            buffer.content.iter().map(|cell| cell.symbol()).collect()
+        };
+        assert!(screen(&terminal).contains("partial signed"));
+        assert!(screen(&terminal).contains("Partial signatures: 2/2"));
+        assert!(screen(&terminal).contains(&hex::encode(&signature[..16])));
+
+        // An unreachable participant is shown as faulted in the first round.
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let unreachable = vec![listener.local_addr().unwrap().to_string()];
+        drop(listener);
+        let mut dashboard = Dashboard::new(message, &unreachable);
+        let result = request_signature_with(
+            message,
+            DEFAULT_CONTEXT,
+            &public,
+            &unreachable,
+            &mut |event| dashboard.apply(event),
+        );
+        dashboard.fail(result.unwrap_err().to_string());
+        assert!(matches!(
+            dashboard.participants[0].commit,
+            Status::Faulted(_)
+        ));
+        assert_eq!(dashboard.participants[0].sign, Status::Waiting);
+        terminal.draw(|frame| dashboard.render(frame)).unwrap();
+        assert!(screen(&terminal).contains("faulted"));
+        assert!(screen(&terminal).contains("Failed: Failed to connect"));
+    }
+
+    #[test]
+    fn test_coordinator_checks_participants() {
+        let frost_keys = generate_key_set(2, 3).unwrap();
+        let message = "hi, this is a test";
+        let coordinate_with = |public: &FrostPublicPackage| {
+            let (address, daemon) = spawn_participant(&frost_keys, 1);
+            let result =
+                request_signature_with(message, DEFAULT_CONTEXT, public, &[address], &mut |_| {});
+            daemon.join().unwrap();
+            result.unwrap_err().to_string()
+        };
+
+        // A participant of another group is turned away.
+        let other = public_package_of(&generate_key_set(2, 3).unwrap()).unwrap();
+        assert!(coordinate_with(&other).contains("a share of a different group key"));
+
+        // So is one whose public key share is not the one the package lists.
+        let mut public = public_package_of(&frost_keys).unwrap();
+        public.public_shares[0].0 = public.public_shares[1].0;
+        assert!(coordinate_with(&public).contains("does not match the public package"));
+
+        let mut public = public_package_of(&frost_keys).unwrap();
+        public.threshold = 3;
+        assert!(coordinate_with(&public).contains("reports threshold 2 instead of 3"));
+    }
+
+    /// Starts a daemon for participant `index` and returns its address.
+    fn spawn_participant(frost_keys: &FrostKeys, index: u32) -> (String, thread::JoinHandle<()>) {
+        let share = share_of(frost_keys, index).unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let daemon =
+            thread::spawn(move || run_participant(&share, listener, Some(1), None, None).unwrap());
+        (address, daemon)
+    }
+
+    #[test]
+    fn test_coordinator_checks_commitment_index() {
+        let frost_keys = generate_key_set(2, 3).unwrap();
+        let public = public_package_of(&frost_keys).unwrap();
+        let (honest, _) = spawn_participant(&frost_keys, 1);
+        let (target, _) = spawn_participant(&frost_keys, 3);
+
+        // Participant 3 joins under its own index but commits under participant 1's.
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let impostor = listener.local_addr().unwrap().to_string();
+        thread::spawn(move || {
+            let (mut client, _) = listener.accept().unwrap();
+            let mut upstream = TcpStream::connect(target).unwrap();
+            while let Ok(Some(request)) = read_frame::<Request, _>(&mut client) {
+                write_frame(&mut upstream, &request).unwrap();
+                let mut response: Response = read_frame(&mut upstream).unwrap().unwrap();
+                if let Response::Commitment(commitment) = &mut response {
+                    commitment.index = 1;
+                }
+                if write_frame(&mut client, &response).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut faulted = Vec::new();
+        let participants = [honest, impostor.clone()];
+        let result = request_signature_with(
+            "hi, this is a test",
+            DEFAULT_CONTEXT,
+            &public,
+            &participants,
+            &mut |event| {
+                if let SigningEvent::Faulted { address, .. } = event {
+                    faulted.push(address);
+                }
+            },
+        );
+        let err = result.unwrap_err().to_string();
+        assert!(
+            err.contains("Participant 3 sent a commitment for participant 1"),
+            "{}",
+            err
+        );
+        assert_eq!(faulted, [impostor]);
+    }
+
+    /// Relays one coordinator connection to `target`, holding back the first
+    /// commitment until `gate` opens and optionally corrupting partial signatures.
+    fn spawn_proxy(
+        target: String,
+        mut gate: Option<mpsc::Receiver<()>>,
+        tamper: bool,
+    ) -> (String, thread::JoinHandle<()>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let proxy = thread::spawn(move || {
+            let (mut client, _) = listener.accept().unwrap();
+            let mut upstream = TcpStream::connect(target).unwrap();
+            while let Ok(Some(request)) = read_frame::<Request, _>(&mut client) {
+                if matches!(request, Request::Commit) {
+                    if let Some(gate) = gate.take() {
+                        let _ = gate.recv();
+                    }
+                }
+                write_frame(&mut upstream, &request).unwrap();
+                let mut response: Response = read_frame(&mut upstream).unwrap().unwrap();
+                if let (true, Response::PartialSignature(partial)) = (tamper, &mut response) {
+                    partial.z[0] ^= 1;
+                }
+                if write_frame(&mut client, &response).is_err() {
+                    break;
+                }
+            }
+        });
+        (address, proxy)
+    }
+
+    #[test]
+    fn test_robust_signing() {
+        let frost_keys = generate_key_set(3, 5).unwrap();
+        let public_package = public_package_of(&frost_keys).unwrap();
+        let message = "hi, this is a test";
+        let timeout = Duration::from_secs(10);
+
+        // Participant 2 never answers, 4 sends an invalid partial signature and 5 only
+        // commits once the first session has started, so the first session is {1, 3, 4}
+        // and the fallback session {1, 3, 5}.
+        let silent = TcpListener::bind("127.0.0.1:0").unwrap();
+        let (open_gate, gate) = mpsc::channel();
+        let mut gate = Some(gate);
+        let mut addresses = Vec::new();
+        let mut threads = Vec::new();
+        for index in 1..=5 {
+            let address = match index {
+                2 => silent.local_addr().unwrap().to_string(),
+                _ => {
+                    let (address, daemon) = spawn_participant(&frost_keys, index);
+                    threads.push(daemon);
+                    if index < 4 {
+                        address
+                    } else {
+                        let gate = if index == 5 { gate.take() } else { None };
+                        let (address, proxy) = spawn_proxy(address, gate, index == 4);
+                        threads.push(proxy);
+                        address
+                    }
+                }
+            };
+            addresses.push(address);
+        }
+
+        let mut events = Vec::new();
+        let signature = request_signature_robust(
+            message,
+            DEFAULT_CONTEXT,
+            &public_package,
+            &addresses,
+            timeout,
+            &mut |event| {
+                if matches!(event, SigningEvent::SessionStarted { .. }) {
+                    let _ = open_gate.send(());
+                }
+                events.push(event)
+            },
+        )
+        .unwrap();
+        verify_signature(&frost_keys.group_key, message.as_bytes(), &signature).unwrap();
+        assert_eq!(events.last(), Some(&SigningEvent::Signed(signature)));
+        let sessions: Vec<Vec<u32>> = events
+            .iter()
+            .filter_map(|event| match event {
+                SigningEvent::SessionStarted { signers } => {
+                    let mut signers = signers.clone();
+                    signers.sort();
+                    Some(signers)
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(sessions, [vec![1, 3, 4], vec![1, 3, 5]]);
+        assert!(events.iter().any(|event| matches!(
+            event,
+            SigningEvent::Faulted { address, reason }
+                if *address == addresses[3] && reason.contains("from participant 4")
+        )));
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        // A participant holding a share of another group is excluded, so no qualified set remains.
+        let other_keys = generate_key_set(3, 5).unwrap();
+        let (first, first_daemon) = spawn_participant(&frost_keys, 1);
+        let (other, other_daemon) = spawn_participant(&other_keys, 2);
+        let (third, third_daemon) = spawn_participant(&frost_keys, 3);
+        let addresses = [first, other, third];
+        let mut events = Vec::new();
+        let result = request_signature_robust(
+            message,
+            DEFAULT_CONTEXT,
+            &public_package,
+            &addresses,
+            timeout,
+            &mut |event| events.push(event),
+        );
+        let err = result.unwrap_err().to_string();
+        assert!(
+            err.contains("No qualified set of signers remains"),
+            "{}",
+            err
+        );
+        assert!(events.iter().any(|event| matches!(
+            event,
+            SigningEvent::Faulted { address, reason }
+                if *address == addresses[1] && reason.contains("different group key")
+        )));
+        first_daemon.join().unwrap();
+        other_daemon.join().unwrap();
+        third_daemon.join().unwrap();
+    }
+
+    #[test]
+    fn test_http_signing_service() {
+        let keys_file = "./results/test_http_signing_service_frost_keys.json";
+        let result = generate_keys(3, 5, keys_file, true);
+        assert!(
+            result.is_ok(),
+            "Failed to generate keys with t=3, n=5: {:?}",
+            result.err()
+        );
+        let audit_log = "./results/test_http_signing_service_audit.log";
+        let _ = remove_file(audit_log);
+        let _ = remove_file(format!("{}.head", audit_log));
+        let service = SigningService::start(
+            load_keys(keys_file).unwrap(),
+            None,
+            None,
+            Some(audit_log.to_string()),
+        );
+        remove_file(keys_file).unwrap();
+
+        let message = "hi, this is a test";
+        let request = json!({ "message": message, "signers": [1, 2, 3] }).to_string();
+        let (status, answer) = service.handle("POST", "/signatures", &request);
+        assert_eq!(status, 202, "Failed to submit signing request: {}", answer);
+        let id = answer["id"].as_u64().unwrap();
+
+        let status_path = format!("/signatures/{}", id);
+        let mut answer = service.handle("GET", &status_path, "").1;
+        for _ in 0..100 {
+            if answer["status"] != "pending" {
+                break;
+            }
+            thread::sleep(Duration::from_millis(50));
+            answer = service.handle("GET", &status_path, "").1;
+        }
+        assert_eq!(
+            answer["status"], "done",
+            "Signing request failed: {}",
+            answer
+        );
+
+        let signature_path = format!("/signatures/{}/signature", id);
+        let (status, answer) = service.handle("GET", &signature_path, "");
+        assert_eq!(status, 200);
+        let signature = answer["signature"].clone();
+
+        let request = json!({ "message": message, "signature": signature }).to_string();
+        let (status, answer) = service.handle("POST", "/verify", &request);
+        assert_eq!(status, 200);
+        assert_eq!(answer["valid"], true, "Signature rejected: {}", answer);
+
+        let request = json!({ "message": "different message", "signature": signature }).to_string();
+        let (_, answer) = service.handle("POST", "/verify", &request);
+        assert_eq!(answer["valid"], false);
+
+        let (status, answer) = service.handle("GET", "/group-key", "");
+        assert_eq!(status, 200);
+        assert_eq!(answer["threshold"], 3);
+        assert_eq!(service.handle("GET", "/signatures/99", "").0, 404);
+        assert!(service.authorize(None));
+
+        // The signing request was recorded in the audit log.
+        assert_eq!(verify_log(audit_log).unwrap(), 1);
+        let entry = fs::read_to_string(audit_log).unwrap();
+        assert!(entry.contains("\"http-sign\""), "{}", entry);
+        remove_file(audit_log).unwrap();
+        remove_file(format!("{}.head", audit_log)).unwrap();
+    }
+
+    #[test]
+    fn test_signing_service_limits() {
+        let service = SigningService::start(
+            generate_key_set(2, 3).unwrap(),
+            None,
+            Some("s3cret".into()),
+            None,
+        );
+        assert!(service.authorize(Some("Bearer s3cret")));
+        assert!(!service.authorize(Some("Bearer s3cre")));
+        assert!(!service.authorize(Some("s3cret")));
+        assert!(!service.authorize(None));
+
+        // Requests for signers that do not exist fail fast, so the worker keeps up.
+        let request = json!({ "message": "hi", "signers": [7, 8] }).to_string();
+        let mut statuses = Vec::new();
+        for _ in 0..MAX_JOBS + 1 {
+            statuses.push(service.handle("POST", "/signatures", &request).0);
+        }
+        assert!(statuses[..MAX_JOBS].iter().all(|&status| status == 202));
+        assert_eq!(statuses[MAX_JOBS], 429);
+    }
+
+    #[test]
+    fn test_signing_service_policy() {
+        let frost_keys = generate_key_set(2, 3).unwrap();
+        let policy = Policy {
+            group_key: frost_keys.group_key.to_vec(),
+            allowed_contexts: Some(vec!["release".to_string()]),
+            ..Policy::default()
+        };
+        let service = SigningService::start(frost_keys, Some(policy), None, None);
+        let sign = |request: serde_json::Value| {
+            let (status, answer) = service.handle("POST", "/signatures", &request.to_string());
+            assert_eq!(status, 202, "Failed to submit signing request: {}", answer);
+            let status_path = format!("/signatures/{}", answer["id"]);
+            let mut answer = service.handle("GET", &status_path, "").1;
+            for _ in 0..100 {
+                if answer["status"] != "pending" {
+                    break;
+                }
+                thread::sleep(Duration::from_millis(50));
+                answer = service.handle("GET", &status_path, "").1;
+            }
+            answer
+        };
+
+        // A request the policy does not allow fails with the violation.
+        let answer = sign(json!({ "message": "hi", "signers": [0, 1] }));
+        assert_eq!(answer["status"], "failed");
+        let error = answer["error"].as_str().unwrap();
+        assert!(error.contains("Policy violation"), "{}", error);
+
+        // An allowed request is signed under its context.
+        let answer = sign(json!({ "message": "hi", "signers": [0, 1], "context": "release" }));
+        assert_eq!(
+            answer["status"], "done",
+            "Signing request failed: {}",
+            answer
+        );
+        let signature_path = format!("/signatures/{}/signature", answer["id"]);
+        let signature = service.handle("GET", &signature_path, "").1["signature"].clone();
+        let request = json!({ "message": "hi", "signature": signature, "context": "release" });
+        let (_, answer) = service.handle("POST", "/verify", &request.to_string());
+        assert_eq!(answer["valid"], true, "Signature rejected: {}", answer);
+        let request = json!({ "message": "hi", "signature": signature });
+        let (_, answer) = service.handle("POST", "/verify", &request.to_string());
+        assert_eq!(answer["valid"], false);
+    }
+
+    #[test]
+    fn test_inspect_files() {
+        let keys_file = "./results/test_inspect_files_frost_keys.json";
+        let share_file = "./results/test_inspect_files_share.json";
+        let public_file = "./results/test_inspect_files_public.json";
+        let signature_file = "./results/test_inspect_files_signature.json";
+        let result = generate_keys(3, 5, keys_file, true);
+        assert!(
+            result.is_ok(),
+            "Failed to generate keys with t=3, n=5: {:?}",
+            result.err()
+        );
+        export_share(keys_file, 2, share_file, true).unwrap();
+        export_public_package(keys_file, public_file).unwrap();
+        sign_message(
+            "hi, this is a test",
+            vec![1, 2, 3],
+            5,
+            keys_file,
+            signature_file,
+        )
+        .unwrap();
+
+        let inspection = inspect_file(keys_file).unwrap();
+        assert_eq!(inspection.kind, FileKind::KeySet);
+        assert_eq!(inspection.threshold, Some(3));
+        assert_eq!(inspection.n, Some(5));
+        assert_eq!(inspection.indices, vec![1, 2, 3, 4, 5]);
+        assert!(inspection.is_valid(), "{}", inspection);
+
+        let inspection = inspect_file(share_file).unwrap();
+        assert_eq!(inspection.kind, FileKind::Share);
+        assert_eq!(inspection.indices, vec![2]);
+        assert!(inspection.is_valid(), "{}", inspection);
+
+        let inspection = inspect_file(public_file).unwrap();
+        assert_eq!(inspection.kind, FileKind::PublicPackage);
+        assert!(inspection.is_valid(), "{}", inspection);
+
+        let inspection = inspect_file(signature_file).unwrap();
+        assert_eq!(inspection.kind, FileKind::Signature);
+        assert!(inspection.is_valid(), "{}", inspection);
+
+        // A group key that is not a valid point must be reported.
+        let mut frost_keys = load_keys(keys_file).unwrap();
+        frost_keys.group_key = [0xff; 32];
+        fs::write(keys_file, serde_json::to_string(&frost_keys).unwrap()).unwrap();
+        let inspection = inspect_file(keys_file).unwrap();
+        assert!(!inspection.is_valid());
+
+        remove_file(keys_file).unwrap();
+        remove_file(share_file).unwrap();
+        remove_file(public_file).unwrap();
+        remove_file(signature_file).unwrap();
+    }
+
+    #[test]
+    fn test_key_file_tamper_detection() {
+        let keys_file = "./results/test_key_file_tamper_detection_frost_keys.json";
+        let tampered_file = "./results/test_key_file_tamper_detection_tampered.json";
+        let result = generate_keys(3, 5, keys_file, true);
+        assert!(
+            result.is_ok(),
+            "Failed to generate keys with t=3, n=5: {:?}",
+            result.err()
+        );
+        let frost_keys = load_keys(keys_file).unwrap();
+        assert!(frost_keys.checksum.is_some());
+        assert_eq!(frost_keys.commitments.len(), 3);
+
+        // A share swapped for another valid scalar no longer lies on the polynomial.
+        let mut tampered = load_keys(keys_file).unwrap();
+        tampered.private_shares[1].0 = tampered.private_shares[2].0;
+        fs::write(tampered_file, serde_json::to_string(&tampered).unwrap()).unwrap();
+        let err = load_keys(tampered_file)
+            .err()
+            .expect("Tampered share was accepted");
+        assert!(err.to_string().contains("private_shares[1]"), "{}", err);
+
+        // A threshold that does not match the polynomial is named as such.
+        let mut tampered = load_keys(keys_file).unwrap();
+        tampered.threshold = 2;
+        fs::write(tampered_file, serde_json::to_string(&tampered).unwrap()).unwrap();
+        let err = load_keys(tampered_file)
+            .err()
+            .expect("Tampered threshold was accepted");
+        assert!(err.to_string().contains("threshold"), "{}", err);
+
+        // Reordering the shares keeps every value consistent but breaks the checksum.
+        let mut tampered = load_keys(keys_file).unwrap();
+        tampered.private_shares.swap(0, 1);
+        fs::write(tampered_file, serde_json::to_string(&tampered).unwrap()).unwrap();
+        let err = load_keys(tampered_file)
+            .err()
+            .expect("Reordered shares were accepted");
+        assert!(err.to_string().contains("checksum"), "{}", err);
+
+        // Removing the checksum of a file with commitments is tampering too.
+        let mut tampered = load_keys(keys_file).unwrap();
+        tampered.checksum = None;
+        fs::write(tampered_file, serde_json::to_string(&tampered).unwrap()).unwrap();
+        let err = load_keys(tampered_file)
+            .err()
+            .expect("Missing checksum was accepted");
+        assert!(err.to_string().contains("checksum"), "{}", err);
+
+        // Key files written before checksums were introduced are rejected
+        // unless legacy files are allowed.
+        let mut legacy = load_keys(keys_file).unwrap();
+        legacy.commitments.clear();
+        legacy.checksum = None;
+        fs::write(tampered_file, serde_json::to_string(&legacy).unwrap()).unwrap();
+        let err = load_keys(tampered_file)
+            .err()
+            .expect("Legacy file was accepted");
+        assert!(err.to_string().contains("--allow-legacy"), "{}", err);
+
+        remove_file(keys_file).unwrap();
+        remove_file(tampered_file).unwrap();
+    }
+
+    #[test]
+    fn test_refresh_keys() {
+        let keys_file = "./results/test_refresh_keys_frost_keys.json";
+        let refreshed_file = "./results/test_refresh_keys_refreshed.json";
+        let signature_file = "./results/test_refresh_keys_signature.json";
+        let result = generate_keys(3, 5, keys_file, true);
+        assert!(
+            result.is_ok(),
+            "Failed to generate keys with t=3, n=5: {:?}",
+            result.err()
+        );
+        let result = refresh_keys(keys_file, refreshed_file, true);
+        assert!(result.is_ok(), "Failed to refresh keys: {:?}", result.err());
+
+        let old_keys = load_keys(keys_file).unwrap();
+        let new_keys = load_keys(refreshed_file).unwrap();
+        assert_eq!(old_keys.group_key, new_keys.group_key);
+        for (old, new) in old_keys.private_shares.iter().zip(&new_keys.private_shares) {
+            assert_eq!(old.1, new.1);
+            assert_ne!(
+                old.0, new.0,
+                "Share of participant {} was not refreshed",
+                old.1
+            );
+        }
+
+        // Refreshed shares still sign for the unchanged group key.
+        let message = "hi, this is a test";
+        sign_message(message, vec![0, 2, 4], 5, refreshed_file, signature_file).unwrap();
+        let result = validate_signature(message, keys_file, signature_file);
+        assert!(result.is_ok(), "Signature after refresh failed to verify");
+
+        // A share from before the refresh does not sign together with refreshed shares.
+        let mut mixed = load_keys(refreshed_file).unwrap();
+        mixed.private_shares[0] = old_keys.private_shares[0];
+        let signed =
+            threshold_sign(&mixed, message.as_bytes(), &[0, 2, 4], 5).and_then(|signature| {
+                verify_signature(&mixed.group_key, message.as_bytes(), &signature)
+            });
+        assert!(signed.is_err(), "An old share signed with refreshed shares");
+
+        remove_file(keys_file).unwrap();
+        remove_file(refreshed_file).unwrap();
+        remove_file(signature_file).unwrap();
+    }
+
+    #[test]
+    fn test_refresh_distributed_rounds() {
+        let keys_file = "./results/test_refresh_distributed_rounds_frost_keys.json";
+        let result = generate_keys(2, 3, keys_file, true);
+        assert!(
+            result.is_ok(),
+            "Failed to generate keys with t=2, n=3: {:?}",
+            result.err()
+        );
+        let frost_keys = load_keys(keys_file).unwrap();
+        let shares: Vec<_> = (1..=3).map(|i| share_of(&frost_keys, i).unwrap()).collect();
+        let packages: Vec<_> = shares
+            .iter()
+            .flat_map(|share| deal_refresh(&mut OsRng, share))
+            .collect();
+
+        let received: Vec<_> = packages.iter().filter(|p| p.to == 2).cloned().collect();
+        let refreshed = apply_refresh(&shares[1], &received).unwrap();
+
+        // Every holder sees the same commitments digest, unless a dealer sends
+        // a different polynomial to one of them.
+        let digests: Vec<_> = (1..=3)
+            .map(|to| {
+                let received: Vec<_> = packages.iter().filter(|p| p.to == to).cloned().collect();
+                commitments_digest(&received)
+            })
+            .collect();
+        assert!(digests.iter().all(|digest| *digest == digests[0]));
+        let mut equivocated: Vec<_> = packages.iter().filter(|p| p.to == 3).cloned().collect();
+        let other = deal_refresh(&mut OsRng, &shares[0]);
+        equivocated[0] = other.into_iter().find(|p| p.to == 3).unwrap();
+        assert_ne!(commitments_digest(&equivocated), digests[0]);
+        assert_eq!(refreshed.group_key, frost_keys.group_key);
+        assert_ne!(refreshed.private_share.0, shares[1].private_share.0);
+
+        // A package from a single holder missing is refused.
+        let result = apply_refresh(&shares[1], &received[1..]);
+        assert!(result.is_err(), "Refresh with a missing package succeeded");
+
+        remove_file(keys_file).unwrap();
+    }
+
+    #[test]
+    fn test_reshare_keys() {
+        let keys_file = "./results/test_reshare_keys_frost_keys.json";
+        let output_dir = "./results/test_reshare_keys";
+        let reshared_keys_file = "./results/test_reshare_keys_reshared.json";
+        let signature_file = "./results/test_reshare_keys_signature.json";
+        let result = generate_keys(3, 5, keys_file, true);
+        assert!(
+            result.is_ok(),
+            "Failed to generate keys with t=3, n=5: {:?}",
+            result.err()
+        );
+
+        // Too few holders to reconstruct the secret.
+        let result = reshare_keys(keys_file, &[2, 4], 2, 3, output_dir, true);
+        assert!(
+            result.is_err(),
+            "Reshare with fewer than t holders succeeded"
+        );
+
+        let result = reshare_keys(keys_file, &[2, 4, 5], 2, 3, output_dir, true);
+        assert!(result.is_ok(), "Failed to reshare keys: {:?}", result.err());
+
+        let old_keys = load_keys(keys_file).unwrap();
+        let public_package =
+            load_public_package(&format!("{}/frost_public.json", output_dir)).unwrap();
+        assert_eq!(public_package.group_key, old_keys.group_key);
+        assert_eq!(public_package.threshold, 2);
+        assert_eq!(public_package.public_shares.len(), 3);
+
+        // Two of the three new shares sign for the unchanged group key.
+        let shares: Vec<_> = (1..=3)
+            .map(|i| load_share(&format!("{}/share_{}.json", output_dir, i)).unwrap())
+            .collect();
+        let reshared_keys = keys_from_shares(&shares).unwrap();
+        fs::write(
+            reshared_keys_file,
+            serde_json::to_string(&reshared_keys).unwrap(),
+        )
+        .unwrap();
+        let message = "hi, this is a test";
+        sign_message(message, vec![0, 2], 3, reshared_keys_file, signature_file).unwrap();
+        let result = validate_signature(message, keys_file, signature_file);
+        assert!(result.is_ok(), "Signature after reshare failed to verify");
+
+        // A dealer sending a different polynomial to one new participant changes
+        // that participant's commitments digest.
+        let dealt: Vec<_> = [2, 4, 5]
+            .iter()
+            .flat_map(|&i| {
+                deal_reshare(
+                    &mut OsRng,
+                    &share_of(&old_keys, i).unwrap(),
+                    &[2, 4, 5],
+                    2,
+                    3,
+                )
+                .unwrap()
+            })
+            .collect();
+        let received =
+            |to: u32| -> Vec<_> { dealt.iter().filter(|p| p.to == to).cloned().collect() };
+        assert_eq!(
+            commitments_digest(&received(1)),
+            commitments_digest(&received(2))
+        );
+        let mut equivocated = received(2);
+        let other = deal_reshare(
+            &mut OsRng,
+            &share_of(&old_keys, 2).unwrap(),
+            &[2, 4, 5],
+            2,
+            3,
+        )
+        .unwrap();
+        equivocated[0] = other.into_iter().find(|p| p.to == 2).unwrap();
+        assert_ne!(
+            commitments_digest(&received(1)),
+            commitments_digest(&equivocated)
+        );
+
+        remove_file(keys_file).unwrap();
+        remove_file(reshared_keys_file).unwrap();
+        remove_file(signature_file).unwrap();
+        fs::remove_dir_all(output_dir).unwrap();
+    }
+
+    #[test]
+    fn test_repair_share() {
+        let keys_file = "./results/test_repair_share_frost_keys.json";
+        let result = generate_keys(3, 5, keys_file, true);
+        assert!(
+            result.is_ok(),
+            "Failed to generate keys with t=3, n=5: {:?}",
+            result.err()
+        );
+        let frost_keys = load_keys(keys_file).unwrap();
+        let public_package = public_package_of(&frost_keys).unwrap();
+
+        // Participant 2 lost its share; participants 1, 4 and 5 help.
+        let helpers = [1, 4, 5];
+        let parts: Vec<_> = helpers
+            .iter()
+            .flat_map(|&i| {
+                let share = share_of(&frost_keys, i).unwrap();
+                deal_repair(&mut OsRng, &share, &helpers, 2).unwrap()
+            })
+            .collect();
+        let sums: Vec<_> = helpers
+            .iter()
+            .map(|&i| {
+                let received: Vec<_> = parts.iter().filter(|p| p.to == i).cloned().collect();
+                combine_repair(i, &received).unwrap()
+            })
+            .collect();
+        // No helper's sum is the lost share.
+        let lost_share = share_of(&frost_keys, 2).unwrap();
+        assert!(sums.iter().all(|sum| sum.sum != lost_share.private_share.0));
+
+        let repaired = complete_repair(&public_package, 2, &sums);
+        assert!(
+            repaired.is_ok(),
+            "Failed to repair share: {:?}",
+            repaired.err()
+        );
+        assert_eq!(repaired.unwrap().private_share, lost_share.private_share);
+
+        // A missing sum is refused.
+        let result = complete_repair(&public_package, 2, &sums[..2]);
+        assert!(result.is_err(), "Repair with a missing sum succeeded");
+
+        remove_file(keys_file).unwrap();
+    }
+
+    #[test]
+    fn test_enroll_participant() {
+        let keys_file = "./results/test_enroll_participant_frost_keys.json";
+        let enrolled_file = "./results/test_enroll_participant_enrolled.json";
+        let public_file = "./results/test_enroll_participant_public.json";
+        let signature_file = "./results/test_enroll_participant_signature.json";
+        let result = generate_keys(2, 3, keys_file, true);
+        assert!(
+            result.is_ok(),
+            "Failed to generate keys with t=2, n=3: {:?}",
+            result.err()
+        );
+        let result = enroll_keys(keys_file, &[1, 3], enrolled_file, true);
+        assert!(
+            result.is_ok(),
+            "Failed to enroll participant: {:?}",
+            result.err()
+        );
+
+        let old_keys = load_keys(keys_file).unwrap();
+        let enrolled_keys = load_keys(enrolled_file).unwrap();
+        assert_eq!(enrolled_keys.group_key, old_keys.group_key);
+        assert_eq!(enrolled_keys.private_shares.len(), 4);
+        assert_eq!(
+            enrolled_keys.private_shares[..3],
+            old_keys.private_shares[..]
+        );
+        assert_eq!(enrolled_keys.private_shares[3].1, 4);
+
+        // The public package lists the new participant.
+        export_public_package(enrolled_file, public_file).unwrap();
+        let public_package = load_public_package(public_file).unwrap();
+        assert_eq!(public_package.public_shares.len(), 4);
+
+        // The new participant signs together with an existing one.
+        let message = "hi, this is a test";
+        sign_message(message, vec![1, 3], 4, enrolled_file, signature_file).unwrap();
+        let result = validate_signature(message, keys_file, signature_file);
+        assert!(
+            result.is_ok(),
+            "Signature with the new participant failed to verify"
+        );
+
+        // Distributed, the existing holders accept the extended public package
+        // and then refresh together with the new participant.
+        let shares: Vec<_> = (1..=3).map(|i| share_of(&old_keys, i).unwrap()).collect();
+        let package = public_package_of(&old_keys).unwrap();
+        let parts: Vec<_> = [&shares[0], &shares[2]]
+            .iter()
+            .flat_map(|share| deal_enrollment(&mut OsRng, share, &package, &[1, 3]).unwrap())
+            .collect();
+        let sums: Vec<_> = [1, 3]
+            .iter()
+            .map(|&helper| {
+                let received: Vec<_> = parts.iter().filter(|p| p.to == helper).cloned().collect();
+                combine_repair(helper, &received).unwrap()
+            })
+            .collect();
+        let (new_share, extended) = complete_enrollment(&package, &sums).unwrap();
+        assert_eq!(new_share.n, 4);
+
+        // Before accepting, an existing holder does not deal to the new participant.
+        let stale = deal_refresh(&mut OsRng, &shares[0]);
+        assert!(stale.iter().all(|p| p.to != 4));
+
+        let mut all: Vec<_> = shares
+            .iter()
+            .map(|share| accept_enrollment(share, &extended).unwrap())
+            .collect();
+        assert!(all.iter().all(|share| share.n == 4));
+        all.push(new_share);
+        let packages: Vec<_> = all
+            .iter()
+            .flat_map(|share| deal_refresh(&mut OsRng, share))
+            .collect();
+        for share in &all {
+            let index = share.private_share.1;
+            let received: Vec<_> = packages.iter().filter(|p| p.to == index).cloned().collect();
+            let result = apply_refresh(share, &received);
+            assert!(
+                result.is_ok(),
+                "Refresh after enrollment failed: {:?}",
+                result.err()
+            );
+        }
+
+        // A share of another group is not accepted.
+        let other = generate_key_set(2, 3).unwrap();
+        let result = accept_enrollment(&share_of(&other, 1).unwrap(), &extended);
+        assert!(result.is_err(), "Share of another group was accepted");
+
+        remove_file(keys_file).unwrap();
+        remove_file(enrolled_file).unwrap();
+        remove_file(public_file).unwrap();
+        remove_file(signature_file).unwrap();
+    }
+
+    #[test]
+    fn test_signing_proposal() {
+        let keys_file = "./results/test_signing_proposal_frost_keys.json";
+        let public_file = "./results/test_signing_proposal_public.json";
+        let share_files = [
+            "./results/test_signing_proposal_share_1.json",
+            "./results/test_signing_proposal_share_3.json",
+        ];
+        let proposal_file = "./results/test_signing_proposal_proposal.json";
+        let expired_file = "./results/test_signing_proposal_expired.json";
+        let signature_file = "./results/test_signing_proposal_signature.json";
+        let message = "hi, this is a test";
+        generate_keys(2, 3, keys_file, true).unwrap();
+        export_public_package(keys_file, public_file).unwrap();
+        export_share(keys_file, 1, share_files[0], true).unwrap();
+        export_share(keys_file, 3, share_files[1], true).unwrap();
+
+        let result = propose(
+            public_file,
+            message,
+            false,
+            "test",
+            vec![1, 3],
+            3600,
+            "please sign",
+            proposal_file,
+        );
+        assert!(
+            result.is_ok(),
+            "Failed to create proposal: {:?}",
+            result.err()
+        );
+
+        // Not approved by every requested signer yet.
+        approve_proposal(proposal_file, share_files[0]).unwrap();
+        let result = sign_proposal(proposal_file, None, keys_file, signature_file, None);
+        assert!(result.is_err(), "Signed a proposal missing an approval");
+
+        approve_proposal(proposal_file, share_files[1]).unwrap();
+        let result = sign_proposal(proposal_file, None, keys_file, signature_file, None);
+        assert!(
+            result.is_ok(),
+            "Failed to sign approved proposal: {:?}",
+            result.err()
+        );
+        let result = validate_signature_with_context(message, "test", keys_file, signature_file);
+        assert!(result.is_ok(), "Proposal signature failed to verify");
+        let result = validate_signature(message, keys_file, signature_file);
+        assert!(
+            result.is_err(),
+            "Proposal signature verified outside its context"
+        );
+
+        // Approvals do not carry over to an edited proposal.
+        let mut proposal = load_proposal(proposal_file).unwrap();
+        proposal.signers = vec![1, 2];
+        proposal.approvals[1].index = 2;
+        fs::write(proposal_file, serde_json::to_string(&proposal).unwrap()).unwrap();
+        let result = sign_proposal(proposal_file, None, keys_file, signature_file, None);
+        assert!(result.is_err(), "Signed an edited proposal");
+
+        // An expired proposal can neither be approved nor signed.
+        propose(
+            public_file,
+            message,
+            true,
+            "test",
+            vec![1, 3],
+            0,
+            "",
+            expired_file,
+        )
+        .unwrap();
+        let result = approve_proposal(expired_file, share_files[0]);
+        assert!(result.is_err(), "Approved an expired proposal");
+        let result = sign_proposal(expired_file, Some(message), keys_file, signature_file, None);
+        assert!(result.is_err(), "Signed an expired proposal");
+
+        remove_file(keys_file).unwrap();
+        remove_file(public_file).unwrap();
+        remove_file(share_files[0]).unwrap();
+        remove_file(share_files[1]).unwrap();
+        remove_file(proposal_file).unwrap();
+        remove_file(expired_file).unwrap();
+        remove_file(signature_file).unwrap();
+    }
+
+    #[test]
+    fn test_signing_policy() {
+        let keys_file = "./results/test_signing_policy_frost_keys.json";
+        let signature_file = "./results/test_signing_policy_signature.json";
+        generate_keys(2, 3, keys_file, true).unwrap();
+        let frost_keys = load_keys(keys_file).unwrap();
+        let policy = Policy {
+            group_key: frost_keys.group_key.to_vec(),
+            allowed_signer_sets: Some(vec![vec![1, 2]]),
+            message_patterns: Some(vec!["release-.*".to_string()]),
+            required_json_fields: None,
+            max_message_size: Some(32),
+            allowed_contexts: Some(vec!["release".to_string()]),
+        };
+        let sign = |message: &str, signers: Vec<u32>, context: &str| {
+            sign_message_with_policy(
+                message,
+                signers,
+                3,
+                keys_file,
+                signature_file,
+                context,
+                Some(&policy),
+            )
+        };
+
+        let result = sign("release-1.0", vec![0, 1], "release");
+        assert!(
+            result.is_ok(),
+            "Allowed request was refused: {:?}",
+            result.err()
+        );
+
+        // The context is bound into the signature, which verifies under it alone.
+        let result =
+            validate_signature_with_context("release-1.0", "release", keys_file, signature_file);
+        assert!(
+            result.is_ok(),
+            "Signature failed to verify under its context"
+        );
+        for other in [DEFAULT_CONTEXT, "hotfix"] {
+            let result =
+                validate_signature_with_context("release-1.0", other, keys_file, signature_file);
+            assert!(
+                result.is_err(),
+                "Signature verified under context {:?}",
+                other
+            );
+        }
+
+        let refused = [
+            ("release-1.0", vec![0, 2], "release"),
+            ("hotfix-1.0", vec![0, 1], "release"),
+            ("release-1.0", vec![0, 1], DEFAULT_CONTEXT),
+            ("release-with-a-very-long-name-1.0", vec![0, 1], "release"),
+        ];
+        for (message, signers, context) in refused {
+            let err = sign(message, signers, context).unwrap_err();
+            assert!(
+                err.downcast_ref::<PolicyViolation>().is_some(),
+                "Expected a policy violation, got: {}",
+                err
+            );
+        }
+
+        remove_file(keys_file).unwrap();
+        remove_file(signature_file).unwrap();
+    }
+
+    #[test]
+    fn test_signing_context_encoding() {
+        let frost_keys = generate_key_set(2, 3).unwrap();
+        let message: &[u8] = b"payments";
+
+        // Each pair would hash the same if the context were appended without its length.
+        let colliding = [
+            (
+                (DEFAULT_CONTEXT, [b":payments".as_slice(), message].concat()),
+                ("payments", message.to_vec()),
+            ),
+            (
+                ("a", [b":b".as_slice(), message].concat()),
+                ("a:b", message.to_vec()),
+            ),
+        ];
+        for ((context, signed), (other_context, other)) in colliding {
+            assert_ne!(
+                compute_message_hash(&signing_context(context), &signed),
+                compute_message_hash(&signing_context(other_context), &other),
+                "Contexts {:?} and {:?} hash to the same message",
+                context,
+                other_context
+            );
+
+            let signature =
+                threshold_sign_with(&frost_keys, &signed, context, &[0, 1], 3, &mut |_| {})
+                    .unwrap();
+            let result =
+                verify_signature_with_context(&frost_keys.group_key, &signed, context, &signature);
+            assert!(
+                result.is_ok(),
+                "Signature failed to verify under its context"
+            );
+            let result = verify_signature_with_context(
+                &frost_keys.group_key,
+                &other,
+                other_context,
+                &signature,
+            );
+            assert!(
+                result.is_err(),
+                "Signature under {:?} verified under {:?}",
+                context,
+                other_context
+            );
+        }
+    }
+
+    #[test]
+    fn test_audit_log() {
+        let log_file = "./results/test_audit_log.log";
+        let head_file = "./results/test_audit_log.log.head";
+        let _ = remove_file(log_file);
+        let _ = remove_file(head_file);
+        for outcome in ["ok", "ok", "error: refused"] {
+            let record = AuditRecord {
+                operation: "sign".to_string(),
+                key_fingerprint: Some("0123456789abcdef".to_string()),
+                message_hashes: vec![audit::message_hash(b"hi, this is a test")],
+                context: Some(DEFAULT_CONTEXT.to_string()),
+                signers: vec![1, 2],
+                outcome: outcome.to_string(),
+            };
+            audit::append(log_file, record).unwrap();
+        }
+        let result = verify_log(log_file);
+        assert_eq!(result.ok(), Some(3), "Intact audit log failed to verify");
+
+        let log = fs::read_to_string(log_file).unwrap();
+        let lines: Vec<&str> = log.lines().collect();
+
+        // An edited entry.
+        fs::write(log_file, log.replacen("error: refused", "ok", 1)).unwrap();
+        assert!(verify_log(log_file).is_err(), "Edited audit log verified");
+
+        // A removed entry.
+        fs::write(log_file, format!("{}\n{}\n", lines[0], lines[2])).unwrap();
+        assert!(
+            verify_log(log_file).is_err(),
+            "Audit log with a removed entry verified"
+        );
+
+        // A truncated log.
+        fs::write(log_file, format!("{}\n{}\n", lines[0], lines[1])).unwrap();
+        assert!(
+            verify_log(log_file).is_err(),
+            "Truncated audit log verified"
+        );
+
+        // A log whose head file is missing is not restarted at the first entry.
+        fs::write(log_file, &log).unwrap();
+        let head = fs::read_to_string(head_file).unwrap();
+        remove_file(head_file).unwrap();
+        let record = AuditRecord {
+            operation: "sign".to_string(),
+            outcome: "ok".to_string(),
+            ..Default::default()
+        };
+        let err = audit::append(log_file, record)
+            .err()
+            .expect("Appended to an audit log without its head file")
+            .to_string();
+        assert!(err.contains("missing"), "{}", err);
+        assert_eq!(fs::read_to_string(log_file).unwrap(), log);
+        fs::write(head_file, head).unwrap();
+
+        remove_file(log_file).unwrap();
+        remove_file(head_file).unwrap();
+    }
+
+    /// A test vector of RFC 9591 Appendix E: a 2-of-3 key set dealt from
+    /// `group_secret_key` and `coefficient`, and participants 1 and 3 signing "test".
+    struct Rfc9591Vector {
+        group_secret_key: &'static str,
+        group_public_key: &'static str,
+        coefficient: &'static str,
+        participant_shares: [&'static str; 3],
+        /// The hiding and binding nonces of participants 1 and 3.
+        nonces: [[&'static str; 2]; 2],
+        signature_shares: [&'static str; 2],
+        signature: &'static str,
+    }
+
+    /// Runs an RFC 9591 test vector through key generation, both signing rounds,
+    /// aggregation and verification, and checks the keys with `check_keys`.
+    fn check_rfc9591_vector<C: Ciphersuite>(vector: &Rfc9591Vector) {
+        let scalar =
+            |hex_scalar: &str| C::deserialize_scalar(&hex::decode(hex_scalar).unwrap()).unwrap();
+        let message = b"test";
+        let group_secret_key = scalar(vector.group_secret_key);
+        let coefficient = scalar(vector.coefficient);
+        let group_key = C::generator() * group_secret_key;
+        assert_eq!(
+            hex::encode(C::serialize_element(&group_key)),
+            vector.group_public_key
+        );
+        for (position, participant_share) in vector.participant_shares.iter().enumerate() {
+            let share = group_secret_key + coefficient * C::scalar_from_u32(position as u32 + 1);
+            assert_eq!(hex::encode(C::serialize_scalar(&share)), *participant_share);
+        }
+
+        let signers = [1u32, 3];
+        let secret = |identifier: u32| scalar(vector.participant_shares[identifier as usize - 1]);
+        let commitments: Vec<SigningCommitment<C>> = signers
+            .iter()
+            .zip(&vector.nonces)
+            .map(|(&identifier, [hiding, binding])| SigningCommitment {
+                identifier,
+                hiding: C::generator() * scalar(hiding),
+                binding: C::generator() * scalar(binding),
+            })
+            .collect();
+        let shares: Vec<SignatureShare<C>> = signers
+            .iter()
+            .zip(&vector.nonces)
+            .map(|(&identifier, [hiding, binding])| {
+                let nonces = SigningNonces {
+                    hiding: scalar(hiding),
+                    binding: scalar(binding),
+                };
+                rfc9591::sign_share(
+                    identifier,
+                    &secret(identifier),
+                    nonces,
+                    &group_key,
+                    &commitments,
+                    message,
+                )
+                .unwrap()
+            })
+            .collect();
+        for (share, expected) in shares.iter().zip(vector.signature_shares) {
+            assert_eq!(hex::encode(C::serialize_scalar(&share.share)), expected);
+        }
+        let public_shares: Vec<(u32, C::Element)> = signers
+            .iter()
+            .map(|&identifier| (identifier, C::generator() * secret(identifier)))
+            .collect();
+        let signature =
+            rfc9591::aggregate(&group_key, &commitments, message, &shares, &public_shares).unwrap();
+        assert_eq!(hex::encode(&signature), vector.signature);
+        assert!(rfc9591::verify::<C>(&group_key, message, &signature).is_ok());
+
+        // The dealt keys pass the same checks as generated ones, including the checksum.
+        let mut keys = SuiteKeys {
+            ciphersuite: C::CONTEXT.to_string(),
+            group_key: C::serialize_element(&group_key),
+            private_shares: vector
+                .participant_shares
+                .iter()
+                .zip(1u32..)
+                .map(|(share, index)| (hex::decode(share).unwrap(), index))
+                .collect(),
+            threshold: 2,
+            commitments: vec![
+                C::serialize_element(&group_key),
+                C::serialize_element(&(C::generator() * coefficient)),
+            ],
+            checksum: None,
+        };
+        let err = check_keys(&keys).err().unwrap().to_string();
+        assert!(
+            err.contains("`checksum`") && err.contains("missing"),
+            "{}",
+            err
+        );
+        keys.checksum = Some(keys.compute_checksum());
+        check_keys(&keys).unwrap();
+        keys.private_shares[1].0 = keys.private_shares[0].0.clone();
+        let err = check_keys(&keys).err().unwrap().to_string();
+        assert!(err.contains("`private_shares[1]`"), "{}", err);
+    }
+
+    #[test]
+    fn test_ciphersuites() {
+        let message = "hi, this is a test";
+        for suite in SuiteId::ALL {
+            let keys_file = format!("./results/test_ciphersuites_{}_keys.json", suite);
+            let signature_file = format!("./results/test_ciphersuites_{}_signature.json", suite);
+
+            generate_keys_with_suite(2, 3, &keys_file, suite, true).unwrap();
+            assert_eq!(load_key_file(&keys_file).unwrap().suite().unwrap(), suite);
+            sign_message(message, vec![0, 2], 3, &keys_file, &signature_file).unwrap();
+            validate_signature(message, &keys_file, &signature_file).unwrap();
+            assert!(
+                validate_signature("different message", &keys_file, &signature_file).is_err(),
+                "{} verification should fail for a different message",
+                suite
+            );
+
+            // Inspection decodes the signature only under the ciphersuite it is given.
+            let length = if suite == SuiteId::Secp256k1 { 65 } else { 64 };
+            let inspection = inspect_file(&signature_file).unwrap();
+            assert_eq!(inspection.signature_lengths, [length]);
+            let inspection = inspect_file_with_suite(&signature_file, Some(suite)).unwrap();
+            assert!(inspection.is_valid(), "{}", inspection);
+            let other = if suite == SuiteId::Secp256k1 {
+                SuiteId::Ed25519
+            } else {
+                SuiteId::Secp256k1
+            };
+            let inspection = inspect_file_with_suite(&signature_file, Some(other)).unwrap();
+            assert!(!inspection.is_valid());
+            assert!(
+                sign_message(message, vec![1], 3, &keys_file, &signature_file).is_err(),
+                "{} signing should fail below the threshold",
+                suite
+            );
+
+            // Commands that only handle frost-dalek keys reject other suites clearly.
+            if suite != SuiteId::FrostDalek {
+                let err = load_keys(&keys_file).err().unwrap().to_string();
+                assert!(err.contains(&suite.to_string()), "{}", err);
+            }
+
+            remove_file(&keys_file).unwrap();
+            remove_file(&signature_file).unwrap();
+        }
+
+        // Signatures made with one suite do not verify under another.
+        let ed_keys = "./results/test_ciphersuites_cross_ed25519.json";
+        let ristretto_keys = "./results/test_ciphersuites_cross_ristretto255.json";
+        let signature_file = "./results/test_ciphersuites_cross_signature.json";
+        generate_keys_with_suite(2, 3, ed_keys, SuiteId::Ed25519, true).unwrap();
+        generate_keys_with_suite(2, 3, ristretto_keys, SuiteId::Ristretto255, true).unwrap();
+        sign_message(message, vec![0, 1], 3, ed_keys, signature_file).unwrap();
+        assert!(validate_signature(message, ristretto_keys, signature_file).is_err());
+        remove_file(ed_keys).unwrap();
+        remove_file(ristretto_keys).unwrap();
+        remove_file(signature_file).unwrap();
+
+        // RFC 9591 Appendix E.1, FROST(Ed25519, SHA-512).
+        check_rfc9591_vector::<Ed25519>(&Rfc9591Vector {
+            group_secret_key: "7b1c33d3f5291d85de664833beb1ad469f7fb6025a0ec78b3a790c6e13a98304",
+            group_public_key: "15d21ccd7ee42959562fc8aa63224c8851fb3ec85a3faf66040d380fb9738673",
+            coefficient: "178199860edd8c62f5212ee91eff1295d0d670ab4ed4506866bae57e7030b204",
+            participant_shares: [
+                "929dcc590407aae7d388761cddb0c0db6f5627aea8e217f4a033f2ec83d93509",
+                "a91e66e012e4364ac9aaa405fcafd370402d9859f7b6685c07eed76bf409e80d",
+                "d3cb090a075eb154e82fdb4b3cb507f110040905468bb9c46da8bdea643a9a02",
+            ],
+            nonces: [
+                [
+                    "812d6104142944d5a55924de6d49940956206909f2acaeedecda2b726e630407",
+                    "b1110165fc2334149750b28dd813a39244f315cff14d4e89e6142f262ed83301",
+                ],
+                [
+                    "c256de65476204095ebdc01bd11dc10e57b36bc96284595b8215222374f99c0e",
+                    "243d71944d929063bc51205714ae3c2218bd3451d0214dfb5aeec2a90c35180d",
+                ],
+            ],
+            signature_shares: [
+                "001719ab5a53ee1a12095cd088fd149702c0720ce5fd2f29dbecf24b7281b603",
+                "bd86125de990acc5e1f13781d8e32c03a9bbd4c53539bbc106058bfd14326007",
+            ],
+            signature: "36282629c383bb820a88b71cae937d41f2f2adfcc3d02e55507e2fb9e2dd3cbebd9d2b0844e49ae0f3fa935161e1419aab7b47d21a37ebeae1f17d4987b3160b",
+        });
+
+        // RFC 9591 Appendix E.4, FROST(ristretto255, SHA-512).
+        check_rfc9591_vector::<Ristretto255>(&Rfc9591Vector {
+            group_secret_key: "1b25a55e463cfd15cf14a5d3acc3d15053f08da49c8afcf3ab265f2ebc4f970b",
+            group_public_key: "e2a62f39eede11269e3bd5a7d97554f5ca384f9f6d3dd9c3c0d05083c7254f57",
+            coefficient: "410f8b744b19325891d73736923525a4f596c805d060dfb9c98009d34e3fec02",
+            participant_shares: [
+                "5c3430d391552f6e60ecdc093ff9f6f4488756aa6cebdbad75a768010b8f830e",
+                "b06fc5eac20b4f6e1b271d9df2343d843e1e1fb03c4cbb673f2872d459ce6f01",
+                "f17e505f0e2581c6acfe54d3846a622834b5e7b50cad9a2109a97ba7a80d5c04",
+            ],
+            nonces: [
+                [
+                    "214f2cabb86ed71427ea7ad4283b0fae26b6746c801ce824b83ceb2b99278c03",
+                    "c9b8f5e16770d15603f744f8694c44e335e8faef00dad182b8d7a34a62552f0c",
+                ],
+                [
+                    "3f7927872b0f9051dd98dd73eb2b91494173bbe0feb65a3e7e58d3e2318fa40f",
+                    "ffd79445fb8030f0a3ddd3861aa4b42b618759282bfe24f1f9304c7009728305",
+                ],
+            ],
+            signature_shares: [
+                "9285f875923ce7e0c491a592e9ea1865ec1b823ead4854b48c8a46287749ee09",
+                "7cb211fe0e3d59d25db6e36b3fb32344794139602a7b24f1ae0dc4e26ad7b908",
+            ],
+            signature: "fc45655fbc66bbffad654ea4ce5fdae253a49a64ace25d9adb62010dd9fb25552164141787162e5b4cab915b4aa45d94655dbb9ed7c378a53b980a0be220a802",
+        });
+
+        // RFC 9591 Appendix E.5, FROST(secp256k1, SHA-256).
+        check_rfc9591_vector::<Secp256k1>(&Rfc9591Vector {
+            group_secret_key: "0d004150d27c3bf2a42f312683d35fac7394b1e9e318249c1bfe7f0795a83114",
+            group_public_key: "02f37c34b66ced1fb51c34a90bdae006901f10625cc06c4f64663b0eae87d87b4f",
+            coefficient: "fbf85eadae3058ea14f19148bb72b45e4399c0b16028acaf0395c9b03c823579",
+            participant_shares: [
+                "08f89ffe80ac94dcb920c26f3f46140bfc7f95b493f8310f5fc1ea2b01f4254c",
+                "04f0feac2edcedc6ce1253b7fab8c86b856a797f44d83d82a385554e6e401984",
+                "00e95d59dd0d46b0e303e500b62b7ccb0e555d49f5b849f5e748c071da8c0dbc",
+            ],
+            nonces: [
+                [
+                    "841d3a6450d7580b4da83c8e618414d0f024391f2aeb511d7579224420aa81f0",
+                    "8d2624f532af631377f33cf44b5ac5f849067cae2eacb88680a31e77c79b5a80",
+                ],
+                [
+                    "2b19b13f193f4ce83a399362a90cdc1e0ddcd83e57089a7af0bdca71d47869b2",
+                    "7a443bde83dc63ef52dda354005225ba0e553243402a4705ce28ffaafe0f5b98",
+                ],
+            ],
+            signature_shares: [
+                "c4fce1775a1e141fb579944166eab0d65eefe7b98d480a569bbbfcb14f91c197",
+                "0160fd0d388932f4826d2ebcd6b9eaba734f7c71cf25b4279a4ca2581e47b18d",
+            ],
+            signature: "0205b6d04d3774c8929413e3c76024d54149c372d57aae62574ed74319b5ea14d0c65dde8492a7471437e6c2fe3da49b90d23f642b5c6dbe7e36089f096dd97324",
+        });
+    }
+
+    #[test]
+    fn test_ed25519_compatibility() {
+        use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+
+        let keys_file = "./results/test_ed25519_compatibility_keys.json";
+        let signature_file = "./results/test_ed25519_compatibility_signature.json";
+        let message = "hi, this is a test";
+        generate_keys_with_suite(3, 5, keys_file, SuiteId::Ed25519, true).unwrap();
+        sign_message(message, vec![0, 2, 4], 5, keys_file, signature_file).unwrap();
+
+        // The group key and threshold signature verify with an independent Ed25519 implementation.
+        let public_key = match load_key_file(keys_file).unwrap() {
+            frost_cli::KeyFile::Suite(keys) => ed25519::public_key(&keys).unwrap(),
+            frost_cli::KeyFile::Frost(_) => panic!("Expected an ed25519 key file"),
+        };
+        let signature: Vec<u8> =
+            serde_json::from_str(&fs::read_to_string(signature_file).unwrap()).unwrap();
+        let signature: [u8; 64] = signature.try_into().unwrap();
+        let verifying_key = VerifyingKey::from_bytes(&public_key).unwrap();
+        verifying_key
+            .verify_strict(message.as_bytes(), &Signature::from_bytes(&signature))
+            .unwrap();
+        assert!(verifying_key
+            .verify_strict(b"different message", &Signature::from_bytes(&signature))
+            .is_err());
+
+        // And the other way around: single-key signatures verify like threshold ones.
+        let signing_key = SigningKey::from_bytes(&[7; 32]);
+        let single = signing_key.sign(message.as_bytes()).to_bytes();
+        ed25519::verify(
+            &signing_key.verifying_key().to_bytes(),
+            message.as_bytes(),
+            &single,
+        )
+        .unwrap();
+
+        // RFC 8032, section 7.1, test 1.
+        let rfc_key: [u8; 32] =
+            hex::decode("d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a")
+                .unwrap()
+                .try_into()
+                .unwrap();
+        let rfc_signature: [u8; 64] = hex::decode(
+            "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901555fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b",
+        )
+        .unwrap()
+        .try_into()
+        .unwrap();
+        ed25519::verify(&rfc_key, b"", &rfc_signature).unwrap();
+
+        // Exported keys.
+        let openssh = ed25519::export_public_key(keys_file, KeyFormat::Openssh, "frost").unwrap();
+        assert!(openssh.starts_with("ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAI"));
+        assert!(openssh.ends_with(" frost"));
+        let hex_key = ed25519::export_public_key(keys_file, KeyFormat::Hex, "").unwrap();
+        assert_eq!(hex_key, hex::encode(public_key));
+
+        remove_file(keys_file).unwrap();
+        remove_file(signature_file).unwrap();
+    }
+
+    #[test]
+    fn test_bip340_vectors() {
+        // BIP-340 test vectors 0 to 18: public key, message, signature and validity.
+        // Vectors 15 to 18 sign messages of 0, 1, 17 and 100 bytes rather than 32-byte hashes.
+        let vectors = [
+            ("F9308A019258C31049344F85F89D5229B531C845836F99B08601F113BCE036F9", "0000000000000000000000000000000000000000000000000000000000000000", "E907831F80848D1069A5371B402410364BDF1C5F8307B0084C55F1CE2DCA821525F66A4A85EA8B71E482A74F382D2CE5EBEEE8FDB2172F477DF4900D310536C0", true),
+            ("DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659", "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89", "6896BD60EEAE296DB48A229FF71DFE071BDE413E6D43F917DC8DCF8C78DE33418906D11AC976ABCCB20B091292BFF4EA897EFCB639EA871CFA95F6DE339E4B0A", true),
+            ("DD308AFEC5777E13121FA72B9CC1B7CC0139715309B086C960E18FD969774EB8", "7E2D58D8B3BCDF1ABADEC7829054F90DDA9805AAB56C77333024B9D0A508B75C", "5831AAEED7B44BB74E5EAB94BA9D4294C49BCF2A60728D8B4C200F50DD313C1BAB745879A5AD954A72C45A91C3A51D3C7ADEA98D82F8481E0E1E03674A6F3FB7", true),
+            ("25D1DFF95105F5253C4022F628A996AD3A0D95FBF21D468A1B33F8C160D8F517", "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF", "7EB0509757E246F19449885651611CB965ECC1A187DD51B64FDA1EDC9637D5EC97582B9CB13DB3933705B32BA982AF5AF25FD78881EBB32771FC5922EFC66EA3", true),
+            ("D69C3509BB99E412E68B0FE8544E72837DFA30746D8BE2AA65975F29D22DC7B9", "4DF3C3F68FCC83B27E9D42C90431A72499F17875C81A599B566C9889B9696703", "00000000000000000000003B78CE563F89A0ED9414F5AA28AD0D96D6795F9C6376AFB1548AF603B3EB45C9F8207DEE1060CB71C04E80F593060B07D28308D7F4", true),
+            ("EEFDEA4CDB677750A420FEE807EACF21EB9898AE79B9768766E4FAA04A2D4A34", "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89", "6CFF5C3BA86C69EA4B7376F31A9BCB4F74C1976089B2D9963DA2E5543E17776969E89B4C5564D00349106B8497785DD7D1D713A8AE82B32FA79D5F7FC407D39B", false),
+            ("DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659", "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89", "FFF97BD5755EEEA420453A14355235D382F6472F8568A18B2F057A14602975563CC27944640AC607CD107AE10923D9EF7A73C643E166BE5EBEAFA34B1AC553E2", false),
+            ("DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659", "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89", "1FA62E331EDBC21C394792D2AB1100A7B432B013DF3F6FF4F99FCB33E0E1515F28890B3EDB6E7189B630448B515CE4F8622A954CFE545735AAEA5134FCCDB2BD", false),
+            ("DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659", "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89", "6CFF5C3BA86C69EA4B7376F31A9BCB4F74C1976089B2D9963DA2E5543E177769961764B3AA9B2FFCB6EF947B6887A226E8D7C93E00C5ED0C1834FF0D0C2E6DA6", false),
+            ("DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659", "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89", "0000000000000000000000000000000000000000000000000000000000000000123DDA8328AF9C23A94C1FEECFD123BA4FB73476F0D594DCB65C6425BD186051", false),
+            ("DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659", "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89", "00000000000000000000000000000000000000000000000000000000000000017615FBAF5AE28864013C099742DEADB4DBA87F11AC6754F93780D5A1837CF197", false),
+            ("DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659", "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89", "4A298DACAE57395A15D0795DDBFD1DCB564DA82B0F269BC70A74F8220429BA1D69E89B4C5564D00349106B8497785DD7D1D713A8AE82B32FA79D5F7FC407D39B", false),
+            ("DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659", "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89", "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEFFFFFC2F69E89B4C5564D00349106B8497785DD7D1D713A8AE82B32FA79D5F7FC407D39B", false),
+            ("DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659", "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89", "6CFF5C3BA86C69EA4B7376F31A9BCB4F74C1976089B2D9963DA2E5543E177769FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEBAAEDCE6AF48A03BBFD25E8CD0364141", false),
+            ("FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEFFFFFC30", "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89", "6CFF5C3BA86C69EA4B7376F31A9BCB4F74C1976089B2D9963DA2E5543E17776969E89B4C5564D00349106B8497785DD7D1D713A8AE82B32FA79D5F7FC407D39B", false),
+            ("778CAA53B4393AC467774D09497A87224BF9FAB6F6E68B23086497324D6FD117", "", "71535DB165ECD9FBBC046E5FFAEA61186BB6AD436732FCCC25291A55895464CF6069CE26BF03466228F19A3A62DB8A649F2D560FAC652827D1AF0574E427AB63", true),
+            ("778CAA53B4393AC467774D09497A87224BF9FAB6F6E68B23086497324D6FD117", "11", "08A20A0AFEF64124649232E0693C583AB1B9934AE63B4C3511F3AE1134C6A303EA3173BFEA6683BD101FA5AA5DBC1996FE7CACFC5A577D33EC14564CEC2BACBF", true),
+            ("778CAA53B4393AC467774D09497A87224BF9FAB6F6E68B23086497324D6FD117", "0102030405060708090A0B0C0D0E0F1011", "5130F39A4059B43BC7CAC09A19ECE52B5D8699D1A71E3C52DA9AFDB6B50AC370C4A482B77BF960F8681540E25B6771ECE1E5A37FD80E5A51897C5566A97EA5A5", true),
+            ("778CAA53B4393AC467774D09497A87224BF9FAB6F6E68B23086497324D6FD117", "99999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999", "403B12B0D8555A344175EA7EC746566303321E5DBFA8BE6F091635163ECA79A8585ED3E3170807E7C03B720FC54C7B23897FCBA0E9D0B4A06894CFD249F22367", true),
+        ];
+        for (index, (public_key, message, signature, valid)) in vectors.iter().enumerate() {
+            let public_key: [u8; 32] = hex::decode(public_key).unwrap().try_into().unwrap();
+            let message = hex::decode(message).unwrap();
+            let signature = hex::decode(signature).unwrap();
+            assert_eq!(
+                bip340::verify(&public_key, &message, &signature).is_ok(),
+                *valid,
+                "BIP-340 test vector {}",
+                index
+            );
+        }
+
+        // BIP-86: the key-path-only output key of the first receiving address.
+        let internal_key: [u8; 32] =
+            hex::decode("cc8a4bc64d897bddc5fbc2f670f7a8ba0b386779106cf1223c6fc5d7cd6fc115")
+                .unwrap()
+                .try_into()
+                .unwrap();
+        assert_eq!(
+            hex::encode(bip340::taproot_output_key(&internal_key, None).unwrap()),
+            "a60869f0dbcf1dc659c9cecbaf8050135ea9e8cdc487053f1dc6880949dc684c"
+        );
+    }
+
+    #[test]
+    fn test_bip340_threshold_signatures() {
+        use k256::schnorr::{Signature, VerifyingKey};
+
+        let keys_file = "./results/test_bip340_keys.json";
+        let taproot_file = "./results/test_bip340_taproot_keys.json";
+        let signature_file = "./results/test_bip340_signature.json";
+        let message = "hi, this is a test";
+        let verify_independently = |public_key: &[u8; 32]| {
+            let signature: Vec<u8> =
+                serde_json::from_str(&fs::read_to_string(signature_file).unwrap()).unwrap();
+            assert_eq!(signature.len(), 64);
+            VerifyingKey::from_bytes(public_key)
+                .unwrap()
+                .verify_raw(
+                    message.as_bytes(),
+                    &Signature::try_from(&signature[..]).unwrap(),
+                )
+                .is_ok()
+        };
+
+        // Group keys are normalized to an even y coordinate, whatever the dealt secret.
+        for _ in 0..4 {
+            generate_keys_with_suite(2, 3, keys_file, SuiteId::Secp256k1Tr, true).unwrap();
+            let keys = match load_key_file(keys_file).unwrap() {
+                frost_cli::KeyFile::Suite(keys) => keys,
+                frost_cli::KeyFile::Frost(_) => panic!("Expected a secp256k1-tr key file"),
+            };
+            assert_eq!(keys.group_key[0], 2);
+
+            // Threshold signatures verify as BIP-340 signatures with an independent implementation.
+            let public_key = bip340::x_only_key(&keys).unwrap();
+            sign_message(message, vec![0, 2], 3, keys_file, signature_file).unwrap();
+            assert!(verify_independently(&public_key));
+
+            // Taproot: the tweaked key set signs for the output key, with and without scripts.
+            let merkle_root = [0x5a; 32];
+            for root in [None, Some(&merkle_root)] {
+                bip340::taproot_tweak_keys(keys_file, root, taproot_file, true).unwrap();
+                let output_key = bip340::taproot_output_key(&public_key, root).unwrap();
+                assert_eq!(
+                    bip340::export_x_only_key(taproot_file).unwrap(),
+                    hex::encode(output_key)
+                );
+                sign_message(message, vec![1, 2], 3, taproot_file, signature_file).unwrap();
+                validate_signature(message, taproot_file, signature_file).unwrap();
+                assert!(verify_independently(&output_key));
+                assert!(!verify_independently(&public_key));
+            }
+        }
+
+        remove_file(keys_file).unwrap();
+        remove_file(taproot_file).unwrap();
+        remove_file(signature_file).unwrap();
+    }
+
+    #[test]
+    fn test_secret_zeroize() {
+        use frost_cli::rfc9591::commit;
+        use frost_cli::secret::{Secret, SecretVec};
+        use std::cell::Cell;
+        use std::mem::MaybeUninit;
+        use std::panic;
+        use std::ptr;
+        use std::rc::Rc;
+        use zeroize::Zeroize;
+
+        // Drops a value in place and returns the bytes it leaves behind.
+        fn drop_and_read<T, U: Copy>(value: T, field: impl Fn(*const T) -> *const U) -> U {
+            let mut slot = MaybeUninit::new(value);
+            unsafe {
+                ptr::drop_in_place(slot.as_mut_ptr());
+                ptr::read(field(slot.as_ptr()))
+            }
+        }
+
+        let keys_file = "./results/test_secret_zeroize_frost_keys.json";
+        generate_keys(2, 3, keys_file, true).unwrap();
+        let share = share_of(&load_keys(keys_file).unwrap(), 1).unwrap();
+        assert_ne!(share.private_share.0, [0u8; 32]);
+        let left = drop_and_read(share, |share| unsafe {
+            ptr::addr_of!((*share).private_share)
+        });
+        assert_eq!(left.0, [0u8; 32]);
+
+        let secret = Ed25519::scalar_from_u32(7);
+        let (nonces, _) = commit::<Ed25519, _>(&mut OsRng, 1, &secret);
+        let zero = Ed25519::serialize_scalar(&Ed25519::scalar_from_u32(0));
+        assert_ne!(Ed25519::serialize_scalar(&nonces.hiding), zero);
+        let left = drop_and_read(nonces, |nonces| unsafe { ptr::addr_of!((*nonces).hiding) });
+        assert_eq!(Ed25519::serialize_scalar(&left), zero);
+        let left = drop_and_read(
+            commit::<Ed25519, _>(&mut OsRng, 1, &secret).0,
+            |nonces| unsafe { ptr::addr_of!((*nonces).binding) },
+        );
+        assert_eq!(Ed25519::serialize_scalar(&left), zero);
+
+        // A `Secret` zeroizes the value it owns when dropped.
+        struct Tracked(Rc<Cell<bool>>);
+        impl Zeroize for Tracked {
+            fn zeroize(&mut self) {
+                self.0.set(true);
+            }
+        }
+        let zeroized = Rc::new(Cell::new(false));
+        let secret = Secret::new(Tracked(zeroized.clone()));
+        assert!(!zeroized.get());
+        drop(secret);
+        assert!(zeroized.get());
+
+        // A `SecretVec` drops its values in place when zeroized, and never grows.
+        struct Dropped(Rc<Cell<usize>>);
+        impl Drop for Dropped {
+            fn drop(&mut self) {
+                self.0.set(self.0.get() + 1);
+            }
+        }
+        let dropped = Rc::new(Cell::new(0));
+        let mut values = Secret::new(SecretVec::with_capacity(2));
+        values.push(Dropped(dropped.clone()));
+        values.push(Dropped(dropped.clone()));
+        let full = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+            values.push(Dropped(Rc::new(Cell::new(0))))
+        }));
+        assert!(full.is_err(), "A full secret buffer grew");
+        assert_eq!(values.len(), 2);
+        drop(values);
+        assert_eq!(dropped.get(), 2);
+
+        remove_file(keys_file).unwrap();
+    }
+
+    #[test]
+    fn test_output_files() {
+        let dir = "./results/test_output_files";
+        let _ = fs::remove_dir_all(dir);
+        let keys_file = format!("{}/keys/frost_keys.json", dir);
+        let public_file = format!("{}/public/frost_public.json", dir);
+
+        // Parent directories are created on demand.
+        generate_keys(2, 3, &keys_file, true).unwrap();
+        export_public_package(&keys_file, &public_file).unwrap();
+        load_keys(&keys_file).unwrap();
+        load_public_package(&public_file).unwrap();
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = |path: &str| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+            assert_eq!(mode(&keys_file), 0o600);
+            assert_eq!(mode(&public_file) & 0o600, 0o600);
+        }
+
+        // Existing key files are only replaced with --force, and replacing them leaves no temporary files.
+        assert!(files::check_overwrite(&keys_file, false).is_err());
+        files::check_overwrite(&keys_file, true).unwrap();
+        files::check_overwrite(&format!("{}/keys/other.json", dir), false).unwrap();
+        let err = generate_keys(2, 3, &keys_file, false)
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(err.contains("--force"), "{}", err);
+        let share_file = format!("{}/keys/share_1.json", dir);
+        export_share(&keys_file, 1, &share_file, false).unwrap();
+        assert!(export_share(&keys_file, 1, &share_file, false).is_err());
+        remove_file(&share_file).unwrap();
+        generate_keys(2, 3, &keys_file, true).unwrap();
+        let entries: Vec<_> = fs::read_dir(format!("{}/keys", dir))
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(entries, vec!["frost_keys.json"]);
+
+        // The write itself refuses to replace a file created after the check.
+        let secret_file = format!("{}/keys/secret.json", dir);
+        files::check_overwrite(&secret_file, false).unwrap();
+        fs::write(&secret_file, "created meanwhile").unwrap();
+        let err = files::write_secret_json(&secret_file, &[1u8, 2, 3], false)
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(err.contains("--force"), "{}", err);
+        assert_eq!(
+            fs::read_to_string(&secret_file).unwrap(),
+            "created meanwhile"
+        );
+        files::write_secret_json(&secret_file, &[1u8, 2, 3], true).unwrap();
+        let entries = fs::read_dir(format!("{}/keys", dir)).unwrap().count();
+        assert_eq!(entries, 2, "A temporary file was left behind");
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_ceremony() {
+        let dir = "./results/test_ceremony";
+        let _ = fs::remove_dir_all(dir);
+        let answers = |dir: &str, confirm: &str| {
+            format!(
+                "3\n2\nAlice\n\nCarol Smith\n{}\nhunter2\nhunter2\n\na\nb\npw\npw\ny\ny\n{}\n",
+                dir, confirm
+            )
+        };
+
+        let mut output = Vec::new();
+        let input = answers(dir, "y");
+        let mut prompter = Prompter::new(input.as_bytes(), &mut output, false);
+        let summary = run_ceremony(&mut prompter, false).unwrap().unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("The passphrases do not match."));
+        assert!(output.contains(&summary.fingerprint));
+        assert_eq!((summary.threshold, summary.n), (2, 3));
+        let labels: Vec<_> = summary.holders.iter().map(|h| h.label.as_str()).collect();
+        assert_eq!(labels, ["Alice", "participant 2", "Carol Smith"]);
+        assert!(summary.holders[2]
+            .share_file
+            .ends_with("share_3_carol-smith.json"));
+        let encrypted: Vec<_> = summary.holders.iter().map(|h| h.encrypted).collect();
+        assert_eq!(encrypted, [true, false, true]);
+
+        // Encrypted shares open with their passphrase only, and match the public package.
+        let public_package = load_public_package(&summary.public_file).unwrap();
+        assert_eq!(public_package.group_key, summary.group_key);
+        let read = |holder: usize| -> EncryptedShare {
+            serde_json::from_str(&fs::read_to_string(&summary.holders[holder].share_file).unwrap())
+                .unwrap()
+        };
+        assert!(decrypt_share(&read(0), "hunter3").is_err());
+        let mut tampered = read(0);
+        tampered.index = 2;
+        assert!(decrypt_share(&tampered, "hunter2").is_err());
+        let alice = decrypt_share(&read(0), "hunter2").unwrap();
+        assert_eq!(alice.private_share.1, 1);
+        let mut expensive = read(0);
+        expensive.m_cost = u32::MAX;
+        let err = decrypt_share(&expensive, "hunter2")
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(err.contains("exceed the limits"), "{}", err);
+
+        // Inspection shows what an encrypted share keeps in the clear, without decrypting it.
+        let inspection = inspect_file(&summary.holders[0].share_file).unwrap();
+        assert_eq!(inspection.kind, FileKind::EncryptedShare);
+        assert_eq!(inspection.indices, vec![1]);
+        assert_eq!(inspection.group_key, Some(summary.group_key.to_vec()));
+        let alice_file = read(0);
+        assert_eq!(
+            inspection.key_derivation,
+            Some((alice_file.m_cost, alice_file.t_cost, alice_file.p_cost))
+        );
+        assert!(inspection.is_valid(), "{}", inspection);
+        assert!(inspection.to_string().contains(&summary.fingerprint));
+        let expensive_file = format!("{}/expensive.json", dir);
+        fs::write(&expensive_file, serde_json::to_string(&expensive).unwrap()).unwrap();
+        assert!(!inspect_file(&expensive_file).unwrap().is_valid());
+
+        let carol = load_share_with_passphrase(&summary.holders[2].share_file, "pw").unwrap();
+        assert!(load_share_with_passphrase(&summary.holders[2].share_file, "hunter2").is_err());
+        let bob = load_share(&summary.holders[1].share_file).unwrap();
+        assert_eq!(carol.group_key, bob.group_key);
+
+        // Existing files are kept without --force, and nothing is written unless every holder confirms.
+        let input = answers(dir, "y");
+        let mut prompter = Prompter::new(input.as_bytes(), Vec::new(), false);
+        assert!(run_ceremony(&mut prompter, false).is_err());
+        let other_dir = format!("{}/declined", dir);
+        let input = answers(&other_dir, "n");
+        let mut prompter = Prompter::new(input.as_bytes(), Vec::new(), false);
+        assert!(run_ceremony(&mut prompter, false).unwrap().is_none());
+        assert!(fs::metadata(&other_dir).is_err());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
     // Fail tests
     #[test]
     fn test_sign_message_fail() {
         let keys_file = "./results/test_sign_message_fail_frost_keys.json";
         let signature_file = "./results/test_sign_message_fail_signature.json";
-        let result = generate_keys(2, 5, keys_file);
+        let result = generate_keys(2, 5, keys_file, true);
         assert!(
             result.is_ok(),
             "Failed to generate keys with t=2, n=5: {:?}",
@@ -123,7 +2071,7 @@ mod tests {
     fn test_verify_signature_fail() {
         let keys_file = "./results/test_verify_signature_fail_frost_keys.json";
         let signature_file = "./results/test_verify_signature_fail_signature.json";
-        let result = generate_keys(3, 5, keys_file);
+        let result = generate_keys(3, 5, keys_file, true);
         assert!(
             result.is_ok(),
             "Failed to generate keys with t=3, n=5: {:?}",
@@ -144,4 +2092,38 @@ mod tests {
         remove_file(keys_file).unwrap();
         remove_file(signature_file).unwrap();
     }
+
+    #[test]
+    fn test_bench() {
+        let report = run_bench(2, 3, 2).unwrap();
+        let phases: Vec<_> = report
+            .phases
+            .iter()
+            .map(|timing| (timing.operation, timing.phase))
+            .collect();
+        assert_eq!(
+            phases,
+            vec![
+                ("generate", "participants"),
+                ("generate", "proofs of secret key"),
+                ("generate", "dkg round 1"),
+                ("generate", "dkg round 2"),
+                ("generate", "group commitments"),
+                ("generate", "save key file"),
+                ("sign", "load key file"),
+                ("sign", "signer keys"),
+                ("sign", "commitments"),
+                ("sign", "partial signatures"),
+                ("sign", "aggregation"),
+                ("sign", "save signature"),
+                ("verify", "load key file"),
+                ("verify", "load signature"),
+                ("verify", "verification"),
+            ]
+        );
+        assert!(report.phases.iter().all(|timing| timing.samples.len() == 2));
+        assert!(report.total("sign") >= report.phases[9].mean());
+        assert!(report.to_string().contains("verify     total"));
+        assert!(run_bench(2, 3, 0).is_err());
+    }
 }
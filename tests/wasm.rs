@@ -0,0 +1,58 @@
+// WebAssembly tests for frost_cli::wasm, run in a headless runtime with
+// `wasm-pack test --node -- --features wasm`.
+#![cfg(all(target_arch = "wasm32", feature = "wasm"))]
+
+use frost_cli::policy::DEFAULT_CONTEXT;
+use frost_cli::rounds::{self, PartialSignature};
+use frost_cli::wasm::{commit, sign_share, verify};
+use frost_cli::{generate_key_set, public_package_of, share_of};
+use std::collections::BTreeMap;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+#[wasm_bindgen_test]
+fn test_wasm_signing_round() {
+    let frost_keys = generate_key_set(2, 3).unwrap();
+    let public_package = public_package_of(&frost_keys).unwrap();
+    let public_json = serde_json::to_string(&public_package).unwrap();
+    let shares: Vec<String> = [1, 3]
+        .iter()
+        .map(|&index| serde_json::to_string(&share_of(&frost_keys, index).unwrap()).unwrap())
+        .collect();
+    let message = "hi, this is a test";
+
+    // Round one: every browser-held share commits.
+    let mut nonces: Vec<_> = shares.iter().map(|share| commit(share).unwrap()).collect();
+    let commitments: Vec<serde_json::Value> = nonces
+        .iter()
+        .map(|nonces| serde_json::from_str(&nonces.commitment().unwrap()).unwrap())
+        .collect();
+    let commitments_json = serde_json::to_string(&commitments).unwrap();
+
+    // Round two: every share signs, and the nonces cannot sign again.
+    let mut partials = Vec::new();
+    for (share, nonces) in shares.iter().zip(nonces.iter_mut()) {
+        let partial = sign_share(share, nonces, message, &commitments_json).unwrap();
+        partials.push(serde_json::from_str::<PartialSignature>(&partial).unwrap());
+        assert!(nonces.used());
+        assert!(sign_share(share, nonces, message, &commitments_json).is_err());
+    }
+
+    let public_shares: BTreeMap<u32, [u8; 32]> = public_package
+        .public_shares
+        .iter()
+        .map(|&(share, index)| (index, share))
+        .collect();
+    let signature = rounds::aggregate(
+        &public_package.group_key,
+        message.as_bytes(),
+        DEFAULT_CONTEXT,
+        &serde_json::from_str::<Vec<_>>(&commitments_json).unwrap(),
+        &partials,
+        &public_shares,
+    )
+    .unwrap();
+    let signature_json = serde_json::to_string(&signature.to_vec()).unwrap();
+
+    verify(message, &public_json, &signature_json).unwrap();
+    assert!(verify("different message", &public_json, &signature_json).is_err());
+}